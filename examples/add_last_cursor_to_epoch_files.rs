@@ -82,6 +82,8 @@ async fn main() -> Result<(), anyhow::Error> {
             num_txs_scanned: json.num_txs_scanned,
             num_txs_touching_0_shared_objs: json.num_txs_touching_0_shared_objs,
             num_txs_touching_0_objs: json.num_txs_touching_0_objs,
+            pruned: false,
+            queried_at_ms: None,
             checkpoints: json.checkpoints,
         };
         
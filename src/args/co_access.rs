@@ -0,0 +1,40 @@
+use clap::Parser;
+
+/// Build the co-access graph of shared objects, i.e., which shared objects
+/// are touched together by the same TXs, report the most co-accessed objects,
+/// connected components and communities, and export the graph in the GraphML
+/// format
+#[derive(Parser, Debug)]
+#[command(author = "Roman Overko", version, about, long_about = None)]
+pub struct Args {
+    /// Which workspace to analyze. This should be a directory in the "data"
+    /// folder with the same name as used in `query-txs`
+    #[arg(short, long, default_value_t = String::from("workspace1"))]
+    pub workspace: String,
+
+    /// First epoch to analyze, inclusive
+    #[arg(short, long, default_value_t = 0)]
+    pub from_epoch: usize,
+
+    /// Last epoch to analyze, inclusive
+    #[arg(short, long, default_value_t = usize::MAX)]
+    pub to_epoch: usize,
+
+    /// Number of top co-accessed pairs and tuples of shared objects to report
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub top: usize,
+
+    /// Ignore edges between shared objects touched together by fewer TXs
+    /// when looking for components and communities, and in the exported graph
+    #[arg(short, long, default_value_t = 1)]
+    pub min_weight: u64,
+
+    /// Read epoch data from the SQLite database of the workspace
+    /// ("data/<WORKSPACE>.sqlite") instead of the epoch data files
+    #[arg(long, default_value_t = false)]
+    pub sqlite: bool,
+
+    /// Print detailed output
+    #[arg(short, long, default_value_t = false)]
+    pub verbose: bool,
+}
@@ -0,0 +1,37 @@
+use clap::Parser;
+use sui_sdk::types::base_types::ObjectID;
+
+use super::parse_object_id;
+
+/// Import epoch data files produced by `query-txs` into the SQLite database
+/// of the same workspace, so that they can be queried by object ID, TX digest
+/// and checkpoint, or list the TXs that touched a shared object
+#[derive(Parser, Debug)]
+#[command(author = "Roman Overko", version, about, long_about = None)]
+pub struct Args {
+    /// Which workspace to import. This should be a directory in the "data"
+    /// folder with the same name as used in `query-txs`
+    #[arg(short, long, default_value_t = String::from("workspace1"))]
+    pub workspace: String,
+
+    /// Also import incomplete epoch data files
+    #[arg(short, long, default_value_t = false)]
+    pub incomplete: bool,
+
+    /// Instead of importing, list the TXs that touched this shared object
+    /// between `--from-epoch` and `--to-epoch`, as stored in the database
+    #[arg(long, value_parser = parse_object_id)]
+    pub touching: Option<ObjectID>,
+
+    /// First epoch to list TXs of, inclusive
+    #[arg(short, long, default_value_t = 0, requires = "touching")]
+    pub from_epoch: usize,
+
+    /// Last epoch to list TXs of, inclusive
+    #[arg(short, long, default_value_t = usize::MAX, requires = "touching")]
+    pub to_epoch: usize,
+
+    /// Print detailed output
+    #[arg(short, long, default_value_t = false)]
+    pub verbose: bool,
+}
@@ -1,4 +1,10 @@
-use clap::Parser;
+use std::str::FromStr;
+
+use clap::{Parser, ValueEnum};
+use strum_macros::Display;
+use sui_sdk::types::base_types::ObjectID;
+
+use super::parse_object_id;
 
 /// Calculate metrics used to analyze shared objects and obtain a list of
 /// all shared object IDs for further analysis
@@ -15,7 +21,134 @@ pub struct Args {
           default_values_t = vec![1, 5, 10, 30, 60])]
     pub intervals: Vec<u64>,
 
+    /// Intervals (in seconds) to use for contention degree calculations over
+    /// wall-clock windows. Requires checkpoint timestamps in the epoch data
+    #[arg(long, num_args = 1.., value_delimiter = ',',
+          value_parser = clap::value_parser!(u64).range(1..))]
+    pub interval_secs: Vec<u64>,
+
+    /// Calculate contention degree and object touchability over sliding
+    /// windows of each interval moving by this many checkpoints, instead of
+    /// tumbling windows. Adds min/max/percentiles over windows to the results
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub stride: Option<u64>,
+
+    /// Metrics to calculate (comma-separated), each stored in its own file.
+    /// Metrics also selected by the options below are added to these, and
+    /// `density` always adds `lifetimes`, which it is resumed from
+    #[arg(long, value_enum, num_args = 1.., value_delimiter = ',',
+          default_values_t = vec![MetricKind::Density, MetricKind::Distributions, MetricKind::Concentration,
+              MetricKind::Lifetimes])]
+    pub metrics: Vec<MetricKind>,
+
+    /// Also calculate metrics counting unique TXs by their digests instead of
+    /// the numbers of TXs reported per checkpoint (same as adding `exact` to
+    /// `--metrics`). Requires full (not pruned) epoch data
+    #[arg(long, default_value_t = false)]
+    pub exact: bool,
+
+    /// Treat the epochs of the workspace as one continuous stream of
+    /// checkpoints: windows span epoch boundaries and count towards the epoch
+    /// they end in, and the window still open at the end of an epoch is
+    /// reported on its own. Always recalculates metrics of all epochs
+    #[arg(long, default_value_t = false)]
+    pub continuous: bool,
+
+    /// Shared objects to leave out of all metrics (comma-separated IDs), as
+    /// if TXs did not touch them. Requires full (not pruned) epoch data
+    #[arg(long, num_args = 1.., value_delimiter = ',', value_parser = parse_object_id)]
+    pub exclude_objects: Vec<ObjectID>,
+
+    /// Whether to include well-known system shared objects (e.g., Clock 0x6)
+    /// in metrics, exclude them, or calculate metrics of them only. Both
+    /// excluding them and keeping them only need TX digests, i.e., full (not
+    /// pruned) epoch data, and abort on pruned epochs
+    #[arg(long, value_enum, default_value_t = SystemObjects::Include)]
+    pub system_objects: SystemObjects,
+
+    /// Also calculate inter-arrival times and burstiness of TXs touching
+    /// this many shared objects touched by the most TXs (selects
+    /// `hot-objects`). Always recalculates metrics of all epochs
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub hot_objects: Option<u64>,
+
+    /// Also report read-mostly shared objects, i.e., those with a ratio of
+    /// mutable references to TXs touching them of at most this (0 for
+    /// read-only objects), and the density of each epoch without them
+    /// (selects `read-mostly`). Densities need full (not pruned) epoch data.
+    /// Always recalculates metrics of all epochs
+    #[arg(long, value_parser = parse_ratio)]
+    pub read_mostly: Option<f64>,
+
+    /// Also report single-writer candidates, i.e., shared objects mutated by
+    /// at least one and at most this many distinct senders, with the share of
+    /// TXs touching them (selects `single-writer`). Needs full (not pruned)
+    /// epoch data with senders. Always recalculates metrics of all epochs
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub single_writer: Option<u64>,
+
+    /// What-if scenario: recalculate metrics of all epochs as if some shared
+    /// objects were owned, and store them apart from the other results.
+    /// Takes comma-separated object or package IDs ("0x..."), type patterns
+    /// ("module.Name", where `*` matches anything; these need the data of
+    /// `query-obj`), or files with one of them per line. Requires full (not
+    /// pruned) epoch data. Only calculates `density`
+    #[arg(long, num_args = 1.., value_delimiter = ',',
+          conflicts_with_all = ["metrics", "exact", "hot_objects", "read_mostly", "single_writer"])]
+    pub treat_as_owned: Vec<String>,
+
+    /// Read epoch data from the SQLite database of the workspace
+    /// ("data/<WORKSPACE>.sqlite") instead of the epoch data files
+    #[arg(long, default_value_t = false)]
+    pub sqlite: bool,
+
+    /// Number of worker threads reading epoch data (and calculating metrics
+    /// of every epoch on its own where possible) ahead of the epoch being
+    /// processed, in parallel. Defaults to the number of available cores
+    #[arg(short, long)]
+    pub jobs: Option<usize>,
+
+    /// Recalculate metrics of all epochs instead of only those added since
+    /// the last run
+    #[arg(long, default_value_t = false)]
+    pub full: bool,
+
     /// Print detailed output
     #[arg(short, long, default_value_t = false)]
     pub verbose: bool,
 }
+
+/// Metrics to calculate: density (`epoch_density_data.json`, including
+/// conflict graphs), distributions, concentration, exact counts, lifetimes of
+/// shared objects (`shared_objects_set.json`), hot, read-mostly and
+/// single-writer objects, TXs touching the Clock and the State object of Pyth
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Display)]
+#[strum(serialize_all = "kebab-case")]
+pub enum MetricKind {
+    Density,
+    Distributions,
+    Concentration,
+    Exact,
+    Lifetimes,
+    HotObjects,
+    ReadMostly,
+    SingleWriter,
+    Clock,
+    PythState,
+}
+
+/// What to do with system shared objects: include, exclude, only
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum SystemObjects {
+    Include,
+    Exclude,
+    Only,
+}
+
+fn parse_ratio(ratio: &str) -> Result<f64, String> {
+    let ratio = f64::from_str(ratio).map_err(|err| err.to_string())?;
+    if !(0.0..=1.0).contains(&ratio) {
+        return Err(String::from("must be between 0 and 1"));
+    }
+    Ok(ratio)
+}
@@ -1,3 +1,15 @@
+use std::str::FromStr;
+
+use sui_sdk::types::base_types::ObjectID;
+
 pub mod query_txs;
 pub mod metrics;
 pub mod query_obj;
+pub mod import;
+pub mod workspace;
+pub mod simulate;
+pub mod co_access;
+
+fn parse_object_id(id: &str) -> Result<ObjectID, String> {
+    ObjectID::from_str(id).map_err(|err| err.to_string())
+}
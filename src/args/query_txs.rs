@@ -31,6 +31,17 @@ pub struct Args {
     #[arg(short = 's', long, default_value_t = 10)]
     pub retry_sleep: u64,
 
+    /// Store the epoch data file in the pruned (counts-only) format: keep
+    /// per-checkpoint, per-object counts of TXs and mutable references, but
+    /// drop TX digests
+    #[arg(long, default_value_t = false)]
+    pub pruned: bool,
+
+    /// Also store the epoch data in the SQLite database of the workspace
+    /// ("data/<WORKSPACE>.sqlite")
+    #[arg(long, default_value_t = false)]
+    pub sqlite: bool,
+
     /// Print detailed output
     #[arg(short, long, default_value_t = false)]
     pub verbose: bool,
@@ -0,0 +1,67 @@
+use std::str::FromStr;
+
+use clap::Parser;
+
+/// Simulate executing the TXs touching shared objects of every epoch on a
+/// number of virtual workers, respecting conflicts on shared objects, and
+/// report the makespan and utilisation of workers
+#[derive(Parser, Debug)]
+#[command(author = "Roman Overko", version, about, long_about = None)]
+pub struct Args {
+    /// Which workspace to simulate. This should be a directory in the "data"
+    /// folder with the same name as used in `query-txs`
+    #[arg(short, long, default_value_t = String::from("workspace1"))]
+    pub workspace: String,
+
+    /// First epoch to simulate, inclusive
+    #[arg(short, long, default_value_t = 0)]
+    pub from_epoch: usize,
+
+    /// Last epoch to simulate, inclusive
+    #[arg(short, long, default_value_t = usize::MAX)]
+    pub to_epoch: usize,
+
+    /// Numbers of workers to simulate
+    #[arg(short = 'n', long, num_args = 1.., value_delimiter = ',',
+          value_parser = clap::value_parser!(u64).range(1..),
+          default_values_t = vec![1, 2, 4, 8, 16, 32, 64])]
+    pub workers: Vec<u64>,
+
+    /// Cost of executing a TX; must be positive
+    #[arg(long, default_value_t = 1.0, value_parser = parse_tx_cost)]
+    pub tx_cost: f64,
+
+    /// Additional cost of every shared object a TX touches
+    #[arg(long, default_value_t = 0.0, value_parser = parse_cost)]
+    pub obj_cost: f64,
+
+    /// Additional cost of every shared object a TX touches by mutable reference
+    #[arg(long, default_value_t = 0.0, value_parser = parse_cost)]
+    pub mut_obj_cost: f64,
+
+    /// Read epoch data from the SQLite database of the workspace
+    /// ("data/<WORKSPACE>.sqlite") instead of the epoch data files
+    #[arg(long, default_value_t = false)]
+    pub sqlite: bool,
+
+    /// Print detailed output
+    #[arg(short, long, default_value_t = false)]
+    pub verbose: bool,
+}
+
+fn parse_cost(cost: &str) -> Result<f64, String> {
+    let cost = f64::from_str(cost).map_err(|err| err.to_string())?;
+    if !cost.is_finite() || cost < 0.0 {
+        return Err(String::from("must be a non-negative number"));
+    }
+    Ok(cost)
+}
+
+// TXs without any cost would give a makespan of 0, and no speedup
+fn parse_tx_cost(cost: &str) -> Result<f64, String> {
+    let cost = parse_cost(cost)?;
+    if cost == 0.0 {
+        return Err(String::from("must be a positive number"));
+    }
+    Ok(cost)
+}
@@ -0,0 +1,70 @@
+use clap::{
+    Parser,
+    Subcommand,
+};
+
+/// Manage workspaces of epoch data files produced by `query-txs`:
+/// merge several workspaces into one, extract a range of epochs from a
+/// workspace into another one, or prune epoch data files to per-object counts
+#[derive(Parser, Debug)]
+#[command(author = "Roman Overko", version, about, long_about = None)]
+pub struct Args {
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Print detailed output
+    #[arg(short, long, default_value_t = false, global = true)]
+    pub verbose: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Merge several workspaces into one. For epochs present in more than one
+    /// workspace, the complete epoch data file is chosen, then the one with
+    /// more scanned TXs, then the full (not pruned) one, then the one already
+    /// in the workspace merged into or in the first of the sources
+    Merge {
+        /// Workspaces to merge. These should be directories in the "data"
+        /// folder
+        #[arg(short, long, num_args = 1.., value_delimiter = ',', required = true)]
+        sources: Vec<String>,
+
+        /// Workspace to merge into; created if it does not exist yet
+        #[arg(short, long)]
+        workspace: String,
+    },
+
+    /// Extract a range of epochs from a workspace into another workspace
+    Split {
+        /// Workspace to extract epochs from. This should be a directory in
+        /// the "data" folder
+        #[arg(short, long, default_value_t = String::from("workspace1"))]
+        workspace: String,
+
+        /// Workspace to extract epochs into; created if it does not exist yet
+        #[arg(short, long)]
+        output: String,
+
+        /// First epoch to extract, inclusive
+        #[arg(short, long, default_value_t = 0)]
+        from_epoch: usize,
+
+        /// Last epoch to extract, inclusive
+        #[arg(short, long, default_value_t = usize::MAX)]
+        to_epoch: usize,
+    },
+
+    /// Prune epoch data files of a workspace, i.e., drop TX digests and keep
+    /// only per-object counts per checkpoint. Pruned files are enough for
+    /// `metrics`, but cannot be loaded by `import`
+    Prune {
+        /// Workspace to prune. This should be a directory in the "data" folder
+        #[arg(short, long, default_value_t = String::from("workspace1"))]
+        workspace: String,
+
+        /// Workspace to write pruned epoch data files into; created if it does
+        /// not exist yet. If not given, the workspace is pruned in place
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+}
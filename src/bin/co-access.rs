@@ -0,0 +1,64 @@
+use std::fs;
+use clap::Parser;
+use std::io::{
+    BufWriter,
+    Write,
+};
+use std::path::Path;
+use colored::Colorize;
+
+use sui_shared_objects::args::co_access::Args;
+use sui_shared_objects::coaccess::CoAccessGraph;
+use sui_shared_objects::workspace::source::EpochSource;
+use sui_shared_objects::consts::{
+    RESULTS_DIR,
+    CO_ACCESS_FILENAME,
+    CO_ACCESS_GRAPH_FILENAME,
+};
+
+fn main() -> Result<(), anyhow::Error> {
+    let args = Args::parse();
+
+    // epochs to analyze: which objects TXs touch together is only known for
+    // complete epoch data with TX digests
+    let source = EpochSource::open(&args.workspace, args.sqlite)?;
+    let selection = source.complete_epochs(args.from_epoch..=args.to_epoch)?;
+    for warning in selection.warnings.iter() {
+        println!("{}", warning.yellow());
+    }
+    let epochs = selection.epochs;
+
+    println!("{}", "\n --- Building the co-access graph --- \n".green());
+    let mut graph = CoAccessGraph::new(args.min_weight);
+    for (k, epoch) in epochs.iter().enumerate() {
+        print!("\rWorking on epoch {}...", format!("{}: {}/{}", epoch, k + 1, epochs.len()).blue());
+        let _ = std::io::stdout().flush();
+
+        let result = source.read_epoch(*epoch)?;
+        for checkpoint_data in result.checkpoints.values() {
+            graph.add_checkpoint(checkpoint_data);
+        }
+    }
+    println!();
+
+    let data = graph.data(args.top);
+    println!("{}", format!("\n{} shared objects, {} co-accessed pairs, {} components, {} communities \
+        of more than one object", data.num_objects, data.num_edges, data.components.len(),
+        data.communities.len()).green());
+    if args.verbose {
+        for pair in data.top_pairs.iter() {
+            println!("{:>10} {:?}", pair.num_txs, pair.objects);
+        }
+    }
+
+    // save results
+    let results_dir = Path::new(RESULTS_DIR).join(&args.workspace);
+    fs::create_dir_all(&results_dir)?;
+    fs::write(results_dir.join(CO_ACCESS_FILENAME), serde_json::to_string_pretty(&data)?)?;
+    let mut writer = BufWriter::new(fs::File::create(results_dir.join(CO_ACCESS_GRAPH_FILENAME))?);
+    graph.write_graphml(&mut writer)?;
+    writer.flush()?;
+
+    println!("{}", format!("\nSaved results to \"{}\"", results_dir.display()).green());
+    Ok(())
+}
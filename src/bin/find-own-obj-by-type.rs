@@ -132,7 +132,7 @@ async fn main() -> Result<(), anyhow::Error> {
     //      ...
     //      }
     // }
-    let mut result = ResultData {
+    let mut result: ResultData = ResultData {
         network: args.network.to_string(),
         version: sui.api_version().to_string(),
         epoch: args.epoch,
@@ -143,6 +143,8 @@ async fn main() -> Result<(), anyhow::Error> {
         num_txs_scanned: 0,
         num_txs_touching_0_shared_objs: 0,
         num_txs_touching_0_objs: 0,
+        pruned: false,
+        queried_at_ms: None,
         checkpoints: BTreeMap::new(),
     };
 
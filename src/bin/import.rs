@@ -0,0 +1,74 @@
+use clap::Parser;
+use std::io::Write;
+use colored::Colorize;
+
+use sui_sdk::types::base_types::ObjectID;
+
+use sui_shared_objects::args::import::Args;
+use sui_shared_objects::workspace::Workspace;
+use sui_shared_objects::db::{
+    db_path,
+    Database,
+};
+
+fn main() -> Result<(), anyhow::Error> {
+    let args = Args::parse();
+    if let Some(object_id) = args.touching {
+        return list_txs_touching(&args, &object_id);
+    }
+
+    let workspace = Workspace::open(&args.workspace)?;
+    let epochs = workspace.epochs();
+
+    let db_file = db_path(&args.workspace);
+    let mut db = Database::open(&db_file)?;
+    if args.verbose {
+        println!("{}", format!("Importing into database \"{}\"\n", db_file.display()).blue());
+    }
+
+    println!("{}", "\n --- Importing epoch data files --- \n".green());
+    let mut num_imported = 0;
+    for (k, epoch) in epochs.iter().enumerate() {
+        print!("\rWorking on epoch {}...", format!("{}: {}/{}", epoch, k + 1, epochs.len()).blue());
+        let _ = std::io::stdout().flush();
+
+        // skip incomplete epoch data files unless asked otherwise
+        if !workspace.manifest.epochs[epoch].complete && !args.incomplete {
+            println!("{}", format!("\nSkipping incomplete epoch data file for epoch {}", epoch).yellow());
+            continue;
+        }
+
+        // pruned epoch data files do not contain TX digests to import
+        if workspace.manifest.epochs[epoch].pruned {
+            println!("{}", format!("\nSkipping pruned epoch data file for epoch {}", epoch).yellow());
+            continue;
+        }
+
+        let result = workspace.open_epoch(*epoch)?;
+        db.write_epoch(&result)?;
+        num_imported += 1;
+    }
+    println!();
+
+    println!("{}", format!("Imported {} epoch data files into \"{}\"", num_imported, db_file.display()).green());
+    Ok(())
+}
+
+// Print the TXs that touched a shared object within a range of epochs, as
+// imported into the database before
+fn list_txs_touching(args: &Args, object_id: &ObjectID) -> Result<(), anyhow::Error> {
+    let db_file = db_path(&args.workspace);
+    if !db_file.exists() {
+        anyhow::bail!("No database \"{}\", run `import` first", db_file.display());
+    }
+    let db = Database::open(&db_file)?;
+    let accesses = db.txs_touching_object(object_id, args.from_epoch, args.to_epoch)?;
+    for access in accesses.iter() {
+        println!("{} {} {} {}", access.epoch, access.checkpoint, access.tx_digest,
+            if access.mutable { "mut" } else { "read" });
+    }
+    if args.verbose {
+        println!("{}", format!("\n{} TXs touched {}", accesses.len(), object_id).green());
+    }
+    Ok(())
+}
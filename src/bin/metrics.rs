@@ -1,11 +1,15 @@
 use std::fs;
-use memmap;
 use clap::Parser;
 use std::io::Write;
 use std::path::Path;
+use std::thread;
+use std::sync::Arc;
+use std::str::FromStr;
 use std::collections::{
-    HashSet,
     BTreeMap,
+    BTreeSet,
+    HashMap,
+    HashSet,
 };
 use serde_json;
 use colored::Colorize;
@@ -13,367 +17,378 @@ use colored::Colorize;
 
 use sui_shared_objects::args::metrics::*;
 use sui_shared_objects::types::*;
+use sui_shared_objects::metrics::MetricsConfig;
+use sui_shared_objects::metrics::clock::Clock;
+use sui_shared_objects::metrics::concentration::Concentration;
+use sui_shared_objects::metrics::density::Density;
+use sui_shared_objects::metrics::distributions::Distributions;
+use sui_shared_objects::metrics::exact::Exact;
+use sui_shared_objects::metrics::hot::HotObjects;
+use sui_shared_objects::metrics::lifetimes::Lifetimes;
+use sui_shared_objects::metrics::metric::{
+    process_epochs,
+    read_saved,
+    Metric,
+};
+use sui_shared_objects::metrics::pyth::PythState;
+use sui_shared_objects::metrics::read_mostly::ReadMostly;
+use sui_shared_objects::metrics::single_writer::SingleWriters;
+use sui_shared_objects::workspace::Workspace;
+use sui_shared_objects::db::{
+    db_path,
+    Database,
+};
+use sui_sdk::types::base_types::ObjectID;
 use sui_shared_objects::consts::{
-    DATA_DIR,
+    SYSTEM_OBJECTS,
     RESULTS_DIR,
-    PLOT_FILENAME,
-    SHARED_OBJECTS_SET_FILENAME,
+    SCENARIO_FILENAME,
+    SHARED_OBJECTS_DATA_FILENAME,
+    METRICS_STATE_FILENAME,
+    DEFAULT_HOT_OBJECTS,
+    DEFAULT_MAX_MUT_RATIO,
+    DEFAULT_MAX_MUT_SENDERS,
 };
 
 fn main() {
     let args = Args::parse();
 
-    let workspace_dir = Path::new(DATA_DIR).join(args.workspace.clone());
-    let mut epoch_data_files: Vec<_> = fs::read_dir(workspace_dir).
-        expect("Couldn't access directory!").
-        map(|f| f.unwrap()).
-        collect();
-    epoch_data_files.sort_by_key(|f| f.path());
-
-    let mut unique_shared_objects_per_epoch: BTreeMap<usize, HashSet<String>> = BTreeMap::new();
-    let mut unique_shared_objects_total = SharedObjectsSetData {
-        shared_objects: BTreeMap::new(),
+    // epochs to process, together with where to read their data from
+    let db = if args.sqlite {
+        Some(Database::open(db_path(&args.workspace)).unwrap())
+    } else {
+        None
     };
-    let mut epochs_data = EpochsData {
-        epochs: BTreeMap::new(),
+    let workspace = if args.sqlite {
+        None
+    } else {
+        Some(Workspace::open(&args.workspace).unwrap())
+    };
+    // describe every epoch, so that changes since the last run can be detected
+    let mut sources: BTreeMap<usize, ProcessedEpoch> = BTreeMap::new();
+    let mut complete: HashSet<usize> = HashSet::new();
+    match (&db, &workspace) {
+        (Some(db), _) => {
+            for epoch in db.epochs().unwrap() {
+                let header = db.read_header(epoch).unwrap();
+                sources.insert(epoch, ProcessedEpoch::from(&header));
+                if header.is_complete() {
+                    complete.insert(epoch);
+                }
+            }
+        },
+        (None, Some(workspace)) => {
+            for (epoch, entry) in workspace.manifest.epochs.iter() {
+                sources.insert(*epoch, ProcessedEpoch::from(entry));
+                if entry.complete {
+                    complete.insert(*epoch);
+                }
+            }
+        },
+        (None, None) => unreachable!(),
     };
 
-    let mut txs_touching_at_least_one_shared_obj_by_mut: HashSet<String> = HashSet::new();
-    let mut tx_shared_obj_count_per_checkpoint: BTreeMap<String, u64> = BTreeMap::new();
+    let results_dir = Path::new(RESULTS_DIR).join(&args.workspace);
 
-    // auxiliary variables to calculate contention level
-    let mut counts_per_interval: BTreeMap<u64, IntervalCounts> = args
-        .intervals
+    // packages and types of shared objects are known once `query-obj` has
+    // been run
+    let shared_objects_data = fs::read_to_string(results_dir.join(SHARED_OBJECTS_DATA_FILENAME))
+        .ok()
+        .and_then(|content| serde_json::from_str::<SharedObjectsData>(&content).ok());
+    let packages = shared_objects_data.as_ref().map(|data| Arc::new(data.shared_objects
         .iter()
-        .map(|i| (*i, IntervalCounts {
-            num_txs: 0,
-            unique_shared_objects: HashSet::new(),
-            unique_shared_objects_touched_by_more_than_1tx: HashSet::new(),
-        }))
-        .collect();
-
-    println!("{}", "\n --- Calculating epoch data --- \n".green());
-    for (k, epoch_data_file) in epoch_data_files.iter().enumerate() {
-        let epoch: usize = epoch_data_file.path().to_str().unwrap().split("=").collect::<Vec<_>>()[1][..3].parse().unwrap();
-
-        print!("\rWorking on epoch {}...", format!("{}: {}/{}", epoch, k, epoch_data_files.len() - 1).blue());
-        let _ = std::io::stdout().flush();
-
-        let file = fs::File::open(epoch_data_file.path())
-            .expect("File not found!");
-        let mmap = unsafe {memmap::Mmap::map(&file)}.unwrap();
-        let content = std::str::from_utf8(&mmap).unwrap();
-        let result: ResultData = serde_json::from_str(content).unwrap();
-
-        // ignore incomplete epoch data files
-        if result.num_txs_scanned != result.num_txs_in_epoch {
-            println!("{}", format!("\nIgnoring incomplete epoch data files starting from {:?}\n", epoch_data_file.path()).yellow());
-            break;
+        .map(|(obj_id, obj)| (*obj_id, obj.address.clone()))
+        .collect::<HashMap<_, _>>()));
+    let types = shared_objects_data.as_ref().map(|data| Arc::new(data.shared_objects
+        .iter()
+        .map(|(obj_id, obj)| (*obj_id, obj.type_.clone()))
+        .collect::<HashMap<_, _>>()));
+    if let Some(packages) = &packages {
+        if args.verbose {
+            println!("{}", format!("\nUsing packages of {} shared objects from \"{}\"", packages.len(),
+                SHARED_OBJECTS_DATA_FILENAME).blue());
         }
+    }
 
-        // insert a new value for key "epoch"
-        unique_shared_objects_per_epoch
-            .entry(epoch)
-            .or_insert(HashSet::new());
-        epochs_data
-            .epochs
-            .entry(epoch)
-            .or_insert(EpochData {
-                num_txs_total: 0,
-                num_txs_touching_shared_objs: 0,
-                num_txs_touching_at_least_one_shared_obj_by_mut: 0,
-                num_shared_objects_per_tx: 0.0,
-                density: 0.0,
-                density_mut: 0.0,
-                num_shared_objects_per_epoch: 0,
-                num_shared_objects_total: 0,
-                num_checkpoints: 0,
-                avg_interval_data: args.intervals.iter().map(|i| (*i, AvgIntervalData{
-                    contention_degree: 0.0,
-                    obj_touchability: 0.0,
-                })).collect(),
-            });
+    // shared objects to calculate metrics for
+    let excluded: BTreeSet<ObjectID> = args.exclude_objects.iter().copied().collect();
+    let system_objects: BTreeSet<ObjectID> = SYSTEM_OBJECTS
+        .iter()
+        .map(|(id, _)| ObjectID::from_str(id).unwrap())
+        .collect();
+    let object_filter = match args.system_objects {
+        SystemObjects::Include if excluded.is_empty() => ObjectFilter::All,
+        SystemObjects::Include => ObjectFilter::Exclude(excluded),
+        SystemObjects::Exclude => ObjectFilter::Exclude(&excluded | &system_objects),
+        SystemObjects::Only => ObjectFilter::Only(&system_objects - &excluded),
+    };
 
-        // Update the number of checkpoints
-        epochs_data
-            .epochs
-            .get_mut(&epoch)
-            .unwrap()
-            .num_checkpoints = result.end_checkpoint - result.start_checkpoint + 1;
+    // shared objects treated as owned in a what-if scenario
+    let treated_as_owned = (!args.treat_as_owned.is_empty()).then(|| {
+        args.treat_as_owned
+            .iter()
+            .map(|entry| OwnedSelector::parse(entry))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .and_then(|selectors| OwnedSelector::select(&selectors.concat(), shared_objects_data.as_ref()))
+            .unwrap_or_else(|err| {
+                println!("\n  {}: {:#}", "ERROR".red(), err);
+                std::process::exit(1);
+            })
+    });
+    let object_filter = match &treated_as_owned {
+        Some(objects) => {
+            println!("{}", format!("\nTreating {} shared objects as owned", objects.len()).green());
+            object_filter.without(objects)
+        },
+        None => object_filter,
+    };
+    if args.verbose && !object_filter.is_all() {
+        println!("{}", format!("\nCalculating metrics for shared objects: {:?}", object_filter).blue());
+    }
 
-        for (checkpoint, checkpoint_data) in result.checkpoints.into_iter() {
-            // Update the total number of TXs
-            epochs_data
-                .epochs
-                .get_mut(&epoch)
-                .unwrap()
-                .num_txs_total += checkpoint_data.num_txs_total;
+    let config = MetricsConfig {
+        intervals: args.intervals.clone(),
+        stride: args.stride,
+        interval_secs: args.interval_secs.clone(),
+        packages,
+        types,
+        continuous: args.continuous,
+        object_filter,
+    };
 
-            // Update the number of TXs touching shared objects
-            epochs_data
-                .epochs
-                .get_mut(&epoch)
-                .unwrap()
-                .num_txs_touching_shared_objs += checkpoint_data.num_txs_touching_shared_objs;
+    // metrics selected by `--metrics` and by the options selecting them, in
+    // the order of `MetricKind`
+    let mut kinds: BTreeSet<MetricKind> = args.metrics.iter().copied().collect();
+    kinds.extend(args.exact.then_some(MetricKind::Exact));
+    kinds.extend(args.hot_objects.map(|_| MetricKind::HotObjects));
+    kinds.extend(args.read_mostly.map(|_| MetricKind::ReadMostly));
+    kinds.extend(args.single_writer.map(|_| MetricKind::SingleWriter));
+    // density is resumed with the shared objects touched so far, which are
+    // taken from the result of lifetimes (see `Density::resume`)
+    if kinds.contains(&MetricKind::Density) {
+        kinds.insert(MetricKind::Lifetimes);
+    }
+    let names: Vec<String> = kinds.iter().map(|kind| kind.to_string()).collect();
+    let create_all = || -> Vec<Box<dyn Metric>> {
+        kinds.iter().map(|kind| create(*kind, &config, &args)).collect()
+    };
 
-            // Count the number of unique TXs touching shared objects per interval.
-            // It might look that this number will be the same for all intervals,
-            // however it is not because some will be re-initialized later
-            for interval in &args.intervals {
-                counts_per_interval
-                    .get_mut(&interval)
-                    .unwrap()
-                    .num_txs += checkpoint_data.num_txs_touching_shared_objs as u64; // TODO: tx_list.len() as u64;
-            }
+    let num_workers = args.jobs.unwrap_or_else(|| thread::available_parallelism().map_or(1, |n| n.get()));
 
-            for (obj_id, tx_list) in checkpoint_data.shared_objects.into_iter() {
-                // collect unique shared objects per epoch
-                unique_shared_objects_per_epoch
-                    .get_mut(&epoch)
-                    .unwrap()
-                    .insert(obj_id.clone());
-                
-                // for every interval, update the sets of unique shared objects
-                for interval in &args.intervals {
-                    counts_per_interval
-                        .get_mut(&interval)
-                        .unwrap()
-                        .unique_shared_objects.insert(obj_id.clone());
-                    if tx_list.len() > 1 {
-                        counts_per_interval
-                            .get_mut(&interval)
-                            .unwrap()
-                            .unique_shared_objects_touched_by_more_than_1tx.insert(obj_id.clone());
-                    }
-                }
+    // every worker thread reads epoch data on its own
+    let load = |epoch: usize| match &workspace {
+        Some(workspace) => workspace.open_epoch_file(epoch),
+        None => Ok(EpochFile::Full(Database::open(db_path(&args.workspace))?.read_epoch(epoch)?)),
+    };
 
-                // collect unique shared in the Sui network and some data about them
-                //
-                // insert the key if it does not exist yet
-                unique_shared_objects_total
-                    .shared_objects
-                    .entry(obj_id.clone())
-                    .or_insert(SharedObjectSetData {
-                        num_txs: 0,
-                        num_mut_refs: 0,
-                        first_touched_at_epoch: epoch,
-                        first_touched_at_checkpoint: checkpoint,
-                        first_touched_by_txs: tx_list.clone(),
-                    });
-                //
-                // update the total number of TXs that touched that object
-                unique_shared_objects_total
-                    .shared_objects
-                    .get_mut(&obj_id)
-                    .unwrap()
-                    .num_txs += tx_list.len();
-                //
-                // update the total number of how many times that object
-                // was passed by a mutable reference
-                for (tx_id, mut_ref) in tx_list.into_iter() {
-                    if mut_ref {
-                        unique_shared_objects_total
-                            .shared_objects
-                            .get_mut(&obj_id)
-                            .unwrap()
-                            .num_mut_refs += 1;
+    if results_dir.exists() {
+        if args.verbose {
+            println!("{}", format!("Workspace \"{}\" already exists\n", results_dir.display()).green());
+        }
+    } else {
+        fs::create_dir_all(results_dir.clone()).unwrap();
+        if args.verbose {
+            println!("{}", format!("Created new workspace \"{}\"\n", results_dir.display()).blue());
+        }
+    }
 
-                        // collect unique txs that touch at least one shared object by mut ref
-                        txs_touching_at_least_one_shared_obj_by_mut.insert(tx_id.clone());
-                    }
-                    // insert new entry with TX ID and set count to 0
-                    tx_shared_obj_count_per_checkpoint
-                        .entry(tx_id.clone())
-                        .or_insert(0);
-                    *tx_shared_obj_count_per_checkpoint
-                        .get_mut(&tx_id.clone())
-                        .unwrap() += 1;
-                } // end of iterating over TX list
-            } // end of iterating over objects within a checkpoint
+    // what-if scenarios only recalculate density of all epochs, and store it
+    // apart from the other results
+    if let Some(treated_as_owned) = treated_as_owned {
+        let epochs = complete_epochs(sources.keys().copied().collect(), &complete, &sources, &config);
+        let mut density = Density::new(&config);
+        calculate(&mut [&mut density], &epochs, num_workers, &config, load, args.verbose);
+        let scenario = ScenarioData {
+            treat_as_owned: args.treat_as_owned.clone(),
+            treated_as_owned,
+            epochs: density.into_data().epochs,
+        };
+        save(&results_dir, SCENARIO_FILENAME, serde_json::to_string_pretty(&scenario));
+        println!("{}", format!("Saved the scenario to \"{}\"", SCENARIO_FILENAME).green());
+        println!("{}", "Done!".green());
+        return;
+    }
 
-            // Update the average (accumulative) number of per TX
-            if tx_shared_obj_count_per_checkpoint.len() > 0 {
-                epochs_data
-                    .epochs
-                    .get_mut(&epoch)
-                    .unwrap()
-                    .num_shared_objects_per_tx += tx_shared_obj_count_per_checkpoint.values().sum::<u64>() as f64 / tx_shared_obj_count_per_checkpoint.len() as f64;
+    // windows carried over between epochs in continuous mode are not saved,
+    // so all epochs are recalculated then
+    let mut registered = create_all();
+    let resumed = if args.full || config.continuous {
+        None
+    } else {
+        resume(&results_dir, &config, &sources, &names, &mut registered)
+    };
+    let mut state = match resumed {
+        Some(state) => {
+            println!("{}", format!("\nResuming from metrics of {} epochs calculated before",
+                state.epochs.len()).green());
+            state
+        },
+        None => {
+            // metrics might have been resumed partially
+            registered = create_all();
+            MetricsState {
+                intervals: config.intervals.clone(),
+                stride: config.stride,
+                interval_secs: config.interval_secs.clone(),
+                packages_fingerprint: config.packages_fingerprint(),
+                continuous: config.continuous,
+                object_filter: config.object_filter.clone(),
+                metrics: Vec::new(),
+                epochs: BTreeMap::new(),
             }
-            // clear the map for the next checkpoint
-            tx_shared_obj_count_per_checkpoint.clear();
+        },
+    };
+    // results of metrics not selected are not updated anymore
+    state.metrics = names;
 
-            // Update the number of TXs touching at least one shared object by mut ref
-            epochs_data
-                .epochs
-                .get_mut(&epoch)
-                .unwrap()
-                .num_txs_touching_at_least_one_shared_obj_by_mut += txs_touching_at_least_one_shared_obj_by_mut.len();
-            // clear set for the next checkpoint
-            txs_touching_at_least_one_shared_obj_by_mut.clear();
+    // only process epochs not accounted for yet
+    let epochs: Vec<usize> = sources
+        .keys()
+        .filter(|epoch| !state.epochs.contains_key(epoch))
+        .copied()
+        .collect();
+    let epochs = complete_epochs(epochs, &complete, &sources, &config);
 
-            for interval in &args.intervals {
-                // do this every `interval` checkpoints
-                if (checkpoint - result.start_checkpoint as u64 + 1) % interval == 0 {
-                    // Calculate contention degree as the number of TXs touching shared
-                    // objects divided by the number of unique touched shared objects
-                    let x: f64 = counts_per_interval.get(&interval).unwrap().num_txs as f64 / 
-                        counts_per_interval.get(&interval).unwrap().unique_shared_objects.len() as f64;
+    let mut metrics: Vec<&mut dyn Metric> = registered
+        .iter_mut()
+        .map(|metric| -> &mut dyn Metric { metric.as_mut() })
+        .collect();
+    calculate(&mut metrics, &epochs, num_workers, &config, load, args.verbose);
+    for epoch in epochs.iter() {
+        state.epochs.insert(*epoch, sources[epoch].clone());
+    }
 
-                    if !x.is_nan() {
-                        // Sum up interval contention degree to the epoch contention degree,
-                        // which will be averaged when epoch ends
-                        epochs_data
-                            .epochs
-                            .get_mut(&epoch)
-                            .unwrap()
-                            .avg_interval_data
-                            .get_mut(&interval)
-                            .unwrap() 
-                            .contention_degree
-                                += x;
-                    }
+    // save results
+    for metric in metrics.iter() {
+        save(&results_dir, metric.filename(), metric.serialize());
+    }
+    save(&results_dir, METRICS_STATE_FILENAME, serde_json::to_string_pretty(&state));
 
-                    // Calculate object touchability as the number of objects touched by
-                    // more than one TX divided by the number of shared objects
-                    let y: f64 = counts_per_interval.get(&interval).unwrap()
-                        .unique_shared_objects_touched_by_more_than_1tx.len() as f64 /
-                        counts_per_interval.get(&interval).unwrap().unique_shared_objects.len() as f64;
+    println!("{}", "Done!".green());
+}
 
-                    if !y.is_nan() {
-                        // Sum up interval object touchability to the epoch object touchability,
-                        // which will be averaged when epoch ends
-                        epochs_data
-                            .epochs
-                            .get_mut(&epoch)
-                            .unwrap()
-                            .avg_interval_data
-                            .get_mut(&interval)
-                            .unwrap() 
-                            .obj_touchability
-                                += y;
-                    }
+// Create a metric selected by `--metrics` (or the options selecting it)
+fn create(kind: MetricKind, config: &MetricsConfig, args: &Args) -> Box<dyn Metric> {
+    match kind {
+        MetricKind::Density => Box::new(Density::new(config)),
+        MetricKind::Distributions => Box::new(Distributions::new(config)),
+        MetricKind::Concentration => Box::new(Concentration::new(config)),
+        MetricKind::Exact => Box::new(Exact::new(config)),
+        MetricKind::Lifetimes => Box::new(Lifetimes::new()),
+        MetricKind::HotObjects => Box::new(HotObjects::new(args.hot_objects.unwrap_or(DEFAULT_HOT_OBJECTS)
+            as usize)),
+        MetricKind::ReadMostly => Box::new(ReadMostly::new(args.read_mostly.unwrap_or(DEFAULT_MAX_MUT_RATIO),
+            config.types.clone())),
+        MetricKind::SingleWriter => Box::new(SingleWriters::new(
+            args.single_writer.unwrap_or(DEFAULT_MAX_MUT_SENDERS) as usize, config.types.clone(),
+            config.packages.clone())),
+        MetricKind::Clock => Box::new(Clock::new()),
+        MetricKind::PythState => Box::new(PythState::new()),
+    }
+}
 
-                    // renew counters and clear sets needed for contention degree
-                    // and object touchability calculations for that interval
-                    counts_per_interval
-                        .get_mut(&interval)
-                        .unwrap()
-                        .num_txs = 0;
-                    counts_per_interval
-                        .get_mut(&interval)
-                        .unwrap()
-                        .unique_shared_objects.clear();
-                    counts_per_interval
-                        .get_mut(&interval)
-                        .unwrap()
-                        .unique_shared_objects_touched_by_more_than_1tx.clear();
-                } // end of if(checkpoint - result.start_checkpoint + 1 % interval == 0)
-            } // end of iterating over intervals
-        } // end of iterating over checkpoints
+// Leave out epochs following the first incomplete one, and make sure shared
+// objects can be filtered in all of them, i.e., none of them is pruned
+fn complete_epochs(mut epochs: Vec<usize>, complete: &HashSet<usize>, sources: &BTreeMap<usize, ProcessedEpoch>,
+        config: &MetricsConfig) -> Vec<usize> {
+    // ignore incomplete epoch data files
+    if let Some(k) = epochs.iter().position(|epoch| !complete.contains(epoch)) {
+        println!("{}", format!("\nIgnoring incomplete epoch data starting from epoch {}", epochs[k]).yellow());
+        epochs.truncate(k);
+    }
 
-        // total number of scanned TXs per epoch must be equal to the sum of TXs from
-        // all checkpoints for that epoch
-        assert_eq!(epochs_data.epochs.get(&epoch).unwrap().num_txs_total, result.num_txs_scanned); 
-        assert!(epochs_data.epochs.get(&epoch).unwrap().num_txs_touching_at_least_one_shared_obj_by_mut <=
-            epochs_data.epochs.get(&epoch).unwrap().num_txs_touching_shared_objs);
+    // shared objects can only be filtered given TX digests
+    let pruned: Vec<usize> = epochs.iter().filter(|epoch| sources[epoch].pruned).copied().collect();
+    if !config.object_filter.is_all() && !pruned.is_empty() {
+        println!("\n  {}: Epoch data of epochs {:?} is pruned, so shared objects cannot be filtered",
+            "ERROR".red(), pruned);
+        std::process::exit(1);
+    }
+    epochs
+}
 
-        // Calculate metrics per epoch
-        //
-        // average number of shared objects per transaction
-        epochs_data
-            .epochs
-            .get_mut(&epoch)
-            .unwrap()
-            .num_shared_objects_per_tx /= epochs_data.epochs.get(&epoch).unwrap().num_checkpoints as f64;
-        // Calculate density as the ratio of the number of TXs touching
-        // shared objects to the total number of TXs per epoch
-        epochs_data.epochs.get_mut(&epoch).unwrap().density = 
-            epochs_data.epochs.get(&epoch).unwrap().num_txs_touching_shared_objs as f64 /
-            epochs_data.epochs.get(&epoch).unwrap().num_txs_total as f64;
-        //
-        // Calculate mut-density as the ratio of the number of TXs touching
-        // at least one shared object by mut ref to the total number of TXs per epoch
-        epochs_data.epochs.get_mut(&epoch).unwrap().density_mut = 
-            epochs_data.epochs.get(&epoch).unwrap().num_txs_touching_at_least_one_shared_obj_by_mut as f64 /
-            epochs_data.epochs.get(&epoch).unwrap().num_txs_total as f64;
-        //
-        for interval in &args.intervals {
-            // Calculate contention degree as the sum of contention degrees
-            // for all intervals within that epoch divided by the number of intervals
-            epochs_data
-                .epochs
-                .get_mut(&epoch)
-                .unwrap()
-                .avg_interval_data
-                .get_mut(&interval)
-                .unwrap() 
-                .contention_degree
-                    /= epochs_data.epochs.get(&epoch).unwrap().num_checkpoints as f64 / *interval as f64;
-            //
-            // Calculate object touchebility as the sum of object touchabilities
-            // for all intervals within that epoch divided by the number of intervals
-            epochs_data
-                .epochs
-                .get_mut(&epoch)
-                .unwrap()
-                .avg_interval_data
-                .get_mut(&interval)
-                .unwrap() 
-                .obj_touchability
-                    /= epochs_data.epochs.get(&epoch).unwrap().num_checkpoints as f64 / *interval as f64;
-            //
-            // update contention degree counters for the next epoch
-            counts_per_interval
-                .get_mut(&interval)
-                .unwrap()
-                .num_txs = 0;
-            counts_per_interval
-                .get_mut(&interval)
-                .unwrap()
-                .unique_shared_objects.clear();
-            counts_per_interval
-                .get_mut(&interval)
-                .unwrap()
-                .unique_shared_objects_touched_by_more_than_1tx.clear();
-        }
-        //
-        // Calculate the number of unique shared objects per epoch
-        epochs_data
-            .epochs
-            .get_mut(&epoch)
-            .unwrap()
-            .num_shared_objects_per_epoch = unique_shared_objects_per_epoch
-                .get(&epoch)
-                .unwrap()
-                .len();
-        //
-        // Calculate the number of unique shared in the Sui network
-        epochs_data
-            .epochs
-            .get_mut(&epoch)
-            .unwrap()
-            .num_shared_objects_total = unique_shared_objects_total
-                .shared_objects
-                .len();
-    } // end of iteration over epoch data files
+// Feed epochs to all metrics in one pass, and print their warnings
+fn calculate<L>(metrics: &mut [&mut dyn Metric], epochs: &[usize], num_workers: usize, config: &MetricsConfig,
+        load: L, verbose: bool)
+where
+    L: Fn(usize) -> anyhow::Result<EpochFile> + Sync,
+{
+    let names: Vec<&str> = metrics.iter().map(|metric| metric.name()).collect();
+    if verbose {
+        println!("{}", format!("\nProcessing {} epochs, reading and calculating them in {} worker threads",
+            epochs.len(), num_workers).blue());
+    }
+    println!("{}", format!("\n --- Calculating {} --- \n", names.join(", ")).green());
+    let mut num_done = 0;
+    let result = process_epochs(metrics, epochs, num_workers, &config.object_filter, load, |epoch| {
+        num_done += 1;
+        print!("\rProcessed epoch {}...", format!("{}: {}/{}", epoch, num_done, epochs.len()).blue());
+        let _ = std::io::stdout().flush();
+    });
     println!();
+    if let Err(err) = result {
+        println!("\n  {}: {:#}", "ERROR".red(), err);
+        std::process::exit(1);
+    }
 
-    // save results 
-    let results_dir = Path::new(RESULTS_DIR).join(args.workspace);
-    if results_dir.exists() {
-        if args.verbose {
-            println!("{}", format!("Workspace \"{}\" already exists\n", results_dir.display()).green());
-        }
-    } else {
-        fs::create_dir_all(results_dir.clone()).unwrap();
-        if args.verbose {
-            println!("{}", format!("Created new workspace \"{}\"\n", results_dir.display()).blue());
+    for metric in metrics.iter_mut() {
+        metric.finish_workspace();
+        for warning in metric.warnings() {
+            println!("{}", warning.yellow());
         }
     }
-    let _ = fs::write(results_dir.join(PLOT_FILENAME), serde_json::to_string_pretty(&epochs_data).
-            unwrap());
-    let _ = fs::write(results_dir.join(SHARED_OBJECTS_SET_FILENAME), serde_json::to_string_pretty(&unique_shared_objects_total).
-            unwrap());
+}
 
-    println!("{}", "Done!".green());
+// Save a result in the results directory of the workspace
+fn save(results_dir: &Path, filename: &str, content: serde_json::Result<String>) {
+    let result = content
+        .map_err(anyhow::Error::from)
+        .and_then(|content| Ok(fs::write(results_dir.join(filename), content)?));
+    if let Err(err) = result {
+        println!("\n  {}: Couldn't save \"{}\": {:#}", "ERROR".red(), filename, err);
+        std::process::exit(1);
+    }
+}
+
+// Resume from the metrics saved by a previous run if they are still valid,
+// i.e., they were calculated for the same windows and shared objects (and
+// the same packages of shared objects, if known), all selected metrics were
+// saved for the same epochs, none of the epochs accounted for has changed
+// since, and no epoch was added before them (which would change first-touch
+// data of shared objects)
+fn resume(results_dir: &Path, config: &MetricsConfig, sources: &BTreeMap<usize, ProcessedEpoch>,
+        names: &[String], metrics: &mut [Box<dyn Metric>]) -> Option<MetricsState> {
+    // metrics saved by an older version lacking some of the metrics fail to
+    // parse, and are then calculated from scratch
+    let mut resumed = || -> anyhow::Result<Option<MetricsState>> {
+        let Some(state) = read_saved::<MetricsState>(results_dir, METRICS_STATE_FILENAME)? else {
+            return Ok(None);
+        };
+        if state.intervals != config.intervals || state.stride != config.stride
+                || state.interval_secs != config.interval_secs
+                || state.packages_fingerprint != config.packages_fingerprint()
+                || state.continuous != config.continuous || state.object_filter != config.object_filter
+                || names.iter().any(|name| !state.metrics.contains(name)) {
+            return Ok(None);
+        }
+        if state.epochs.iter().any(|(epoch, processed)| sources.get(epoch) != Some(processed)) {
+            return Ok(None);
+        }
+        if let Some(last) = state.epochs.keys().last() {
+            if sources.range(..last).any(|(epoch, _)| !state.epochs.contains_key(epoch)) {
+                return Ok(None);
+            }
+        }
+        for metric in metrics.iter_mut() {
+            let epochs = metric.resume(results_dir)?;
+            if !epochs.is_some_and(|epochs| epochs.iter().eq(state.epochs.keys())) {
+                return Ok(None);
+            }
+        }
+        Ok(Some(state))
+    };
+    resumed().unwrap_or_else(|err| {
+        println!("{}", format!("{:#}, recalculating metrics of all epochs", err).yellow());
+        None
+    })
 }
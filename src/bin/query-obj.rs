@@ -2,7 +2,6 @@ use std::fs;
 use memmap;
 use std::io::Write;
 use std::path::Path;
-use std::str::FromStr;
 use indexmap::IndexMap;
 use serde_json;
 use clap::Parser;
@@ -65,7 +64,7 @@ async fn main() -> Result<(), anyhow::Error> {
     // convert the set of shared object to a vector of shared object IDs
     let shared_objects_ids: Vec<ObjectID> = shared_objects_set_data.shared_objects
         .keys()
-        .map(|s| ObjectID::from_str(&s).unwrap())
+        .copied()
         .collect();
 
     // count the number of scanned objects
@@ -175,8 +174,7 @@ async fn main() -> Result<(), anyhow::Error> {
             if let SuiParsedData::MoveObject(sui_parsed_move_object) = sui_obj_data.content.as_ref().unwrap() {
                 // temporarily variables
                 let object_id = sui_obj_data
-                    .object_id
-                    .to_string();
+                    .object_id;
                 let address = sui_parsed_move_object
                     .type_
                     .address
@@ -216,7 +214,7 @@ async fn main() -> Result<(), anyhow::Error> {
                 // update shared objects data
                 shared_objects_data
                     .shared_objects
-                    .insert(object_id.clone(), SharedObjectData {
+                    .insert(object_id, SharedObjectData {
                         address: address.clone(),
                         type_: type_.clone(),
                         is_resource: is_resource,
@@ -395,7 +393,7 @@ async fn main() -> Result<(), anyhow::Error> {
     // sort shared objects data by num_txs in descending order
     let mut shared_objects_vec = Vec::from_iter(shared_objects_data.shared_objects);
     shared_objects_vec.sort_by(|(_, a), (_, b)| b.num_txs.cmp(&a.num_txs));
-    let sorted_shared_objects: IndexMap<String, SharedObjectData> = shared_objects_vec
+    let sorted_shared_objects: IndexMap<ObjectID, SharedObjectData> = shared_objects_vec
         .into_iter()
         .collect();
     shared_objects_data.shared_objects = sorted_shared_objects;
@@ -0,0 +1,86 @@
+use std::fs;
+use clap::Parser;
+use std::io::Write;
+use std::path::Path;
+use std::collections::BTreeMap;
+use colored::Colorize;
+
+use sui_shared_objects::args::simulate::Args;
+use sui_shared_objects::types::*;
+use sui_shared_objects::simulator::{
+    simulate_epoch,
+    SimulationCounts,
+};
+use sui_shared_objects::workspace::source::EpochSource;
+use sui_shared_objects::consts::{
+    RESULTS_DIR,
+    SIMULATION_FILENAME,
+};
+
+fn main() -> Result<(), anyhow::Error> {
+    let args = Args::parse();
+
+    let workers: Vec<usize> = args.workers.iter().map(|n| *n as usize).collect();
+    let cost_model = CostModel {
+        tx_cost: args.tx_cost,
+        obj_cost: args.obj_cost,
+        mut_obj_cost: args.mut_obj_cost,
+    };
+
+    // epochs to simulate: the conflicts between TXs are only known for
+    // complete epoch data with TX digests
+    let source = EpochSource::open(&args.workspace, args.sqlite)?;
+    let selection = source.complete_epochs(args.from_epoch..=args.to_epoch)?;
+    for warning in selection.warnings.iter() {
+        println!("{}", warning.yellow());
+    }
+    let epochs = selection.epochs;
+
+    if args.verbose {
+        println!("{}", format!("\nSimulating {} epochs on {:?} workers with {:?}", epochs.len(), workers,
+            cost_model).blue());
+    }
+
+    println!("{}", "\n --- Simulating epochs --- \n".green());
+    let mut epochs_data = BTreeMap::new();
+    let mut total: BTreeMap<usize, SimulationCounts> = BTreeMap::new();
+    for (k, epoch) in epochs.iter().enumerate() {
+        print!("\rWorking on epoch {}...", format!("{}: {}/{}", epoch, k + 1, epochs.len()).blue());
+        let _ = std::io::stdout().flush();
+
+        let result = source.read_epoch(*epoch)?;
+        let counts = simulate_epoch(&result, &workers, &cost_model);
+        for (num_workers, epoch_counts) in counts.iter() {
+            let data = epoch_counts.data(*num_workers);
+            total.entry(*num_workers).or_default().add(data.num_txs, data.work, data.makespan);
+        }
+        epochs_data.insert(*epoch, counts
+            .iter()
+            .map(|(num_workers, counts)| (*num_workers, counts.data(*num_workers)))
+            .collect());
+    }
+    println!();
+
+    let simulations = SimulationsData {
+        cost_model,
+        epochs: epochs_data,
+        total: total
+            .iter()
+            .map(|(num_workers, counts)| (*num_workers, counts.data(*num_workers)))
+            .collect(),
+    };
+
+    println!("{}", format!("\n{:>8} {:>14} {:>12} {:>8}", "workers", "makespan", "utilisation", "speedup").green());
+    for (num_workers, data) in simulations.total.iter() {
+        println!("{:>8} {:>14.2} {:>12.3} {:>8.2}", num_workers, data.makespan, data.utilisation, data.speedup);
+    }
+
+    // save results
+    let results_dir = Path::new(RESULTS_DIR).join(&args.workspace);
+    fs::create_dir_all(&results_dir)?;
+    let results_file = results_dir.join(SIMULATION_FILENAME);
+    fs::write(&results_file, serde_json::to_string_pretty(&simulations)?)?;
+
+    println!("{}", format!("\nSaved results to \"{}\"", results_file.display()).green());
+    Ok(())
+}
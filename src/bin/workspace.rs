@@ -0,0 +1,83 @@
+use clap::Parser;
+use colored::Colorize;
+
+use sui_shared_objects::args::workspace::{
+    Args,
+    Command,
+};
+use sui_shared_objects::workspace::{
+    MergedEpoch,
+    Workspace,
+};
+
+fn main() -> Result<(), anyhow::Error> {
+    let args = Args::parse();
+
+    let (workspace, merged) = match args.command {
+        Command::Merge { sources, workspace } => {
+            let sources = sources
+                .iter()
+                .map(|name| Workspace::open(name))
+                .collect::<Result<Vec<_>, _>>()?;
+            let network = sources
+                .iter()
+                .map(|source| source.manifest.network.clone())
+                .find(|network| !network.is_empty())
+                .unwrap_or_default();
+            let mut workspace = Workspace::open_or_create(&workspace, &network)?;
+            println!("{}", format!("\n --- Merging {} workspaces into \"{}\" --- \n",
+                sources.len(), workspace.dir().display()).green());
+            let merged = workspace.merge(&sources)?;
+            (workspace, merged)
+        },
+        Command::Split { workspace, output, from_epoch, to_epoch } => {
+            let source = Workspace::open(&workspace)?;
+            println!("{}", format!("\n --- Extracting epochs {}..={} from \"{}\" --- \n",
+                from_epoch, to_epoch, source.dir().display()).green());
+            let mut workspace = Workspace::open_or_create(&output, &source.manifest.network)?;
+            let merged = source.split(&mut workspace, from_epoch..=to_epoch)?;
+            (workspace, merged)
+        },
+        Command::Prune { workspace, output } => {
+            let mut workspace = match output {
+                Some(output) => {
+                    let source = Workspace::open(&workspace)?;
+                    let mut workspace = Workspace::open_or_create(&output, &source.manifest.network)?;
+                    source.split(&mut workspace, ..)?;
+                    workspace
+                },
+                None => Workspace::open(&workspace)?,
+            };
+            println!("{}", format!("\n --- Pruning epoch data files in \"{}\" --- \n",
+                workspace.dir().display()).green());
+            let epochs = workspace.epochs();
+            for epoch in epochs {
+                if workspace.prune_epoch(epoch)? {
+                    if args.verbose {
+                        println!("Epoch {} pruned", epoch);
+                    }
+                } else if args.verbose {
+                    println!("Epoch {} already pruned", epoch);
+                }
+            }
+            (workspace, Vec::new())
+        },
+    };
+
+    for MergedEpoch { epoch, source, candidates } in merged.iter() {
+        if candidates.len() > 1 {
+            println!("{}", format!("Epoch {} found in workspaces {:?}, taking it from \"{}\"",
+                epoch, candidates, source).yellow());
+        } else if args.verbose {
+            println!("Epoch {} taken from \"{}\"", epoch, source);
+        }
+    }
+
+    let num_complete = workspace.manifest.epochs
+        .values()
+        .filter(|entry| entry.complete)
+        .count();
+    println!("{}", format!("Workspace \"{}\" now contains {} epochs ({} complete)",
+        workspace.dir().display(), workspace.manifest.epochs.len(), num_complete).green());
+    Ok(())
+}
@@ -0,0 +1,285 @@
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+use std::io::{
+    self,
+    Write,
+};
+
+use sui_sdk::types::base_types::{
+    ObjectID,
+    TransactionDigest,
+};
+
+use crate::types::{
+    CheckpointData,
+    CoAccessData,
+    CoAccessedObjects,
+    ObjectCluster,
+};
+use crate::utils::{
+    DisjointSets,
+    Interner,
+};
+
+// Maximum number of rounds of label propagation when detecting communities
+const MAX_LABEL_PROPAGATION_ROUNDS: usize = 100;
+
+// Co-access graph of shared objects: objects are nodes, and the edge between
+// two objects is weighted by the number of TXs touching both of them. Edges
+// lighter than `min_weight` are ignored when looking for components and
+// communities, and when exporting the graph
+pub struct CoAccessGraph {
+    min_weight: u64,
+    object_ids: Interner<ObjectID>,
+    // the number of TXs touching each object
+    num_txs_per_object: Vec<u64>,
+    // edges between objects, as pairs of interned IDs (smaller one first)
+    edges: HashMap<(u32, u32), u64>,
+    // sets of shared objects touched together by TXs touching more than one
+    // shared object, as sorted interned IDs
+    tuples: HashMap<Vec<u32>, u64>,
+    num_txs: u64,
+    num_txs_touching_more_than_1_shared_obj: u64,
+}
+
+impl CoAccessGraph {
+    pub fn new(min_weight: u64) -> Self {
+        CoAccessGraph {
+            min_weight,
+            object_ids: Interner::new(),
+            num_txs_per_object: Vec::new(),
+            edges: HashMap::new(),
+            tuples: HashMap::new(),
+            num_txs: 0,
+            num_txs_touching_more_than_1_shared_obj: 0,
+        }
+    }
+
+    pub fn add_checkpoint(&mut self, checkpoint_data: &CheckpointData) {
+        // shared objects touched by each TX
+        let mut txs: BTreeMap<&TransactionDigest, Vec<u32>> = BTreeMap::new();
+        for (obj_id, txs_touching_obj) in checkpoint_data.shared_objects.iter() {
+            let obj_index = self.object_ids.intern(obj_id);
+            if obj_index as usize == self.num_txs_per_object.len() {
+                self.num_txs_per_object.push(0);
+            }
+            self.num_txs_per_object[obj_index as usize] += txs_touching_obj.len() as u64;
+            for tx_digest in txs_touching_obj.keys() {
+                txs.entry(tx_digest).or_default().push(obj_index);
+            }
+        }
+
+        for (_, mut objects) in txs.into_iter() {
+            self.num_txs += 1;
+            if objects.len() < 2 {
+                continue;
+            }
+            self.num_txs_touching_more_than_1_shared_obj += 1;
+            objects.sort_unstable();
+            for (k, a) in objects.iter().enumerate() {
+                for b in objects[k + 1..].iter() {
+                    *self.edges.entry((*a, *b)).or_default() += 1;
+                }
+            }
+            *self.tuples.entry(objects).or_default() += 1;
+        }
+    }
+
+    fn num_objects(&self) -> usize {
+        self.object_ids.len()
+    }
+
+    // Edges not lighter than `min_weight`, in a deterministic order
+    fn heavy_edges(&self) -> Vec<((u32, u32), u64)> {
+        let mut edges: Vec<((u32, u32), u64)> = self.edges
+            .iter()
+            .filter(|(_, weight)| **weight >= self.min_weight)
+            .map(|(edge, weight)| (*edge, *weight))
+            .collect();
+        edges.sort_unstable();
+        edges
+    }
+
+    // Connected components, largest first. Every object belongs to exactly
+    // one of them, i.e., objects never touched together with others make
+    // components of their own
+    pub fn components(&self) -> Vec<Vec<u32>> {
+        let mut sets = DisjointSets::new(self.num_objects());
+        for ((a, b), _) in self.heavy_edges() {
+            sets.union(a as usize, b as usize);
+        }
+        let labels: Vec<usize> = (0..self.num_objects()).map(|obj| sets.find(obj)).collect();
+        clusters(&labels)
+    }
+
+    // Communities, largest first, detected by (weighted) label propagation:
+    // starting with every object in a community of its own, objects join the
+    // community they are most heavily connected to, until none of them moves
+    pub fn communities(&self) -> Vec<Vec<u32>> {
+        let mut neighbours: Vec<Vec<(usize, u64)>> = vec![Vec::new(); self.num_objects()];
+        for ((a, b), weight) in self.heavy_edges() {
+            neighbours[a as usize].push((b as usize, weight));
+            neighbours[b as usize].push((a as usize, weight));
+        }
+
+        let mut labels: Vec<usize> = (0..self.num_objects()).collect();
+        for _ in 0..MAX_LABEL_PROPAGATION_ROUNDS {
+            let mut changed = false;
+            for obj in 0..self.num_objects() {
+                let mut weights: BTreeMap<usize, u64> = BTreeMap::new();
+                for (neighbour, weight) in neighbours[obj].iter() {
+                    *weights.entry(labels[*neighbour]).or_default() += weight;
+                }
+                // ties are broken by the smallest label for the result to be
+                // deterministic
+                let best = weights
+                    .into_iter()
+                    .fold(None, |best: Option<(usize, u64)>, (label, weight)| match best {
+                        Some((_, best_weight)) if best_weight >= weight => best,
+                        _ => Some((label, weight)),
+                    });
+                if let Some((label, _)) = best {
+                    if label != labels[obj] {
+                        labels[obj] = label;
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        clusters(&labels)
+    }
+
+    fn object_ids(&self, objects: &[u32]) -> Vec<ObjectID> {
+        objects.iter().map(|obj| *self.object_ids.value(*obj)).collect()
+    }
+
+    // The `n` most heavily co-accessed pairs of objects
+    pub fn top_pairs(&self, n: usize) -> Vec<CoAccessedObjects> {
+        let pairs: Vec<(Vec<u32>, u64)> = self.edges
+            .iter()
+            .map(|((a, b), weight)| (vec![*a, *b], *weight))
+            .collect();
+        self.top(pairs, n)
+    }
+
+    // The `n` sets of objects most often touched together (and with no other
+    // shared object) by TXs
+    pub fn top_tuples(&self, n: usize) -> Vec<CoAccessedObjects> {
+        let tuples: Vec<(Vec<u32>, u64)> = self.tuples
+            .iter()
+            .map(|(objects, num_txs)| (objects.clone(), *num_txs))
+            .collect();
+        self.top(tuples, n)
+    }
+
+    fn top(&self, sets: Vec<(Vec<u32>, u64)>, n: usize) -> Vec<CoAccessedObjects> {
+        let mut sets: Vec<CoAccessedObjects> = sets
+            .into_iter()
+            .map(|(objects, num_txs)| {
+                let mut objects = self.object_ids(&objects);
+                objects.sort();
+                CoAccessedObjects {
+                    objects,
+                    num_txs,
+                }
+            })
+            .collect();
+        sets.sort_by(|a, b| b.num_txs.cmp(&a.num_txs).then_with(|| a.objects.cmp(&b.objects)));
+        sets.truncate(n);
+        sets
+    }
+
+    fn object_clusters(&self, clusters: &[Vec<u32>]) -> Vec<ObjectCluster> {
+        clusters
+            .iter()
+            .filter(|objects| objects.len() > 1)
+            .map(|objects| {
+                let mut objects = objects.clone();
+                objects.sort_by_key(|obj| std::cmp::Reverse(self.num_txs_per_object[*obj as usize]));
+                ObjectCluster {
+                    num_objects: objects.len(),
+                    num_txs: objects.iter().map(|obj| self.num_txs_per_object[*obj as usize]).sum(),
+                    objects: self.object_ids(&objects),
+                }
+            })
+            .collect()
+    }
+
+    // Summary of the graph with the `n` top pairs and tuples of objects; only
+    // components and communities of more than one object are listed
+    pub fn data(&self, n: usize) -> CoAccessData {
+        let components = self.components();
+        CoAccessData {
+            num_txs: self.num_txs,
+            num_txs_touching_more_than_1_shared_obj: self.num_txs_touching_more_than_1_shared_obj,
+            num_objects: self.num_objects(),
+            num_edges: self.edges.len(),
+            num_components: components.len(),
+            top_pairs: self.top_pairs(n),
+            top_tuples: self.top_tuples(n),
+            components: self.object_clusters(&components),
+            communities: self.object_clusters(&self.communities()),
+        }
+    }
+
+    // Export the graph in the GraphML format, with the number of TXs, the
+    // component and the community (indices into the lists of `data`) of
+    // every object, and the weight of every edge
+    pub fn write_graphml<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut component_of = vec![0; self.num_objects()];
+        for (k, objects) in self.components().iter().enumerate() {
+            for obj in objects.iter() {
+                component_of[*obj as usize] = k;
+            }
+        }
+        let mut community_of = vec![0; self.num_objects()];
+        for (k, objects) in self.communities().iter().enumerate() {
+            for obj in objects.iter() {
+                community_of[*obj as usize] = k;
+            }
+        }
+
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(writer, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+        writeln!(writer, r#"  <key id="num_txs" for="node" attr.name="num_txs" attr.type="long"/>"#)?;
+        writeln!(writer, r#"  <key id="component" for="node" attr.name="component" attr.type="long"/>"#)?;
+        writeln!(writer, r#"  <key id="community" for="node" attr.name="community" attr.type="long"/>"#)?;
+        writeln!(writer, r#"  <key id="weight" for="edge" attr.name="weight" attr.type="long"/>"#)?;
+        writeln!(writer, r#"  <graph id="co-access" edgedefault="undirected">"#)?;
+        for obj in 0..self.num_objects() {
+            writeln!(writer, r#"    <node id="{}">"#, self.object_ids.value(obj as u32))?;
+            writeln!(writer, r#"      <data key="num_txs">{}</data>"#, self.num_txs_per_object[obj])?;
+            writeln!(writer, r#"      <data key="component">{}</data>"#, component_of[obj])?;
+            writeln!(writer, r#"      <data key="community">{}</data>"#, community_of[obj])?;
+            writeln!(writer, r#"    </node>"#)?;
+        }
+        for ((a, b), weight) in self.heavy_edges() {
+            writeln!(writer, r#"    <edge source="{}" target="{}">"#, self.object_ids.value(a),
+                self.object_ids.value(b))?;
+            writeln!(writer, r#"      <data key="weight">{}</data>"#, weight)?;
+            writeln!(writer, r#"    </edge>"#)?;
+        }
+        writeln!(writer, r#"  </graph>"#)?;
+        writeln!(writer, r#"</graphml>"#)
+    }
+}
+
+// Group objects by their labels, largest groups first (ties broken by the
+// smallest object)
+fn clusters(labels: &[usize]) -> Vec<Vec<u32>> {
+    let mut clusters: HashMap<usize, Vec<u32>> = HashMap::new();
+    for (obj, label) in labels.iter().enumerate() {
+        clusters.entry(*label).or_default().push(obj as u32);
+    }
+    let mut clusters: Vec<Vec<u32>> = clusters.into_values().collect();
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a[0].cmp(&b[0])));
+    clusters
+}
+
+#[cfg(test)]
+mod tests;
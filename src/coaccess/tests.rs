@@ -0,0 +1,80 @@
+use super::CoAccessGraph;
+use crate::types::CoAccessedObjects;
+use crate::utils::fixtures::{
+    checkpoint_of_txs as checkpoint,
+    obj,
+};
+
+// - TXs 1 and 2 touch A and B, TX 3 touches A, B and C;
+// - TX 4 touches D and E, TX 5 touches F only.
+fn graph(min_weight: u64) -> CoAccessGraph {
+    let mut graph = CoAccessGraph::new(min_weight);
+    graph.add_checkpoint(&checkpoint(&[(1, &["0xa", "0xb"]), (2, &["0xa", "0xb"])]));
+    graph.add_checkpoint(&checkpoint(&[(3, &["0xa", "0xb", "0xc"]), (4, &["0xd", "0xe"]), (5, &["0xf"])]));
+    graph
+}
+
+#[test]
+fn top_pairs_and_tuples() {
+    let data = graph(1).data(2);
+    assert_eq!(data.num_txs, 5);
+    assert_eq!(data.num_txs_touching_more_than_1_shared_obj, 4);
+    assert_eq!(data.num_objects, 6);
+    // A-B, A-C, B-C and D-E
+    assert_eq!(data.num_edges, 4);
+    assert_eq!(data.top_pairs, vec![
+        CoAccessedObjects { objects: vec![obj("0xa"), obj("0xb")], num_txs: 3 },
+        CoAccessedObjects { objects: vec![obj("0xa"), obj("0xc")], num_txs: 1 },
+    ]);
+    assert_eq!(data.top_tuples, vec![
+        CoAccessedObjects { objects: vec![obj("0xa"), obj("0xb")], num_txs: 2 },
+        CoAccessedObjects { objects: vec![obj("0xa"), obj("0xb"), obj("0xc")], num_txs: 1 },
+    ]);
+}
+
+#[test]
+fn components() {
+    let data = graph(1).data(0);
+    // {A, B, C}, {D, E} and {F}
+    assert_eq!(data.num_components, 3);
+    assert_eq!(data.components.len(), 2);
+    assert_eq!(data.components[0].objects, vec![obj("0xa"), obj("0xb"), obj("0xc")]);
+    assert_eq!(data.components[0].num_txs, 3 + 3 + 1);
+    assert_eq!(data.components[1].objects, vec![obj("0xd"), obj("0xe")]);
+
+    // only A and B are touched together by at least two TXs
+    let data = graph(2).data(0);
+    assert_eq!(data.num_components, 5);
+    assert_eq!(data.components.len(), 1);
+    assert_eq!(data.components[0].num_objects, 2);
+}
+
+#[test]
+fn communities() {
+    // A and B, and C and D are touched together by three TXs each, while B
+    // and C by a single TX
+    let mut graph = CoAccessGraph::new(1);
+    graph.add_checkpoint(&checkpoint(&[
+        (1, &["0xa", "0xb"]), (2, &["0xa", "0xb"]), (3, &["0xa", "0xb"]),
+        (4, &["0xc", "0xd"]), (5, &["0xc", "0xd"]), (6, &["0xc", "0xd"]),
+        (7, &["0xb", "0xc"]),
+    ]));
+    let data = graph.data(0);
+    assert_eq!(data.num_components, 1);
+    assert_eq!(data.communities.len(), 2);
+    assert_eq!(data.communities[0].objects, vec![obj("0xb"), obj("0xa")]);
+    assert_eq!(data.communities[1].objects, vec![obj("0xc"), obj("0xd")]);
+}
+
+#[test]
+fn graphml() {
+    let mut output = Vec::new();
+    graph(2).write_graphml(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#));
+    assert_eq!(output.matches("<node ").count(), 6);
+    // only the edge between A and B is heavy enough
+    assert_eq!(output.matches("<edge ").count(), 1);
+    assert!(output.contains(&format!(r#"<edge source="{}" target="{}">"#, obj("0xa"), obj("0xb"))));
+    assert!(output.contains(r#"<data key="weight">3</data>"#));
+}
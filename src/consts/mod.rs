@@ -9,6 +9,18 @@ pub const QUERY_MAX_RESULT_LIMIT: usize = 50;
 /// Name of directory where the pre-processed data is stored
 pub const DATA_DIR: &str = "data";
 
+/// Extension of the SQLite database file kept next to a workspace in the
+/// "data" folder
+pub const SQLITE_FILE_EXTENSION: &str = "sqlite";
+
+/// Name of the manifest file describing the epoch data files of a workspace
+pub const WORKSPACE_MANIFEST_FILENAME: &str = "workspace.json";
+
+/// Version of the Sui Rust SDK this project is built with, recorded in the
+/// workspace manifest. Must be updated together with the revision the
+/// `sui-sdk` dependency is pinned to in `Cargo.toml`
+pub const SUI_SDK_VERSION: &str = "1.24.0";
+
 /// Name of directory where the results (figures) are stored
 pub const RESULTS_DIR: &str = "results";
 
@@ -21,6 +33,87 @@ pub const PLOT_FILENAME: &str = "epoch_density_data.json";
 /// Name of file where shared objects ID are stored as strings
 pub const SHARED_OBJECTS_SET_FILENAME: &str = "shared_objects_set.json";
 
+/// Name of file where metrics counting unique TXs per epoch are stored
+pub const EXACT_FILENAME: &str = "epoch_exact_data.json";
+
+/// Name of file where distributions of metrics per epoch are stored
+pub const DISTRIBUTIONS_FILENAME: &str = "epoch_distributions.json";
+
+/// Width of histogram bins of contention degree
+pub const CONTENTION_DEGREE_BIN_WIDTH: f64 = 0.25;
+
+/// Name of file where concentration of TXs over shared objects and packages
+/// per epoch is stored
+pub const CONCENTRATION_FILENAME: &str = "epoch_concentration_data.json";
+
+/// Numbers of the most touched shared objects (or packages) whose share of
+/// TXs is calculated
+pub const CONCENTRATION_TOP_K: [usize; 3] = [1, 10, 100];
+
+/// Name of file where inter-arrival times of TXs touching the most touched
+/// shared objects are stored
+pub const HOT_OBJECTS_FILENAME: &str = "hot_objects.json";
+
+/// Name of file where shared objects (almost) never mutated are stored
+pub const READ_MOSTLY_FILENAME: &str = "read_mostly_objects.json";
+
+/// Name of file where shared objects mutated by only a few senders are stored
+pub const SINGLE_WRITER_FILENAME: &str = "single_writer_objects.json";
+
+/// Name of file where TXs touching the Clock are stored
+pub const CLOCK_FILENAME: &str = "clock_object.json";
+
+/// Name of file where TXs touching the State object of Pyth are stored
+pub const PYTH_STATE_FILENAME: &str = "pyth_state_object.json";
+
+/// Number of hot objects reported if `hot-objects` is selected by
+/// `--metrics` without `--hot-objects`
+pub const DEFAULT_HOT_OBJECTS: u64 = 10;
+
+/// Largest ratio of mutable references of read-mostly objects if
+/// `read-mostly` is selected by `--metrics` without `--read-mostly`, i.e.,
+/// read-only objects
+pub const DEFAULT_MAX_MUT_RATIO: f64 = 0.0;
+
+/// Most senders mutating a single-writer candidate if `single-writer` is
+/// selected by `--metrics` without `--single-writer`
+pub const DEFAULT_MAX_MUT_SENDERS: u64 = 1;
+
+/// Name of file where metrics of a what-if scenario (`--treat-as-owned`) are
+/// stored
+pub const SCENARIO_FILENAME: &str = "scenario_epoch_density_data.json";
+
+/// Well-known system shared objects (ID and name), which behave very
+/// differently from shared objects of applications
+pub const SYSTEM_OBJECTS: [(&str, &str); 6] = [
+    ("0x5", "SuiSystemState"),
+    ("0x6", "Clock"),
+    ("0x7", "AuthenticatorState"),
+    ("0x8", "Random"),
+    ("0x9", "Bridge"),
+    ("0x403", "DenyList"),
+];
+
+/// ID of the Clock shared object
+pub const CLOCK_OBJECT: &str = "0x6";
+
+/// ID of the State shared object of the Pyth oracle
+pub const PYTH_STATE_OBJECT: &str = "0xf9ff3ef935ef6cdfb659a203bf2754cebeb63346e29114a535ea6f41315e5a3f";
+
+/// Name of file where `metrics` keeps its state between runs
+pub const METRICS_STATE_FILENAME: &str = "metrics_state.json";
+
+/// Name of file where results of the scheduler simulator are stored
+pub const SIMULATION_FILENAME: &str = "simulation.json";
+
+/// Name of file where the summary of the co-access graph of shared objects
+/// is stored
+pub const CO_ACCESS_FILENAME: &str = "co_access.json";
+
+/// Name of file where the co-access graph of shared objects is exported in
+/// the GraphML format
+pub const CO_ACCESS_GRAPH_FILENAME: &str = "co_access.graphml";
+
 /// Name of file where shared objects data is stored
 pub const SHARED_OBJECTS_DATA_FILENAME: &str = "shared_objects_data.json";
 
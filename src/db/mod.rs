@@ -0,0 +1,327 @@
+use std::collections::BTreeMap;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::str::FromStr;
+
+use anyhow::Context;
+use rusqlite::{
+    params,
+    Connection,
+    OptionalExtension,
+};
+use sui_sdk::types::base_types::{
+    ObjectID,
+    SuiAddress,
+    TransactionDigest,
+};
+
+use crate::consts::{
+    DATA_DIR,
+    SQLITE_FILE_EXTENSION,
+};
+use crate::types::{
+    CheckpointData,
+    EpochHeader,
+    ResultData,
+};
+
+// Tables and indexes of the SQLite storage backend:
+// - `epochs` mirrors the header of an epoch data file;
+// - `checkpoints` stores per-checkpoint TX counts and timestamps;
+// - `transactions` stores TXs touching at least one shared object (and
+//   their senders, if known);
+// - `shared_object_accesses` stores which TX touched which shared object
+//   (and whether by a mutable reference).
+// The primary key of `shared_object_accesses` starts with `object_id`, so it
+// also serves as the index by object ID.
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS epochs (
+        epoch                          INTEGER PRIMARY KEY,
+        network                        TEXT    NOT NULL,
+        version                        TEXT    NOT NULL,
+        start_checkpoint               INTEGER NOT NULL,
+        end_checkpoint                 INTEGER NOT NULL,
+        last_cursor                    TEXT    NOT NULL,
+        num_txs_in_epoch               INTEGER NOT NULL,
+        num_txs_scanned                INTEGER NOT NULL,
+        num_txs_touching_0_shared_objs INTEGER NOT NULL,
+        num_txs_touching_0_objs        INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS checkpoints (
+        checkpoint                   INTEGER PRIMARY KEY,
+        epoch                        INTEGER NOT NULL REFERENCES epochs(epoch),
+        num_txs_total                INTEGER NOT NULL,
+        num_txs_touching_shared_objs INTEGER NOT NULL,
+        timestamp_ms                 INTEGER
+    );
+    CREATE TABLE IF NOT EXISTS transactions (
+        digest     TEXT    PRIMARY KEY,
+        checkpoint INTEGER NOT NULL REFERENCES checkpoints(checkpoint),
+        sender     TEXT
+    );
+    CREATE TABLE IF NOT EXISTS shared_object_accesses (
+        object_id  TEXT    NOT NULL,
+        tx_digest  TEXT    NOT NULL REFERENCES transactions(digest),
+        checkpoint INTEGER NOT NULL REFERENCES checkpoints(checkpoint),
+        mutable    INTEGER NOT NULL,
+        PRIMARY KEY (object_id, tx_digest)
+    );
+    CREATE INDEX IF NOT EXISTS checkpoints_by_epoch
+        ON checkpoints(epoch);
+    CREATE INDEX IF NOT EXISTS transactions_by_checkpoint
+        ON transactions(checkpoint);
+    CREATE INDEX IF NOT EXISTS accesses_by_tx_digest
+        ON shared_object_accesses(tx_digest);
+    CREATE INDEX IF NOT EXISTS accesses_by_checkpoint
+        ON shared_object_accesses(checkpoint);
+";
+
+// A single access of a shared object by a TX, as stored in the database
+#[derive(Debug)]
+pub struct SharedObjAccess {
+    pub epoch: usize,
+    pub checkpoint: u64,
+    pub tx_digest: TransactionDigest,
+    pub mutable: bool,
+}
+
+// SQLite database holding the same data as the epoch data files of a workspace
+pub struct Database {
+    conn: Connection,
+}
+
+// Path of the database for a given workspace, i.e., `data/<workspace>.sqlite`.
+// The database is kept next to (not inside) the workspace directory so that
+// it is not mistaken for an epoch data file.
+pub fn db_path(workspace: &str) -> PathBuf {
+    Path::new(DATA_DIR).join(format!("{}.{}", workspace, SQLITE_FILE_EXTENSION))
+}
+
+impl Database {
+    // Open (or create) the database and make sure all tables exist
+    pub fn open<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let conn = Connection::open(path.as_ref())
+            .with_context(|| format!("Couldn't open database {:?}", path.as_ref()))?;
+        conn.execute_batch(SCHEMA)?;
+
+        // databases created before checkpoint timestamps were recorded
+        let has_timestamps: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('checkpoints') WHERE name = 'timestamp_ms'",
+            [], |row| row.get(0))?;
+        if !has_timestamps {
+            conn.execute("ALTER TABLE checkpoints ADD COLUMN timestamp_ms INTEGER", [])?;
+        }
+        // databases created before senders of TXs were recorded
+        let has_senders: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('transactions') WHERE name = 'sender'",
+            [], |row| row.get(0))?;
+        if !has_senders {
+            conn.execute("ALTER TABLE transactions ADD COLUMN sender TEXT", [])?;
+        }
+        Ok(Database { conn })
+    }
+
+    // Store data of an epoch, replacing anything stored for it before
+    // (e.g., by a previous incomplete scan)
+    pub fn write_epoch(&mut self, result: &ResultData) -> anyhow::Result<()> {
+        let tx = self.conn.transaction()?;
+
+        tx.execute(
+            "DELETE FROM shared_object_accesses WHERE checkpoint IN
+                (SELECT checkpoint FROM checkpoints WHERE epoch = ?1)",
+            params![result.epoch as i64],
+        )?;
+        tx.execute(
+            "DELETE FROM transactions WHERE checkpoint IN
+                (SELECT checkpoint FROM checkpoints WHERE epoch = ?1)",
+            params![result.epoch as i64],
+        )?;
+        tx.execute("DELETE FROM checkpoints WHERE epoch = ?1",
+            params![result.epoch as i64])?;
+        tx.execute(
+            "INSERT OR REPLACE INTO epochs VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                result.epoch as i64,
+                result.network,
+                result.version,
+                result.start_checkpoint as i64,
+                result.end_checkpoint as i64,
+                result.last_cursor,
+                result.num_txs_in_epoch as i64,
+                result.num_txs_scanned as i64,
+                result.num_txs_touching_0_shared_objs as i64,
+                result.num_txs_touching_0_objs as i64,
+            ],
+        )?;
+
+        {
+            let mut insert_checkpoint = tx.prepare(
+                "INSERT INTO checkpoints VALUES (?1, ?2, ?3, ?4, ?5)")?;
+            let mut insert_tx = tx.prepare(
+                "INSERT OR IGNORE INTO transactions VALUES (?1, ?2, ?3)")?;
+            let mut insert_access = tx.prepare(
+                "INSERT OR REPLACE INTO shared_object_accesses VALUES (?1, ?2, ?3, ?4)")?;
+
+            for (checkpoint, checkpoint_data) in result.checkpoints.iter() {
+                insert_checkpoint.execute(params![
+                    *checkpoint as i64,
+                    result.epoch as i64,
+                    checkpoint_data.num_txs_total as i64,
+                    checkpoint_data.num_txs_touching_shared_objs as i64,
+                    checkpoint_data.timestamp_ms.map(|t| t as i64),
+                ])?;
+                for (obj_id, tx_list) in checkpoint_data.shared_objects.iter() {
+                    let obj_id = obj_id.to_string();
+                    for (tx_id, mutable) in tx_list.iter() {
+                        let sender = checkpoint_data.senders.get(tx_id).map(|sender| sender.to_string());
+                        let tx_id = tx_id.to_string();
+                        insert_tx.execute(params![tx_id, *checkpoint as i64, sender])?;
+                        insert_access.execute(params![obj_id, tx_id, *checkpoint as i64, mutable])?;
+                    }
+                }
+            }
+        }
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    // List epochs stored in the database in ascending order
+    pub fn epochs(&self) -> anyhow::Result<Vec<usize>> {
+        let mut stmt = self.conn.prepare("SELECT epoch FROM epochs ORDER BY epoch")?;
+        let epochs = stmt
+            .query_map([], |row| row.get::<_, i64>(0))?
+            .map(|e| e.map(|e| e as usize))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(epochs)
+    }
+
+    // Read the header of an epoch, i.e., everything but the checkpoints
+    pub fn read_header(&self, epoch: usize) -> anyhow::Result<EpochHeader> {
+        self.conn
+            .query_row(
+                "SELECT network, version, start_checkpoint, end_checkpoint, last_cursor,
+                    num_txs_in_epoch, num_txs_scanned, num_txs_touching_0_shared_objs,
+                    num_txs_touching_0_objs
+                FROM epochs WHERE epoch = ?1",
+                params![epoch as i64],
+                |row| Ok(EpochHeader {
+                    network: row.get(0)?,
+                    version: row.get(1)?,
+                    epoch,
+                    start_checkpoint: row.get::<_, i64>(2)? as usize,
+                    end_checkpoint: row.get::<_, i64>(3)? as usize,
+                    last_cursor: row.get(4)?,
+                    num_txs_in_epoch: row.get::<_, i64>(5)? as usize,
+                    num_txs_scanned: row.get::<_, i64>(6)? as usize,
+                    num_txs_touching_0_shared_objs: row.get::<_, i64>(7)? as usize,
+                    num_txs_touching_0_objs: row.get::<_, i64>(8)? as usize,
+                    pruned: false,
+                    queried_at_ms: None,
+                }),
+            )
+            .optional()?
+            .with_context(|| format!("Epoch {} not found in the database", epoch))
+    }
+
+    // Read data of an epoch back in the same structure as an epoch data file
+    pub fn read_epoch(&self, epoch: usize) -> anyhow::Result<ResultData> {
+        let header = self.read_header(epoch)?;
+        let mut result = ResultData {
+            network: header.network,
+            version: header.version,
+            epoch,
+            start_checkpoint: header.start_checkpoint,
+            end_checkpoint: header.end_checkpoint,
+            last_cursor: header.last_cursor,
+            num_txs_in_epoch: header.num_txs_in_epoch,
+            num_txs_scanned: header.num_txs_scanned,
+            num_txs_touching_0_shared_objs: header.num_txs_touching_0_shared_objs,
+            num_txs_touching_0_objs: header.num_txs_touching_0_objs,
+            pruned: false,
+            queried_at_ms: None,
+            checkpoints: BTreeMap::new(),
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT checkpoint, num_txs_total, num_txs_touching_shared_objs, timestamp_ms
+            FROM checkpoints WHERE epoch = ?1")?;
+        let mut rows = stmt.query(params![epoch as i64])?;
+        while let Some(row) = rows.next()? {
+            result.checkpoints.insert(row.get::<_, i64>(0)? as u64, CheckpointData {
+                num_txs_total: row.get::<_, i64>(1)? as usize,
+                num_txs_touching_shared_objs: row.get::<_, i64>(2)? as usize,
+                timestamp_ms: row.get::<_, Option<i64>>(3)?.map(|t| t as u64),
+                shared_objects: BTreeMap::new(),
+                senders: BTreeMap::new(),
+            });
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT a.checkpoint, a.object_id, a.tx_digest, a.mutable
+            FROM shared_object_accesses a
+            JOIN checkpoints c ON c.checkpoint = a.checkpoint
+            WHERE c.epoch = ?1")?;
+        let mut rows = stmt.query(params![epoch as i64])?;
+        while let Some(row) = rows.next()? {
+            let checkpoint = row.get::<_, i64>(0)? as u64;
+            let obj_id = ObjectID::from_str(&row.get::<_, String>(1)?)?;
+            let tx_id = TransactionDigest::from_str(&row.get::<_, String>(2)?)?;
+            result.checkpoints
+                .get_mut(&checkpoint)
+                .with_context(|| format!("Checkpoint {} not found in the database", checkpoint))?
+                .shared_objects
+                .entry(obj_id)
+                .or_default()
+                .insert(tx_id, row.get(3)?);
+        }
+
+        let mut stmt = self.conn.prepare(
+            "SELECT t.checkpoint, t.digest, t.sender
+            FROM transactions t
+            JOIN checkpoints c ON c.checkpoint = t.checkpoint
+            WHERE c.epoch = ?1 AND t.sender IS NOT NULL")?;
+        let mut rows = stmt.query(params![epoch as i64])?;
+        while let Some(row) = rows.next()? {
+            let checkpoint = row.get::<_, i64>(0)? as u64;
+            let tx_id = TransactionDigest::from_str(&row.get::<_, String>(1)?)?;
+            let sender = SuiAddress::from_str(&row.get::<_, String>(2)?)?;
+            result.checkpoints
+                .get_mut(&checkpoint)
+                .with_context(|| format!("Checkpoint {} not found in the database", checkpoint))?
+                .senders
+                .insert(tx_id, sender);
+        }
+
+        Ok(result)
+    }
+
+    // List all TXs that touched a given shared object within
+    // an (inclusive) range of epochs, ordered by checkpoint
+    pub fn txs_touching_object(&self, object_id: &ObjectID, from_epoch: usize, to_epoch: usize)
+            -> anyhow::Result<Vec<SharedObjAccess>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT c.epoch, a.checkpoint, a.tx_digest, a.mutable
+            FROM shared_object_accesses a
+            JOIN checkpoints c ON c.checkpoint = a.checkpoint
+            WHERE a.object_id = ?1 AND c.epoch BETWEEN ?2 AND ?3
+            ORDER BY a.checkpoint, a.tx_digest")?;
+        let mut rows = stmt.query(
+            params![object_id.to_string(), from_epoch as i64, to_epoch.min(i64::MAX as usize) as i64])?;
+        let mut accesses = Vec::new();
+        while let Some(row) = rows.next()? {
+            accesses.push(SharedObjAccess {
+                epoch: row.get::<_, i64>(0)? as usize,
+                checkpoint: row.get::<_, i64>(1)? as u64,
+                tx_digest: TransactionDigest::from_str(&row.get::<_, String>(2)?)?,
+                mutable: row.get(3)?,
+            });
+        }
+        Ok(accesses)
+    }
+}
+
+#[cfg(test)]
+mod tests;
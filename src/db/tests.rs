@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use sui_sdk::types::base_types::SuiAddress;
+
+use super::Database;
+use crate::types::ResultData;
+use crate::utils::fixtures::{
+    checkpoint,
+    obj,
+    result_data,
+    tx,
+};
+
+// Epoch 0 spans checkpoints 0..=1:
+// - checkpoint 0: TX 1 (sent by 0x1) writes A and reads B, TX 2 (sender
+//   unknown) reads A;
+// - checkpoint 1: no TXs touching shared objects.
+fn epoch0() -> ResultData {
+    let mut checkpoint0 = checkpoint(3, 2, &[("0xa", &[(1, true), (2, false)]), ("0xb", &[(1, false)])]);
+    checkpoint0.timestamp_ms = Some(1_700_000_000_000);
+    checkpoint0.senders = BTreeMap::from([(tx(1), SuiAddress::from_str("0x1").unwrap())]);
+    result_data(0, 0, vec![checkpoint0, checkpoint(1, 0, &[])])
+}
+
+// Epoch 1 spans checkpoints 2..=3, A is written by TX 3 and TX 4 in each
+fn epoch1() -> ResultData {
+    result_data(1, 2, vec![
+        checkpoint(1, 1, &[("0xa", &[(3, true)])]),
+        checkpoint(1, 1, &[("0xa", &[(4, false)])]),
+    ])
+}
+
+fn database() -> Database {
+    Database::open(":memory:").unwrap()
+}
+
+#[test]
+fn epochs_are_read_back_as_written() {
+    let mut db = database();
+    db.write_epoch(&epoch1()).unwrap();
+    db.write_epoch(&epoch0()).unwrap();
+    assert_eq!(db.epochs().unwrap(), vec![0, 1]);
+
+    // timestamps and senders are kept, checkpoints without timestamps or
+    // shared objects too
+    let result = db.read_epoch(0).unwrap();
+    assert_eq!(result, epoch0());
+    assert_eq!(result.checkpoints[&0].timestamp_ms, Some(1_700_000_000_000));
+    assert_eq!(result.checkpoints[&0].senders.len(), 1);
+    assert_eq!(db.read_epoch(1).unwrap(), epoch1());
+
+    let header = db.read_header(1).unwrap();
+    assert_eq!((header.start_checkpoint, header.end_checkpoint, header.num_txs_scanned), (2, 3, 2));
+    assert!(db.read_epoch(2).is_err());
+}
+
+#[test]
+fn incomplete_epoch_is_rewritten() {
+    let mut db = database();
+    // a scan interrupted after checkpoint 0, with TX 2 not seen yet
+    let mut incomplete = epoch0();
+    incomplete.checkpoints.remove(&1);
+    incomplete.checkpoints.get_mut(&0).unwrap().shared_objects.get_mut(&obj("0xa")).unwrap().remove(&tx(2));
+    incomplete.num_txs_scanned = 3;
+    incomplete.last_cursor = String::from("incomplete");
+    db.write_epoch(&incomplete).unwrap();
+    assert!(!db.read_header(0).unwrap().is_complete());
+
+    // nothing of the previous scan is left over
+    db.write_epoch(&epoch0()).unwrap();
+    assert!(db.read_header(0).unwrap().is_complete());
+    assert_eq!(db.read_epoch(0).unwrap(), epoch0());
+
+    // nor of a previous scan touching other objects
+    let mut rescanned = epoch0();
+    rescanned.checkpoints.get_mut(&0).unwrap().shared_objects.remove(&obj("0xb"));
+    db.write_epoch(&rescanned).unwrap();
+    assert_eq!(db.read_epoch(0).unwrap(), rescanned);
+    assert!(db.txs_touching_object(&obj("0xb"), 0, 0).unwrap().is_empty());
+}
+
+#[test]
+fn txs_touching_object_within_epochs() {
+    let mut db = database();
+    db.write_epoch(&epoch0()).unwrap();
+    db.write_epoch(&epoch1()).unwrap();
+
+    let accesses = db.txs_touching_object(&obj("0xa"), 0, usize::MAX).unwrap();
+    let accesses: Vec<_> = accesses
+        .iter()
+        .map(|access| (access.epoch, access.checkpoint, access.tx_digest, access.mutable))
+        .collect();
+    assert_eq!(accesses, vec![
+        (0, 0, tx(1), true),
+        (0, 0, tx(2), false),
+        (1, 2, tx(3), true),
+        (1, 3, tx(4), false),
+    ]);
+
+    // both ends of the range are inclusive
+    let epochs = |from, to| -> Vec<usize> {
+        db.txs_touching_object(&obj("0xa"), from, to).unwrap().iter().map(|access| access.epoch).collect()
+    };
+    assert_eq!(epochs(1, 1), vec![1, 1]);
+    assert_eq!(epochs(0, 0), vec![0, 0]);
+    assert!(epochs(2, 5).is_empty());
+    assert_eq!(db.txs_touching_object(&obj("0xb"), 1, 1).unwrap().len(), 0);
+    assert_eq!(db.txs_touching_object(&obj("0xb"), 0, 1).unwrap().len(), 1);
+}
@@ -2,3 +2,8 @@ pub mod args;
 pub mod types;
 pub mod utils;
 pub mod consts;
+pub mod db;
+pub mod workspace;
+pub mod metrics;
+pub mod simulator;
+pub mod coaccess;
@@ -10,11 +10,14 @@ use std::fs;
 use std::io::Write;
 use std::path::Path;
 use std::str::FromStr;
+use std::time::{
+    SystemTime,
+    UNIX_EPOCH,
+};
 
 // external crates
 use clap::Parser;
 use colored::Colorize;
-use memmap;
 use serde_json;
 use sui_sdk::SuiClientBuilder;
 use sui_sdk::rpc_types::{
@@ -31,15 +34,27 @@ use tokio::time::{
 // workspace crates
 use sui_shared_objects::args::query_txs::Args;
 use sui_shared_objects::consts::{
+    DATA_DIR,
     EPOCH_TO_CHECKPOINTS_FILENAME,
     RESULTS_DIR,
 };
+use sui_shared_objects::db::{
+    db_path,
+    Database,
+};
 use sui_shared_objects::types::{
     CheckpointData,
+    EpochFile,
+    EpochHeader,
     EpochToCheckpointData,
+    PrunedResultData,
     ResultData,
 };
 use sui_shared_objects::utils::process_tx_inputs;
+use sui_shared_objects::workspace::{
+    epoch_file_name,
+    Workspace,
+};
 
 
 #[tokio::main]
@@ -109,19 +124,19 @@ async fn main() -> Result<(), anyhow::Error> {
     // Number of TXs left to scan
     let mut tx_to_scan = epoch_data.tx_number;
 
-    // Create workspace if it does not exist yet
-    let workspace_dir = Path::new("data").join(args.workspace);
-    if workspace_dir.exists() {
+    // Create workspace if it does not exist yet. Data from different
+    // networks must not be mixed in the same workspace
+    let workspace_exists = Path::new(DATA_DIR).join(&args.workspace).exists();
+    let mut workspace = Workspace::open_or_create(&args.workspace,
+        &args.network.to_string())?;
+    if workspace_exists {
         if args.verbose {
             println!("{}", format!("Workspace \"{}\" already exists\n",
-                    workspace_dir.display()).green());
-        }
-    } else {
-        fs::create_dir_all(workspace_dir.clone())?;
-        if args.verbose {
-            println!("{}", format!("Created new workspace \"{}\"\n",
-                    workspace_dir.display()).blue());
+                    workspace.dir().display()).green());
         }
+    } else if args.verbose {
+        println!("{}", format!("Created new workspace \"{}\"\n",
+                workspace.dir().display()).blue());
     }
 
     // Map (sorted by key) for storing data we are interested in.
@@ -153,26 +168,44 @@ async fn main() -> Result<(), anyhow::Error> {
         num_txs_scanned: 0,
         num_txs_touching_0_shared_objs: 0,
         num_txs_touching_0_objs: 0,
+        pruned: false,
+        queried_at_ms: None,
         checkpoints: BTreeMap::new(),
     };
 
-    // Epoch data file to save data
-    let epoch_data_file = workspace_dir.join(format!("epoch={:0>3}_{}-{}.json",
-        args.epoch,
-        epoch_data.start_checkpoint,
-        epoch_data.end_checkpoint
-    ));
+    // Epoch data file to save data: the one already recorded for this epoch
+    // in the workspace manifest, or a new one
+    let epoch_data_file_name = match workspace.manifest.epochs.get(&args.epoch) {
+        Some(entry) => entry.file.clone(),
+        None => epoch_file_name(
+            args.epoch,
+            epoch_data.start_checkpoint,
+            epoch_data.end_checkpoint
+        ),
+    };
+    let epoch_data_file = workspace.dir().join(&epoch_data_file_name);
+    // Counts of TXs scanned before, if the epoch data file is pruned
+    let mut pruned_result: Option<PrunedResultData> = None;
+
     // Check if the epoch data file already exists
     if epoch_data_file.exists() {
         println!("{}", format!("File \"{}\" already exists.",
                 epoch_data_file.display()).yellow());
 
-        // Read it
-        let mmap = unsafe {memmap::Mmap::map(&fs::File::open(
-                epoch_data_file.clone()
-        ).unwrap())}.unwrap();
-        let content = std::str::from_utf8(&mmap).unwrap();
-        result = serde_json::from_str(content).unwrap();
+        // Read it. TXs of a pruned epoch data file cannot be restored, so only
+        // its header is taken over, and TXs scanned now are added to its
+        // counts when saving
+        match workspace.open_epoch_file(args.epoch)? {
+            EpochFile::Full(full_result) => result = full_result,
+            EpochFile::Pruned(pruned) => {
+                result.last_cursor = pruned.last_cursor.clone();
+                result.num_txs_scanned = pruned.num_txs_scanned;
+                result.num_txs_touching_0_shared_objs =
+                    pruned.num_txs_touching_0_shared_objs;
+                result.num_txs_touching_0_objs = pruned.num_txs_touching_0_objs;
+                pruned_result = Some(pruned);
+            },
+        }
 
         // Check if this epoch data file is complete, i.e., if all TXs for
         // the epoch of interest were scanned
@@ -280,7 +313,9 @@ async fn main() -> Result<(), anyhow::Error> {
                     or_insert(CheckpointData {
                         num_txs_total: 0,
                         num_txs_touching_shared_objs: 0,
-                        shared_objects: BTreeMap::new()
+                        timestamp_ms: tx.timestamp_ms,
+                        shared_objects: BTreeMap::new(),
+                        senders: BTreeMap::new(),
                     });
                 result.checkpoints.
                     get_mut(&tx.checkpoint.unwrap_or_default()).
@@ -296,6 +331,11 @@ async fn main() -> Result<(), anyhow::Error> {
                         get_mut(&tx.checkpoint.unwrap_or_default()).
                         unwrap().
                         num_txs_touching_shared_objs += 1;
+                    result.checkpoints.
+                        get_mut(&tx.checkpoint.unwrap_or_default()).
+                        unwrap().
+                        senders.
+                        insert(tx.digest, tx_info.sender);
                     for shared_obj in tx_info.shared_objects.iter() {
                         // Insert a new shared object ID if it does not
                         // exist already
@@ -303,7 +343,7 @@ async fn main() -> Result<(), anyhow::Error> {
                             get_mut(&tx.checkpoint.unwrap_or_default()).
                             unwrap().
                             shared_objects.
-                            entry(shared_obj.id.clone()).
+                            entry(shared_obj.id).
                             or_insert(BTreeMap::new());
 
                         // Both checkpoint and shared object ID keys must now
@@ -315,7 +355,7 @@ async fn main() -> Result<(), anyhow::Error> {
                             .shared_objects
                             .get_mut(&shared_obj.id)
                             .unwrap()
-                            .entry(tx.digest.to_string())
+                            .entry(tx.digest)
                             .or_insert(shared_obj.mutable);
                     }
                 }
@@ -422,7 +462,9 @@ async fn main() -> Result<(), anyhow::Error> {
                 or_insert(CheckpointData {
                     num_txs_total: 0,
                     num_txs_touching_shared_objs: 0,
-                    shared_objects: BTreeMap::new()
+                    timestamp_ms: tx.timestamp_ms,
+                    shared_objects: BTreeMap::new(),
+                    senders: BTreeMap::new(),
                 });
             result.checkpoints.
                 get_mut(&tx.checkpoint.unwrap_or_default()).
@@ -438,6 +480,11 @@ async fn main() -> Result<(), anyhow::Error> {
                     get_mut(&tx.checkpoint.unwrap_or_default()).
                     unwrap().
                     num_txs_touching_shared_objs += 1;
+                result.checkpoints.
+                    get_mut(&tx.checkpoint.unwrap_or_default()).
+                    unwrap().
+                    senders.
+                    insert(tx.digest, tx_info.sender);
                 for shared_obj in tx_info.shared_objects.iter() {
                     // Insert a new shared object ID if it does not exist
                     // already
@@ -445,7 +492,7 @@ async fn main() -> Result<(), anyhow::Error> {
                         get_mut(&tx.checkpoint.unwrap_or_default()).
                         unwrap().
                         shared_objects.
-                        entry(shared_obj.id.clone()).
+                        entry(shared_obj.id).
                         or_insert(BTreeMap::new());
 
                     // Both checkpoint and shared object ID keys must now
@@ -457,7 +504,7 @@ async fn main() -> Result<(), anyhow::Error> {
                         .shared_objects
                         .get_mut(&shared_obj.id)
                         .unwrap()
-                        .entry(tx.digest.to_string())
+                        .entry(tx.digest)
                         .or_insert(shared_obj.mutable);
                 }
             }
@@ -495,10 +542,44 @@ async fn main() -> Result<(), anyhow::Error> {
     result.num_txs_scanned = tx_count;
     result.num_txs_touching_0_shared_objs = tx_0shared_count;
     result.num_txs_touching_0_objs = tx_0total_count;
+    // lets `workspace merge` prefer the most recently queried epoch data file
+    result.queried_at_ms = Some(SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_millis() as u64));
+
+    // Save data to disk, either in full or pruned (counts-only) format.
+    // Once pruned, an epoch data file stays pruned
+    let header = if args.pruned || pruned_result.is_some() {
+        let pruned = match pruned_result.as_ref() {
+            Some(previous) => result.prune_onto(previous),
+            None => result.prune(),
+        };
+        fs::write(epoch_data_file, serde_json::to_string_pretty(&pruned)
+            .unwrap())?;
+        EpochHeader::from(&pruned)
+    } else {
+        fs::write(epoch_data_file, serde_json::to_string_pretty(&result)
+            .unwrap())?;
+        EpochHeader::from(&result)
+    };
 
-    // Save data to disk
-    fs::write(epoch_data_file, serde_json::to_string_pretty(&result)
-        .unwrap())?;
+    // Record the epoch data file in the workspace manifest
+    workspace.add_epoch(&epoch_data_file_name, &header)?;
+    workspace.save()?;
+
+    // Save data to the SQLite database as well, if requested.
+    // The database stores TX digests, so pruned data cannot be saved there
+    if args.sqlite && header.pruned {
+        println!("{}", format!("Epoch {} is pruned, not saving it to the \
+                SQLite database", args.epoch).yellow());
+    } else if args.sqlite {
+        let db_file = db_path(&args.workspace);
+        Database::open(&db_file)?.write_epoch(&result)?;
+        if args.verbose {
+            println!("{}", format!("\nSaved epoch {} to database \"{}\"",
+                    args.epoch, db_file.display()).green());
+        }
+    }
 
     println!();
     if args.verbose {
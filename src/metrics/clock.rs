@@ -0,0 +1,118 @@
+use std::collections::{
+    BTreeSet,
+    HashSet,
+};
+use std::str::FromStr;
+
+use sui_sdk::types::base_types::{
+    ObjectID,
+    TransactionDigest,
+};
+
+use crate::types::{
+    CheckpointData,
+    ClockCheckpointData,
+    ClockData,
+    PrunedCheckpointData,
+};
+use crate::consts::{
+    CLOCK_FILENAME,
+    CLOCK_OBJECT,
+};
+use crate::metrics::metric::Metric;
+
+// TXs touching the Clock, and whether they touch other shared objects too,
+// which needs TX digests; epochs without them (pruned) are skipped
+#[derive(Debug)]
+pub struct Clock {
+    clock: ObjectID,
+    epoch: Option<usize>,
+    epochs_without_digests: BTreeSet<usize>,
+    data: ClockData,
+}
+
+impl Clock {
+    pub fn new() -> Self {
+        Clock {
+            clock: ObjectID::from_str(CLOCK_OBJECT).unwrap(),
+            epoch: None,
+            epochs_without_digests: BTreeSet::new(),
+            data: ClockData::default(),
+        }
+    }
+
+    pub fn data(&self) -> &ClockData {
+        &self.data
+    }
+}
+
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::new()
+    }
+}
+
+impl Metric for Clock {
+    fn name(&self) -> &'static str {
+        "clock"
+    }
+
+    fn filename(&self) -> &'static str {
+        CLOCK_FILENAME
+    }
+
+    fn begin_epoch(&mut self, epoch: usize, _start_checkpoint: usize, _end_checkpoint: usize) {
+        self.epoch = Some(epoch);
+        self.data.num_txs_touching_only_clock_per_epoch.insert(epoch, 0);
+    }
+
+    fn observe_checkpoint(&mut self, checkpoint: u64, _checkpoint_data: &PrunedCheckpointData,
+            full_checkpoint_data: Option<&CheckpointData>) {
+        let epoch = self.epoch.expect("begin_epoch() must be called before observing checkpoints");
+        let Some(full_checkpoint_data) = full_checkpoint_data else {
+            if self.epochs_without_digests.insert(epoch) {
+                self.data.num_txs_touching_only_clock_per_epoch.remove(&epoch);
+            }
+            return;
+        };
+        let Some(txs) = full_checkpoint_data.shared_objects.get(&self.clock) else {
+            return;
+        };
+        self.data.num_txs += txs.len();
+        // TXs of the checkpoint touching shared objects other than the Clock
+        let txs_touching_others: HashSet<&TransactionDigest> = full_checkpoint_data.shared_objects
+            .iter()
+            .filter(|(obj_id, _)| **obj_id != self.clock)
+            .flat_map(|(_, txs)| txs.keys())
+            .collect();
+        for tx_digest in txs.keys() {
+            if txs_touching_others.contains(tx_digest) {
+                self.data.num_txs_touching_other_shared_objs += 1;
+            } else {
+                self.data.num_txs_touching_only_clock += 1;
+                if let Some(num_txs) = self.data.num_txs_touching_only_clock_per_epoch.get_mut(&epoch) {
+                    *num_txs += 1;
+                }
+            }
+        }
+        if self.data.peak_checkpoint.as_ref().is_none_or(|peak| txs.len() > peak.num_txs) {
+            self.data.peak_checkpoint = Some(ClockCheckpointData {
+                epoch,
+                checkpoint,
+                num_txs: txs.len(),
+                txs: txs.keys().copied().collect(),
+            });
+        }
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        if self.epochs_without_digests.is_empty() {
+            return Vec::new();
+        }
+        vec![format!("No TX digests in epochs {:?}, skipping them for the Clock", self.epochs_without_digests)]
+    }
+
+    fn serialize(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.data)
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::types::{
+    CheckpointData,
+    ConcentrationData,
+    ConcentrationsData,
+    EpochConcentrationData,
+    PrunedCheckpointData,
+};
+use crate::consts::CONCENTRATION_FILENAME;
+use crate::metrics::MetricsConfig;
+use crate::metrics::metric::{
+    downcast,
+    read_saved,
+    Metric,
+};
+
+// Concentration of TXs over shared objects within every epoch, and over
+// their packages if known, stored in `epoch_concentration_data.json`
+pub struct Concentration {
+    packages: Option<Arc<HashMap<ObjectID, String>>>,
+    epoch: Option<usize>,
+    // the number of TXs touching each shared object within the current epoch
+    txs_per_object: HashMap<ObjectID, u64>,
+    concentrations: ConcentrationsData,
+}
+
+impl Concentration {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Concentration {
+            packages: config.packages.clone(),
+            epoch: None,
+            txs_per_object: HashMap::new(),
+            concentrations: ConcentrationsData::default(),
+        }
+    }
+
+    pub fn data(&self) -> &ConcentrationsData {
+        &self.concentrations
+    }
+}
+
+impl Metric for Concentration {
+    fn name(&self) -> &'static str {
+        "concentration"
+    }
+
+    fn filename(&self) -> &'static str {
+        CONCENTRATION_FILENAME
+    }
+
+    fn begin_epoch(&mut self, epoch: usize, _start_checkpoint: usize, _end_checkpoint: usize) {
+        self.epoch = Some(epoch);
+        self.txs_per_object.clear();
+    }
+
+    fn observe_checkpoint(&mut self, _checkpoint: u64, checkpoint_data: &PrunedCheckpointData,
+            _full_checkpoint_data: Option<&CheckpointData>) {
+        for (obj_id, obj_counts) in checkpoint_data.shared_objects.iter() {
+            *self.txs_per_object.entry(*obj_id).or_default() += obj_counts.num_txs as u64;
+        }
+    }
+
+    // Objects without a known package (e.g., touched after `query-obj` was
+    // run) are left out of the concentration over packages
+    fn finish_epoch(&mut self) {
+        let epoch = self.epoch
+            .take()
+            .expect("begin_epoch() must be called before finish_epoch()");
+        let num_txs_per_object: Vec<u64> = self.txs_per_object.values().copied().collect();
+        let packages = self.packages.as_ref().map(|packages| {
+            let mut num_txs_per_package: HashMap<&String, u64> = HashMap::new();
+            for (obj_id, num_txs) in self.txs_per_object.iter() {
+                if let Some(package) = packages.get(obj_id) {
+                    *num_txs_per_package.entry(package).or_default() += num_txs;
+                }
+            }
+            num_txs_per_package.into_values().collect::<Vec<u64>>()
+        });
+        self.concentrations.epochs.insert(epoch, EpochConcentrationData {
+            objects: ConcentrationData::of(&num_txs_per_object),
+            packages: packages.and_then(|num_txs_per_package| ConcentrationData::of(&num_txs_per_package)),
+        });
+    }
+
+    fn fork(&self) -> Option<Box<dyn Metric>> {
+        Some(Box::new(Concentration {
+            packages: self.packages.clone(),
+            epoch: None,
+            txs_per_object: HashMap::new(),
+            concentrations: ConcentrationsData::default(),
+        }))
+    }
+
+    fn merge_epoch(&mut self, part: Box<dyn Metric>) {
+        self.concentrations.epochs.extend(downcast::<Concentration>(part).concentrations.epochs);
+    }
+
+    fn resume(&mut self, results_dir: &Path) -> anyhow::Result<Option<Vec<usize>>> {
+        let Some(concentrations) = read_saved::<ConcentrationsData>(results_dir, CONCENTRATION_FILENAME)? else {
+            return Ok(None);
+        };
+        self.concentrations = concentrations;
+        Ok(Some(self.concentrations.epochs.keys().copied().collect()))
+    }
+
+    fn serialize(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.concentrations)
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+
+use sui_sdk::types::base_types::TransactionDigest;
+
+use crate::types::{
+    CheckpointData,
+    ConflictGraphData,
+};
+use crate::metrics::stats;
+use crate::utils::DisjointSets;
+
+// Shared objects accessed by each TX touching shared objects within a
+// checkpoint, and whether by a mutable reference. Objects are indexed in the
+// order of their IDs. TXs are ordered by their digests, since the order in
+// which TXs were executed is not stored in epoch data files. This is an
+// assumption: the longest chain (and thus the speedup bound) of a conflict
+// graph depends on it, and is exact only for TXs that happened to execute in
+// the order of their digests
+pub fn tx_accesses(checkpoint_data: &CheckpointData) -> Vec<Vec<(usize, bool)>> {
+    let mut txs: BTreeMap<&TransactionDigest, Vec<(usize, bool)>> = BTreeMap::new();
+    for (obj_index, txs_touching_obj) in checkpoint_data.shared_objects.values().enumerate() {
+        for (tx_digest, mut_ref) in txs_touching_obj.iter() {
+            txs.entry(tx_digest).or_default().push((obj_index, *mut_ref));
+        }
+    }
+    txs.into_values().collect()
+}
+
+// Conflict graph of the TXs within a checkpoint: two TXs conflict if they
+// touch the same shared object and at least one of them writes it (passes it
+// by a mutable reference), i.e., they cannot be executed in parallel
+#[derive(Debug, Default, PartialEq)]
+pub struct ConflictGraph {
+    pub num_txs: usize,
+    // the longest chain of TXs each conflicting with the previous one, when
+    // every TX is executed as early as possible in the order of `tx_accesses`
+    pub longest_chain: usize,
+    // groups of TXs not conflicting with TXs of other groups
+    pub num_components: usize,
+}
+
+impl ConflictGraph {
+    pub fn of(checkpoint_data: &CheckpointData) -> Self {
+        ConflictGraph::of_accesses(&tx_accesses(checkpoint_data), checkpoint_data.shared_objects.len())
+    }
+
+    // Conflict graph of TXs given by their accesses to shared objects with
+    // any (e.g., interned) indices, such as TXs of several checkpoints
+    pub fn of_interned<'a, I: IntoIterator<Item = &'a Vec<(u32, bool)>>>(txs: I) -> Self {
+        let mut objects: HashMap<u32, usize> = HashMap::new();
+        let txs: Vec<Vec<(usize, bool)>> = txs
+            .into_iter()
+            .map(|accesses| accesses
+                .iter()
+                .map(|(obj, mut_ref)| {
+                    let num_objects = objects.len();
+                    (*objects.entry(*obj).or_insert(num_objects), *mut_ref)
+                })
+                .collect())
+            .collect();
+        ConflictGraph::of_accesses(&txs, objects.len())
+    }
+
+    // Conflict graph of TXs given by `tx_accesses` to `num_objects` shared objects
+    pub fn of_accesses(txs: &[Vec<(usize, bool)>], num_objects: usize) -> Self {
+
+        // A TX can run right after the last TX writing any of its objects,
+        // and, if it writes an object, after all TXs reading it since then.
+        // Levels of TXs are the lengths of the chains ending with them
+        let mut last_write_levels = vec![0; num_objects];
+        let mut last_read_levels = vec![0; num_objects];
+        let mut longest_chain = 0;
+        for accesses in txs.iter() {
+            let level = 1 + accesses
+                .iter()
+                .map(|(obj, mut_ref)| if *mut_ref {
+                    last_write_levels[*obj].max(last_read_levels[*obj])
+                } else {
+                    last_write_levels[*obj]
+                })
+                .max()
+                .unwrap_or(0);
+            for (obj, mut_ref) in accesses.iter() {
+                if *mut_ref {
+                    last_write_levels[*obj] = level;
+                    last_read_levels[*obj] = 0;
+                } else {
+                    last_read_levels[*obj] = last_read_levels[*obj].max(level);
+                }
+            }
+            longest_chain = longest_chain.max(level);
+        }
+
+        // All TXs touching a written object conflict with each other (through
+        // the TXs writing it), while TXs only reading an object do not
+        let mut components = DisjointSets::new(txs.len());
+        let mut txs_per_object: HashMap<usize, (Vec<usize>, bool)> = HashMap::new();
+        for (tx, accesses) in txs.iter().enumerate() {
+            for (obj, mut_ref) in accesses.iter() {
+                let (txs_touching_obj, written) = txs_per_object.entry(*obj).or_default();
+                txs_touching_obj.push(tx);
+                *written |= *mut_ref;
+            }
+        }
+        for (txs_touching_obj, written) in txs_per_object.values() {
+            if *written {
+                for tx in txs_touching_obj.iter().skip(1) {
+                    components.union(txs_touching_obj[0], *tx);
+                }
+            }
+        }
+
+        ConflictGraph {
+            num_txs: txs.len(),
+            longest_chain,
+            num_components: components.count(),
+        }
+    }
+
+    // Upper bound of the speedup of executing the TXs in parallel (on
+    // unlimited workers) over executing them one by one, 0 without TXs
+    pub fn speedup_bound(&self) -> f64 {
+        stats::ratio(self.num_txs, self.longest_chain)
+    }
+}
+
+// Aggregates conflict graphs of the checkpoints (or windows) of an epoch
+#[derive(Debug, Default)]
+pub struct ConflictGraphCounts {
+    // checkpoints (or windows) with TXs touching shared objects
+    num_checkpoints: usize,
+    num_txs: usize,
+    sum_longest_chains: usize,
+    max_longest_chain: usize,
+    sum_components: usize,
+    sum_speedup_bounds: f64,
+}
+
+impl ConflictGraphCounts {
+    pub fn add(&mut self, graph: &ConflictGraph) {
+        if graph.num_txs == 0 {
+            return;
+        }
+        self.num_checkpoints += 1;
+        self.num_txs += graph.num_txs;
+        self.sum_longest_chains += graph.longest_chain;
+        self.max_longest_chain = self.max_longest_chain.max(graph.longest_chain);
+        self.sum_components += graph.num_components;
+        self.sum_speedup_bounds += graph.speedup_bound();
+    }
+
+    // Metrics averaged over the checkpoints (or windows) with TXs
+    pub fn data(&self) -> ConflictGraphData {
+        self.data_over(self.num_checkpoints as f64)
+    }
+
+    // Metrics averaged over `num_windows` windows, including those without
+    // TXs (which might be fractional, see `WindowSeries::summarize`)
+    pub fn data_over(&self, num_windows: f64) -> ConflictGraphData {
+        if num_windows <= 0.0 {
+            return ConflictGraphData::default();
+        }
+        ConflictGraphData {
+            avg_longest_chain: self.sum_longest_chains as f64 / num_windows,
+            max_longest_chain: self.max_longest_chain,
+            avg_num_components: self.sum_components as f64 / num_windows,
+            avg_speedup_bound: self.sum_speedup_bounds / num_windows,
+            speedup_bound: stats::ratio(self.num_txs, self.sum_longest_chains),
+        }
+    }
+}
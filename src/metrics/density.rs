@@ -0,0 +1,328 @@
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+    HashSet,
+};
+use std::path::Path;
+
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::types::{
+    AvgIntervalData,
+    CheckpointData,
+    EpochData,
+    EpochsData,
+    PrunedCheckpointData,
+    SharedObjectsSetData,
+};
+use crate::consts::{
+    PLOT_FILENAME,
+    SHARED_OBJECTS_SET_FILENAME,
+};
+use crate::metrics::MetricsConfig;
+use crate::metrics::conflicts::{
+    tx_accesses,
+    ConflictGraph,
+    ConflictGraphCounts,
+};
+use crate::metrics::metric::{
+    downcast,
+    read_saved,
+    Metric,
+};
+use crate::metrics::stats;
+use crate::metrics::window::{
+    CheckpointTxs,
+    TouchedObject,
+    WindowCounts,
+    Windows,
+};
+use crate::utils::Interner;
+
+// Calculates metrics of shared objects stored in `epoch_density_data.json`
+// (density, mut-density, contention degree, object touchability, ...) from
+// the numbers of TXs reported per checkpoint, so that pruned epoch data files
+// can be used as well. Only conflict graphs need TX digests, so they are
+// left out for pruned epochs
+pub struct Density {
+    intervals: Vec<u64>,
+    interval_secs: Vec<u64>,
+    // epoch being processed
+    epoch: Option<usize>,
+    // shared object IDs touched so far are interned so that windows store and
+    // hash compact indices instead of full IDs
+    object_ids: Interner<ObjectID>,
+    // unique shared objects touched within the current epoch
+    objects_per_epoch: HashSet<u32>,
+    windows: Windows,
+    // conflict graphs of the checkpoints of the current epoch, unless a
+    // checkpoint has no TX digests
+    conflict_graphs: Option<ConflictGraphCounts>,
+    epochs_without_timestamps: BTreeSet<usize>,
+    epochs_data: EpochsData,
+}
+
+impl Density {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Density {
+            intervals: config.intervals.clone(),
+            interval_secs: config.interval_secs.clone(),
+            epoch: None,
+            object_ids: Interner::new(),
+            objects_per_epoch: HashSet::new(),
+            windows: Windows::new(config),
+            conflict_graphs: None,
+            epochs_without_timestamps: BTreeSet::new(),
+            epochs_data: EpochsData {
+                epochs: BTreeMap::new(),
+            },
+        }
+    }
+
+    pub fn data(&self) -> &EpochsData {
+        &self.epochs_data
+    }
+
+    pub fn into_data(self) -> EpochsData {
+        self.epochs_data
+    }
+}
+
+impl Metric for Density {
+    fn name(&self) -> &'static str {
+        "density"
+    }
+
+    fn filename(&self) -> &'static str {
+        PLOT_FILENAME
+    }
+
+    fn begin_epoch(&mut self, epoch: usize, start_checkpoint: usize, end_checkpoint: usize) {
+        self.epoch = Some(epoch);
+        self.objects_per_epoch.clear();
+        self.windows.begin_epoch(start_checkpoint, end_checkpoint);
+        self.conflict_graphs = Some(ConflictGraphCounts::default());
+        self.epochs_data.epochs.insert(epoch, EpochData {
+            num_txs_total: 0,
+            num_txs_touching_shared_objs: 0,
+            num_txs_touching_at_least_one_shared_obj_by_mut: 0,
+            num_shared_objects_per_tx: 0.0,
+            density: 0.0,
+            density_mut: 0.0,
+            num_shared_objects_per_epoch: 0,
+            num_shared_objects_total: 0,
+            num_checkpoints: end_checkpoint - start_checkpoint + 1,
+            num_read_read_collisions: 0,
+            num_read_write_collisions: 0,
+            num_write_write_collisions: 0,
+            conflict_graph: None,
+            avg_interval_data: self.intervals
+                .iter()
+                .map(|i| (*i, AvgIntervalData::default()))
+                .collect(),
+            avg_interval_secs_data: self.interval_secs
+                .iter()
+                .map(|i| (*i, AvgIntervalData::default()))
+                .collect(),
+        });
+    }
+
+    fn observe_checkpoint(&mut self, checkpoint: u64, checkpoint_data: &PrunedCheckpointData,
+            full_checkpoint_data: Option<&CheckpointData>) {
+        let epoch = self.epoch.expect("begin_epoch() must be called before observing checkpoints");
+        let epoch_data = self.epochs_data.epochs.get_mut(&epoch).unwrap();
+
+        // Update the total number of TXs
+        epoch_data.num_txs_total += checkpoint_data.num_txs_total;
+
+        // Update the number of TXs touching shared objects
+        epoch_data.num_txs_touching_shared_objs += checkpoint_data.num_txs_touching_shared_objs;
+
+        // Update the number of TXs touching at least one shared object by mut ref
+        epoch_data.num_txs_touching_at_least_one_shared_obj_by_mut
+            += checkpoint_data.num_txs_touching_at_least_one_shared_obj_by_mut;
+
+        // count the number of shared objects touched by each TX
+        let mut num_shared_obj_refs = 0;
+        // shared objects touched within the checkpoint
+        let mut touched_objects = Vec::with_capacity(checkpoint_data.shared_objects.len());
+
+        for (obj_id, obj_counts) in checkpoint_data.shared_objects.iter() {
+            let obj_index = self.object_ids.intern(obj_id);
+            num_shared_obj_refs += obj_counts.num_txs;
+
+            // collect unique shared objects per epoch
+            self.objects_per_epoch.insert(obj_index);
+            touched_objects.push(TouchedObject {
+                index: obj_index,
+                num_txs: obj_counts.num_txs,
+                num_mut_refs: obj_counts.num_mut_refs,
+            });
+
+            // Count pairs of TXs touching the same shared object within the
+            // checkpoint by the kind of their accesses: only pairs involving a
+            // mutable reference conflict, read-read pairs can run in parallel
+            let num_writes = obj_counts.num_mut_refs as u64;
+            let num_reads = obj_counts.num_txs as u64 - num_writes;
+            epoch_data.num_read_read_collisions += num_reads * num_reads.saturating_sub(1) / 2;
+            epoch_data.num_read_write_collisions += num_reads * num_writes;
+            epoch_data.num_write_write_collisions += num_writes * num_writes.saturating_sub(1) / 2;
+        }
+
+        // Update the average (accumulative) number of shared objects per TX
+        if checkpoint_data.num_txs_touching_shared_objs > 0 {
+            epoch_data.num_shared_objects_per_tx
+                += num_shared_obj_refs as f64 / checkpoint_data.num_txs_touching_shared_objs as f64;
+        }
+
+        // Conflict graphs of the checkpoint and of the windows it is in
+        let accesses = full_checkpoint_data.map(|full_checkpoint_data| {
+            let txs = tx_accesses(full_checkpoint_data);
+            if let Some(graphs) = self.conflict_graphs.as_mut() {
+                graphs.add(&ConflictGraph::of_accesses(&txs, full_checkpoint_data.shared_objects.len()));
+            }
+            let objects: Vec<u32> = full_checkpoint_data.shared_objects
+                .keys()
+                .map(|obj_id| self.object_ids.intern(obj_id))
+                .collect();
+            txs.into_iter()
+                .map(|accesses| accesses.into_iter().map(|(obj, mut_ref)| (objects[obj], mut_ref)).collect())
+                .collect::<Vec<Vec<(u32, bool)>>>()
+        });
+        if accesses.is_none() {
+            self.conflict_graphs = None;
+        }
+        let txs = accesses.as_deref().map(|accesses| CheckpointTxs {
+            accesses,
+            ..CheckpointTxs::default()
+        });
+
+        let num_txs = checkpoint_data.num_txs_touching_shared_objs as u64;
+        self.windows.push(checkpoint, checkpoint_data.timestamp_ms, num_txs, &touched_objects, txs);
+    }
+
+    // Calculate the metrics of the current epoch
+    fn finish_epoch(&mut self) {
+        let epoch = self.epoch
+            .take()
+            .expect("begin_epoch() must be called before finish_epoch()");
+        let epoch_data = self.epochs_data.epochs.get_mut(&epoch).unwrap();
+        let num_checkpoints = epoch_data.num_checkpoints as f64;
+
+        // average number of shared objects per transaction
+        if num_checkpoints > 0.0 {
+            epoch_data.num_shared_objects_per_tx /= num_checkpoints;
+        }
+
+        // Calculate density as the ratio of the number of TXs touching
+        // shared objects to the total number of TXs per epoch
+        epoch_data.density = stats::ratio(epoch_data.num_txs_touching_shared_objs, epoch_data.num_txs_total);
+
+        // Calculate mut-density as the ratio of the number of TXs touching
+        // at least one shared object by mut ref to the total number of TXs per epoch
+        epoch_data.density_mut = stats::ratio(epoch_data.num_txs_touching_at_least_one_shared_obj_by_mut,
+            epoch_data.num_txs_total);
+
+        // Calculate contention degree, object touchability and conflict
+        // graphs as the average over the windows of each interval within that
+        // epoch (see `Windows::intervals`); in continuous mode, the window
+        // still open is reported on its own
+        let has_digests = self.conflict_graphs.is_some();
+        epoch_data.conflict_graph = self.conflict_graphs.take().map(|graphs| graphs.data());
+        for (interval, summary) in self.windows.intervals(epoch_data.num_checkpoints) {
+            let avg_interval_data = epoch_data.avg_interval_data.get_mut(&interval).unwrap();
+            summary.series.summarize(avg_interval_data, summary.num_windows, summary.sliding);
+            avg_interval_data.conflict_graph = has_digests
+                .then(|| summary.series.conflict_graph(summary.num_windows))
+                .flatten();
+            avg_interval_data.num_windows = summary.num_finished;
+            avg_interval_data.partial_window = summary.open.and_then(WindowCounts::partial);
+        }
+        if self.windows.missing_timestamps() {
+            if !epoch_data.avg_interval_secs_data.is_empty() {
+                self.epochs_without_timestamps.insert(epoch);
+            }
+            epoch_data.avg_interval_secs_data.clear();
+        }
+        for (interval_secs, summary) in self.windows.intervals_secs() {
+            let Some(avg_interval_data) = epoch_data.avg_interval_secs_data.get_mut(&interval_secs) else {
+                continue;
+            };
+            summary.series.summarize(avg_interval_data, summary.num_windows, false);
+            avg_interval_data.conflict_graph = has_digests
+                .then(|| summary.series.conflict_graph(summary.num_windows))
+                .flatten();
+            avg_interval_data.num_windows = summary.num_finished;
+            avg_interval_data.partial_window = summary.open.and_then(WindowCounts::partial);
+        }
+
+        epoch_data.num_shared_objects_per_epoch = self.objects_per_epoch.len();
+        epoch_data.num_shared_objects_total = self.object_ids.len();
+    }
+
+    fn fork(&self) -> Option<Box<dyn Metric>> {
+        Some(Box::new(Density {
+            intervals: self.intervals.clone(),
+            interval_secs: self.interval_secs.clone(),
+            epoch: None,
+            object_ids: Interner::new(),
+            objects_per_epoch: HashSet::new(),
+            windows: self.windows.fork()?,
+            conflict_graphs: None,
+            epochs_without_timestamps: BTreeSet::new(),
+            epochs_data: EpochsData {
+                epochs: BTreeMap::new(),
+            },
+        }))
+    }
+
+    // The cumulative number of shared objects counts the objects of all
+    // epochs merged so far, so epochs have to be merged in ascending order
+    fn merge_epoch(&mut self, part: Box<dyn Metric>) {
+        let part = downcast::<Density>(part);
+        for obj_id in part.object_ids.values() {
+            self.object_ids.intern(obj_id);
+        }
+        for (epoch, mut epoch_data) in part.epochs_data.epochs.into_iter() {
+            epoch_data.num_shared_objects_total = self.object_ids.len();
+            self.epochs_data.epochs.insert(epoch, epoch_data);
+        }
+        self.epochs_without_timestamps.extend(part.epochs_without_timestamps);
+    }
+
+    // wall-clock intervals need checkpoint timestamps, which older epoch data
+    // files do not have
+    fn warnings(&self) -> Vec<String> {
+        if self.epochs_without_timestamps.is_empty() {
+            return Vec::new();
+        }
+        vec![format!("No checkpoint timestamps in epochs {:?}, skipping --interval-secs for them",
+            self.epochs_without_timestamps)]
+    }
+
+    // The cumulative number of shared objects needs all shared objects
+    // touched so far, which are taken from the result of `lifetimes`, so
+    // both have to be saved for the same epochs (`metrics` always calculates
+    // `lifetimes` along with density)
+    fn resume(&mut self, results_dir: &Path) -> anyhow::Result<Option<Vec<usize>>> {
+        let Some(epochs_data) = read_saved::<EpochsData>(results_dir, PLOT_FILENAME)? else {
+            return Ok(None);
+        };
+        let Some(shared_objects_set) = read_saved::<SharedObjectsSetData>(results_dir,
+                SHARED_OBJECTS_SET_FILENAME)? else {
+            return Ok(None);
+        };
+        if !shared_objects_set.epochs.keys().eq(epochs_data.epochs.keys()) {
+            return Ok(None);
+        }
+        for obj_id in shared_objects_set.shared_objects.keys() {
+            self.object_ids.intern(obj_id);
+        }
+        self.epochs_data = epochs_data;
+        Ok(Some(self.epochs_data.epochs.keys().copied().collect()))
+    }
+
+    fn serialize(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.epochs_data)
+    }
+}
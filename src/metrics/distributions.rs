@@ -0,0 +1,170 @@
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+use std::path::Path;
+
+use sui_sdk::types::base_types::{
+    ObjectID,
+    TransactionDigest,
+};
+
+use crate::types::{
+    CheckpointData,
+    DistributionsData,
+    EpochDistributionsData,
+    HistogramData,
+    PrunedCheckpointData,
+};
+use crate::consts::{
+    CONTENTION_DEGREE_BIN_WIDTH,
+    DISTRIBUTIONS_FILENAME,
+};
+use crate::metrics::MetricsConfig;
+use crate::metrics::metric::{
+    downcast,
+    read_saved,
+    Metric,
+};
+use crate::metrics::window::{
+    TouchedObject,
+    Windows,
+};
+use crate::utils::Interner;
+
+// Distributions of metrics within every epoch, stored in
+// `epoch_distributions.json`. Contention degree is calculated over the same
+// windows as in `Density`
+pub struct Distributions {
+    epoch: Option<(usize, usize)>,
+    object_ids: Interner<ObjectID>,
+    windows: Windows,
+    // value -> number of occurrences of the number of shared objects per TX
+    // (unless a checkpoint has no TX digests) and of TXs per object per
+    // checkpoint within the current epoch
+    shared_objects_per_tx: Option<BTreeMap<u64, u64>>,
+    txs_per_object_per_checkpoint: BTreeMap<u64, u64>,
+    distributions: DistributionsData,
+}
+
+impl Distributions {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Distributions {
+            epoch: None,
+            object_ids: Interner::new(),
+            windows: Windows::new(config),
+            shared_objects_per_tx: None,
+            txs_per_object_per_checkpoint: BTreeMap::new(),
+            distributions: DistributionsData::default(),
+        }
+    }
+
+    pub fn data(&self) -> &DistributionsData {
+        &self.distributions
+    }
+}
+
+impl Metric for Distributions {
+    fn name(&self) -> &'static str {
+        "distributions"
+    }
+
+    fn filename(&self) -> &'static str {
+        DISTRIBUTIONS_FILENAME
+    }
+
+    fn begin_epoch(&mut self, epoch: usize, start_checkpoint: usize, end_checkpoint: usize) {
+        self.epoch = Some((epoch, end_checkpoint - start_checkpoint + 1));
+        self.windows.begin_epoch(start_checkpoint, end_checkpoint);
+        self.shared_objects_per_tx = Some(BTreeMap::new());
+        self.txs_per_object_per_checkpoint.clear();
+    }
+
+    fn observe_checkpoint(&mut self, checkpoint: u64, checkpoint_data: &PrunedCheckpointData,
+            full_checkpoint_data: Option<&CheckpointData>) {
+        let mut touched_objects = Vec::with_capacity(checkpoint_data.shared_objects.len());
+        for (obj_id, obj_counts) in checkpoint_data.shared_objects.iter() {
+            *self.txs_per_object_per_checkpoint.entry(obj_counts.num_txs as u64).or_default() += 1;
+            touched_objects.push(TouchedObject {
+                index: self.object_ids.intern(obj_id),
+                num_txs: obj_counts.num_txs,
+                num_mut_refs: obj_counts.num_mut_refs,
+            });
+        }
+        let num_txs = checkpoint_data.num_txs_touching_shared_objs as u64;
+        self.windows.push(checkpoint, checkpoint_data.timestamp_ms, num_txs, &touched_objects, None);
+
+        // the number of shared objects per TX needs TX digests, which pruned
+        // checkpoints lack
+        match full_checkpoint_data {
+            Some(full_checkpoint_data) => if let Some(shared_objects_per_tx) = self.shared_objects_per_tx.as_mut() {
+                let mut num_objects_per_tx: HashMap<&TransactionDigest, u64> = HashMap::new();
+                for txs in full_checkpoint_data.shared_objects.values() {
+                    for tx_digest in txs.keys() {
+                        *num_objects_per_tx.entry(tx_digest).or_default() += 1;
+                    }
+                }
+                for num_objects in num_objects_per_tx.into_values() {
+                    *shared_objects_per_tx.entry(num_objects).or_default() += 1;
+                }
+            },
+            None => self.shared_objects_per_tx = None,
+        }
+    }
+
+    // Distributions of contention degree over the same windows as the
+    // averages of `Density`
+    fn finish_epoch(&mut self) {
+        let (epoch, num_checkpoints) = self.epoch
+            .take()
+            .expect("begin_epoch() must be called before finish_epoch()");
+        let contention_degree_histogram = |values: &[f64]| HistogramData::of_values(values,
+            CONTENTION_DEGREE_BIN_WIDTH);
+        let contention_degree_secs = if self.windows.missing_timestamps() {
+            BTreeMap::new()
+        } else {
+            self.windows
+                .intervals_secs()
+                .filter_map(|(interval_secs, summary)| Some((interval_secs,
+                    contention_degree_histogram(&summary.series.contention_degrees)?)))
+                .collect()
+        };
+        self.distributions.epochs.insert(epoch, EpochDistributionsData {
+            shared_objects_per_tx: self.shared_objects_per_tx.as_ref().and_then(HistogramData::of_counts),
+            txs_per_object_per_checkpoint: HistogramData::of_counts(&self.txs_per_object_per_checkpoint),
+            contention_degree: self.windows
+                .intervals(num_checkpoints)
+                .filter_map(|(interval, summary)| Some((interval,
+                    contention_degree_histogram(&summary.series.contention_degrees)?)))
+                .collect(),
+            contention_degree_secs,
+        });
+    }
+
+    fn fork(&self) -> Option<Box<dyn Metric>> {
+        Some(Box::new(Distributions {
+            epoch: None,
+            object_ids: Interner::new(),
+            windows: self.windows.fork()?,
+            shared_objects_per_tx: None,
+            txs_per_object_per_checkpoint: BTreeMap::new(),
+            distributions: DistributionsData::default(),
+        }))
+    }
+
+    fn merge_epoch(&mut self, part: Box<dyn Metric>) {
+        self.distributions.epochs.extend(downcast::<Distributions>(part).distributions.epochs);
+    }
+
+    fn resume(&mut self, results_dir: &Path) -> anyhow::Result<Option<Vec<usize>>> {
+        let Some(distributions) = read_saved::<DistributionsData>(results_dir, DISTRIBUTIONS_FILENAME)? else {
+            return Ok(None);
+        };
+        self.distributions = distributions;
+        Ok(Some(self.distributions.epochs.keys().copied().collect()))
+    }
+
+    fn serialize(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.distributions)
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+    HashSet,
+};
+use std::path::Path;
+
+use sui_sdk::types::base_types::{
+    ObjectID,
+    TransactionDigest,
+};
+
+use crate::types::{
+    CheckpointData,
+    ExactData,
+    ExactEpochData,
+    ExactIntervalData,
+    PrunedCheckpointData,
+};
+use crate::consts::EXACT_FILENAME;
+use crate::metrics::MetricsConfig;
+use crate::metrics::metric::{
+    downcast,
+    read_saved,
+    Metric,
+};
+use crate::metrics::stats::ratio;
+use crate::metrics::window::{
+    CheckpointTxs,
+    TouchedObject,
+    WindowCounts,
+    WindowsSummary,
+    Windows,
+};
+use crate::utils::Interner;
+
+// Counts of unique TXs touching shared objects within an epoch, as opposed
+// to the numbers of TXs touching shared objects reported per checkpoint.
+// TX digests are interned, like shared object IDs
+#[derive(Debug, Default)]
+pub struct ExactCounts {
+    txs: HashSet<u32>,
+    txs_touching_at_least_one_shared_obj_by_mut: HashSet<u32>,
+    // unique (TX, shared object) pairs
+    shared_obj_refs: HashSet<(u32, u32)>,
+}
+
+impl ExactCounts {
+    // Count the TXs of a checkpoint and return their interned digests
+    pub fn observe(&mut self, checkpoint_data: &CheckpointData, object_ids: &mut Interner<ObjectID>,
+            tx_ids: &mut Interner<TransactionDigest>) -> Vec<u32> {
+        let mut txs = HashSet::new();
+        for (obj_id, txs_touching_obj) in checkpoint_data.shared_objects.iter() {
+            let obj_index = object_ids.intern(obj_id);
+            for (tx_digest, mut_ref) in txs_touching_obj.iter() {
+                let tx_index = tx_ids.intern(tx_digest);
+                txs.insert(tx_index);
+                self.shared_obj_refs.insert((tx_index, obj_index));
+                if *mut_ref {
+                    self.txs_touching_at_least_one_shared_obj_by_mut.insert(tx_index);
+                }
+            }
+        }
+        self.txs.extend(txs.iter());
+        txs.into_iter().collect()
+    }
+
+    pub fn data(&self, num_txs_total: usize) -> ExactEpochData {
+        let num_txs = self.txs.len();
+        let num_txs_by_mut = self.txs_touching_at_least_one_shared_obj_by_mut.len();
+        ExactEpochData {
+            num_txs_touching_shared_objs: num_txs,
+            num_txs_touching_at_least_one_shared_obj_by_mut: num_txs_by_mut,
+            num_shared_objects_per_tx: ratio(self.shared_obj_refs.len(), num_txs),
+            density: ratio(num_txs, num_txs_total),
+            density_mut: ratio(num_txs_by_mut, num_txs_total),
+            avg_interval_data: BTreeMap::new(),
+            avg_interval_secs_data: BTreeMap::new(),
+        }
+    }
+}
+
+// Metrics counting unique TXs (by their digests) rather than the numbers of
+// TXs reported per checkpoint, stored in `epoch_exact_data.json`: density,
+// mut-density and contention degree over the same windows as in `Density`.
+// Epochs without TX digests (pruned) are `None`
+pub struct Exact {
+    // epoch being processed and its number of checkpoints
+    epoch: Option<(usize, usize)>,
+    object_ids: Interner<ObjectID>,
+    // TX digests are interned for as long as open windows include them (see
+    // `finish_epoch`)
+    tx_ids: Interner<TransactionDigest>,
+    windows: Windows,
+    num_txs_total: usize,
+    counts: ExactCounts,
+    // whether all checkpoints of the current epoch have TX digests
+    has_digests: bool,
+    epochs_without_digests: BTreeSet<usize>,
+    data: ExactData,
+}
+
+impl Exact {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Exact {
+            epoch: None,
+            object_ids: Interner::new(),
+            tx_ids: Interner::new(),
+            windows: Windows::new(config),
+            num_txs_total: 0,
+            counts: ExactCounts::default(),
+            has_digests: true,
+            epochs_without_digests: BTreeSet::new(),
+            data: ExactData::default(),
+        }
+    }
+
+    pub fn data(&self) -> &ExactData {
+        &self.data
+    }
+
+    // the number of TX digests kept for open windows
+    pub fn num_tx_ids(&self) -> usize {
+        self.tx_ids.len()
+    }
+}
+
+impl Metric for Exact {
+    fn name(&self) -> &'static str {
+        "exact"
+    }
+
+    fn filename(&self) -> &'static str {
+        EXACT_FILENAME
+    }
+
+    fn begin_epoch(&mut self, epoch: usize, start_checkpoint: usize, end_checkpoint: usize) {
+        self.epoch = Some((epoch, end_checkpoint - start_checkpoint + 1));
+        if self.windows.begin_epoch(start_checkpoint, end_checkpoint) {
+            self.tx_ids = Interner::new();
+        }
+        self.num_txs_total = 0;
+        self.counts = ExactCounts::default();
+        self.has_digests = true;
+    }
+
+    // windows tell which of their checkpoints lack TXs counted exactly
+    fn observe_checkpoint(&mut self, checkpoint: u64, checkpoint_data: &PrunedCheckpointData,
+            full_checkpoint_data: Option<&CheckpointData>) {
+        self.num_txs_total += checkpoint_data.num_txs_total;
+        let txs = match full_checkpoint_data {
+            Some(full_checkpoint_data) => Some(self.counts.observe(full_checkpoint_data, &mut self.object_ids,
+                &mut self.tx_ids)),
+            None => {
+                self.has_digests = false;
+                None
+            },
+        };
+        let touched_objects: Vec<TouchedObject> = checkpoint_data.shared_objects
+            .iter()
+            .map(|(obj_id, obj_counts)| TouchedObject {
+                index: self.object_ids.intern(obj_id),
+                num_txs: obj_counts.num_txs,
+                num_mut_refs: obj_counts.num_mut_refs,
+            })
+            .collect();
+        let num_txs = checkpoint_data.num_txs_touching_shared_objs as u64;
+        let txs = txs.as_deref().map(|digests| CheckpointTxs {
+            digests,
+            ..CheckpointTxs::default()
+        });
+        self.windows.push(checkpoint, checkpoint_data.timestamp_ms, num_txs, &touched_objects, txs);
+    }
+
+    fn finish_epoch(&mut self) {
+        let (epoch, num_checkpoints) = self.epoch
+            .take()
+            .expect("begin_epoch() must be called before finish_epoch()");
+        let interval_data = |summary: WindowsSummary| ExactIntervalData {
+            contention_degree: summary.series.exact_contention_degree(summary.num_windows),
+            partial_window: summary.open.and_then(WindowCounts::exact_contention_degree),
+        };
+        let epoch_data = self.has_digests.then(|| {
+            let mut epoch_data = self.counts.data(self.num_txs_total);
+            epoch_data.avg_interval_data = self.windows
+                .intervals(num_checkpoints)
+                .map(|(interval, summary)| (interval, interval_data(summary)))
+                .collect();
+            if !self.windows.missing_timestamps() {
+                epoch_data.avg_interval_secs_data = self.windows
+                    .intervals_secs()
+                    .map(|(interval_secs, summary)| (interval_secs, interval_data(summary)))
+                    .collect();
+            }
+            epoch_data
+        });
+        if epoch_data.is_none() {
+            self.epochs_without_digests.insert(epoch);
+        }
+        self.data.epochs.insert(epoch, epoch_data);
+
+        // Release digests of TXs that left all open windows, so that they are
+        // not kept for the whole stream in continuous mode
+        let windows: Vec<&WindowCounts> = self.windows.counts().collect();
+        self.tx_ids.retain(|tx| windows.iter().any(|counts| counts.contains_tx(tx)));
+    }
+
+    fn fork(&self) -> Option<Box<dyn Metric>> {
+        Some(Box::new(Exact {
+            epoch: None,
+            object_ids: Interner::new(),
+            tx_ids: Interner::new(),
+            windows: self.windows.fork()?,
+            num_txs_total: 0,
+            counts: ExactCounts::default(),
+            has_digests: true,
+            epochs_without_digests: BTreeSet::new(),
+            data: ExactData::default(),
+        }))
+    }
+
+    fn merge_epoch(&mut self, part: Box<dyn Metric>) {
+        let part = downcast::<Exact>(part);
+        self.data.epochs.extend(part.data.epochs);
+        self.epochs_without_digests.extend(part.epochs_without_digests);
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        if self.epochs_without_digests.is_empty() {
+            return Vec::new();
+        }
+        vec![format!("No TX digests in epochs {:?}, skipping --exact for them", self.epochs_without_digests)]
+    }
+
+    fn resume(&mut self, results_dir: &Path) -> anyhow::Result<Option<Vec<usize>>> {
+        let Some(data) = read_saved::<ExactData>(results_dir, EXACT_FILENAME)? else {
+            return Ok(None);
+        };
+        self.data = data;
+        Ok(Some(self.data.epochs.keys().copied().collect()))
+    }
+
+    fn serialize(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.data)
+    }
+}
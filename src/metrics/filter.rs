@@ -0,0 +1,141 @@
+use std::collections::{
+    BTreeSet,
+    HashSet,
+};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anyhow::{
+    bail,
+    Context,
+};
+use sui_sdk::types::base_types::{
+    ObjectID,
+    TransactionDigest,
+};
+
+use crate::types::{
+    CheckpointData,
+    ObjectFilter,
+    OwnedSelector,
+    SharedObjectsData,
+};
+use crate::utils::matches_pattern;
+
+impl ObjectFilter {
+    pub fn is_all(&self) -> bool {
+        *self == ObjectFilter::All
+    }
+
+    // Whether metrics are calculated for a shared object
+    pub fn keeps(&self, obj_id: &ObjectID) -> bool {
+        match self {
+            ObjectFilter::All => true,
+            ObjectFilter::Exclude(objects) => !objects.contains(obj_id),
+            ObjectFilter::Only(objects) => objects.contains(obj_id),
+        }
+    }
+
+    // The same filter, but also leaving out some shared objects
+    pub fn without(&self, objects: &BTreeSet<ObjectID>) -> ObjectFilter {
+        match self {
+            _ if objects.is_empty() => self.clone(),
+            ObjectFilter::All => ObjectFilter::Exclude(objects.clone()),
+            ObjectFilter::Exclude(excluded) => ObjectFilter::Exclude(excluded | objects),
+            ObjectFilter::Only(kept) => ObjectFilter::Only(kept - objects),
+        }
+    }
+
+    // Checkpoint data with only the shared objects kept. TXs touching none of
+    // them no longer count as TXs touching shared objects (and their senders
+    // are dropped); all others still do, as reported for the checkpoint
+    pub fn apply(&self, checkpoint_data: &CheckpointData) -> CheckpointData {
+        let (kept, dropped): (Vec<_>, Vec<_>) = checkpoint_data.shared_objects
+            .iter()
+            .partition(|(obj_id, _)| self.keeps(obj_id));
+        let kept_txs: HashSet<&TransactionDigest> = kept
+            .iter()
+            .flat_map(|(_, txs)| txs.keys())
+            .collect();
+        let dropped_txs: HashSet<&TransactionDigest> = dropped
+            .iter()
+            .flat_map(|(_, txs)| txs.keys())
+            .filter(|tx_digest| !kept_txs.contains(tx_digest))
+            .collect();
+
+        CheckpointData {
+            num_txs_total: checkpoint_data.num_txs_total,
+            num_txs_touching_shared_objs: checkpoint_data.num_txs_touching_shared_objs
+                .saturating_sub(dropped_txs.len()),
+            timestamp_ms: checkpoint_data.timestamp_ms,
+            shared_objects: kept
+                .into_iter()
+                .map(|(obj_id, txs)| (*obj_id, txs.clone()))
+                .collect(),
+            senders: checkpoint_data.senders
+                .iter()
+                .filter(|(tx_digest, _)| kept_txs.contains(tx_digest))
+                .map(|(tx_digest, sender)| (*tx_digest, *sender))
+                .collect(),
+        }
+    }
+}
+
+impl OwnedSelector {
+    // Selectors given by an ID ("0x..."), a type pattern, or a file with one
+    // of them per line (empty lines and lines starting with `#` are skipped)
+    pub fn parse(entry: &str) -> anyhow::Result<Vec<OwnedSelector>> {
+        if Path::new(entry).is_file() {
+            let content = fs::read_to_string(entry)
+                .with_context(|| format!("Couldn't read objects to treat as owned from {:?}", entry))?;
+            return content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(OwnedSelector::parse_one)
+                .collect();
+        }
+        Ok(vec![OwnedSelector::parse_one(entry)?])
+    }
+
+    fn parse_one(entry: &str) -> anyhow::Result<OwnedSelector> {
+        if entry.starts_with("0x") {
+            return Ok(OwnedSelector::Id(ObjectID::from_str(entry)
+                .with_context(|| format!("Invalid object or package ID {:?}", entry))?));
+        }
+        Ok(OwnedSelector::Type(entry.to_string()))
+    }
+
+    // Shared objects selected by any of the selectors. Packages and types of
+    // shared objects are only known given the data of `query-obj`, which
+    // type patterns need
+    pub fn select(selectors: &[OwnedSelector], shared_objects_data: Option<&SharedObjectsData>)
+            -> anyhow::Result<BTreeSet<ObjectID>> {
+        let mut objects = BTreeSet::new();
+        for selector in selectors.iter() {
+            match selector {
+                OwnedSelector::Id(id) => {
+                    objects.insert(*id);
+                    let Some(data) = shared_objects_data else {
+                        continue;
+                    };
+                    objects.extend(data.shared_objects
+                        .iter()
+                        .filter(|(_, obj)| ObjectID::from_str(&obj.address).is_ok_and(|package| package == *id))
+                        .map(|(obj_id, _)| *obj_id));
+                },
+                OwnedSelector::Type(pattern) => {
+                    let Some(data) = shared_objects_data else {
+                        bail!("Type pattern {:?} needs the shared objects data of `query-obj`", pattern);
+                    };
+                    objects.extend(data.shared_objects
+                        .iter()
+                        .filter(|(_, obj)| matches_pattern(pattern, &obj.type_))
+                        .map(|(obj_id, _)| *obj_id));
+                },
+            }
+        }
+        Ok(objects)
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::types::{
+    CheckpointData,
+    Distribution,
+    HistogramData,
+    HotObjectData,
+    HotObjectsData,
+    PrunedCheckpointData,
+};
+use crate::consts::HOT_OBJECTS_FILENAME;
+use crate::metrics::metric::Metric;
+use crate::metrics::stats::burstiness_of_counts;
+
+// Accesses to a single hot shared object observed so far
+#[derive(Debug, Default)]
+struct ObjectAccesses {
+    num_txs: u64,
+    num_active_checkpoints: u64,
+    txs_per_epoch: BTreeMap<usize, u64>,
+    // most TXs within a checkpoint, and the (first) checkpoint with them
+    peak: Option<(u64, u64)>,
+    // last checkpoint touching the object, and its timestamp
+    last: Option<(u64, Option<u64>)>,
+    // inter-arrival times -> number of occurrences
+    inter_arrival_checkpoints: BTreeMap<u64, u64>,
+    inter_arrival_ms: BTreeMap<u64, u64>,
+    // whether any checkpoint touching the object had no timestamp
+    missing_timestamps: bool,
+}
+
+// Inter-arrival times of TXs touching the most touched shared objects. Only
+// the number of TXs per checkpoint is known, so the first TX in a checkpoint
+// arrives after the last checkpoint touching the object, and all others
+// arrive at the same time as it. Which objects are the most touched is only
+// known once all epochs are observed, so all of them are tracked.
+// Checkpoints must be observed in order
+#[derive(Debug, Default)]
+pub struct HotObjects {
+    // the number of objects to report
+    num_objects: usize,
+    objects: HashMap<ObjectID, ObjectAccesses>,
+    epoch: Option<usize>,
+}
+
+impl HotObjects {
+    pub fn new(num_objects: usize) -> HotObjects {
+        HotObjects {
+            num_objects,
+            objects: HashMap::new(),
+            epoch: None,
+        }
+    }
+
+    // Report the most touched objects, most touched first (ties by ID)
+    pub fn data(&self) -> HotObjectsData {
+        let mut objects: Vec<(&ObjectID, &ObjectAccesses)> = self.objects.iter().collect();
+        objects.sort_by(|(a_id, a), (b_id, b)| b.num_txs.cmp(&a.num_txs).then(a_id.cmp(b_id)));
+        HotObjectsData {
+            objects: objects
+                .into_iter()
+                .take(self.num_objects)
+                .map(|(obj_id, accesses)| {
+                    let (peak_txs_per_checkpoint, peak_checkpoint) = accesses.peak.unwrap_or_default();
+                    let timestamps = !accesses.missing_timestamps;
+                    HotObjectData {
+                        object: *obj_id,
+                        num_txs: accesses.num_txs,
+                        num_active_checkpoints: accesses.num_active_checkpoints,
+                        txs_per_epoch: accesses.txs_per_epoch.clone(),
+                        peak_txs_per_checkpoint,
+                        peak_checkpoint,
+                        inter_arrival_checkpoints: HistogramData::of_counts(&accesses.inter_arrival_checkpoints),
+                        burstiness_checkpoints: burstiness_of_counts(&accesses.inter_arrival_checkpoints),
+                        inter_arrival_secs: Distribution::of_counts(&accesses.inter_arrival_ms)
+                            .filter(|_| timestamps)
+                            .map(|distribution| distribution.scaled(0.001)),
+                        burstiness_secs: burstiness_of_counts(&accesses.inter_arrival_ms).filter(|_| timestamps),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Metric for HotObjects {
+    fn name(&self) -> &'static str {
+        "hot-objects"
+    }
+
+    fn filename(&self) -> &'static str {
+        HOT_OBJECTS_FILENAME
+    }
+
+    fn begin_epoch(&mut self, epoch: usize, _start_checkpoint: usize, _end_checkpoint: usize) {
+        self.epoch = Some(epoch);
+    }
+
+    fn observe_checkpoint(&mut self, checkpoint: u64, checkpoint_data: &PrunedCheckpointData,
+            _full_checkpoint_data: Option<&CheckpointData>) {
+        let epoch = self.epoch.expect("begin_epoch() must be called before observing checkpoints");
+        for (obj_id, counts) in checkpoint_data.shared_objects.iter() {
+            if counts.num_txs == 0 {
+                continue;
+            }
+            let accesses = self.objects.entry(*obj_id).or_default();
+            let num_txs = counts.num_txs as u64;
+            accesses.num_txs += num_txs;
+            accesses.num_active_checkpoints += 1;
+            *accesses.txs_per_epoch.entry(epoch).or_default() += num_txs;
+            if accesses.peak.is_none_or(|(peak, _)| num_txs > peak) {
+                accesses.peak = Some((num_txs, checkpoint));
+            }
+
+            if let Some((last_checkpoint, last_timestamp)) = accesses.last {
+                *accesses.inter_arrival_checkpoints.entry(checkpoint - last_checkpoint).or_default() += 1;
+                match (last_timestamp, checkpoint_data.timestamp_ms) {
+                    (Some(last_timestamp), Some(timestamp)) => {
+                        *accesses.inter_arrival_ms.entry(timestamp.saturating_sub(last_timestamp)).or_default()
+                            += 1;
+                    },
+                    _ => accesses.missing_timestamps = true,
+                }
+            } else if checkpoint_data.timestamp_ms.is_none() {
+                accesses.missing_timestamps = true;
+            }
+            if num_txs > 1 {
+                *accesses.inter_arrival_checkpoints.entry(0).or_default() += num_txs - 1;
+                *accesses.inter_arrival_ms.entry(0).or_default() += num_txs - 1;
+            }
+            accesses.last = Some((checkpoint, checkpoint_data.timestamp_ms));
+        }
+    }
+
+    fn serialize(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.data())
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry;
+use std::path::Path;
+
+use crate::types::{
+    CheckpointData,
+    LifetimeEpochData,
+    PrunedCheckpointData,
+    SharedObjectSetData,
+    SharedObjectsSetData,
+};
+use crate::consts::SHARED_OBJECTS_SET_FILENAME;
+use crate::metrics::metric::{
+    downcast,
+    read_saved,
+    Metric,
+};
+
+// The set of all shared objects touched, stored in `shared_objects_set.json`
+// with the lifetime of every object and the TXs that touched it first (if
+// known), and the numbers of shared objects appearing and retiring in every
+// epoch
+pub struct Lifetimes {
+    epoch: Option<usize>,
+    shared_objects_set: SharedObjectsSetData,
+}
+
+impl Lifetimes {
+    pub fn new() -> Self {
+        Lifetimes {
+            epoch: None,
+            shared_objects_set: SharedObjectsSetData {
+                shared_objects: BTreeMap::new(),
+                epochs: BTreeMap::new(),
+            },
+        }
+    }
+
+    pub fn data(&self) -> &SharedObjectsSetData {
+        &self.shared_objects_set
+    }
+}
+
+impl Default for Lifetimes {
+    fn default() -> Self {
+        Lifetimes::new()
+    }
+}
+
+impl Metric for Lifetimes {
+    fn name(&self) -> &'static str {
+        "lifetimes"
+    }
+
+    fn filename(&self) -> &'static str {
+        SHARED_OBJECTS_SET_FILENAME
+    }
+
+    fn begin_epoch(&mut self, epoch: usize, _start_checkpoint: usize, _end_checkpoint: usize) {
+        self.epoch = Some(epoch);
+        self.shared_objects_set.epochs.insert(epoch, LifetimeEpochData::default());
+    }
+
+    // TX digests (if not pruned) are only needed to record which TXs touched
+    // a shared object first
+    fn observe_checkpoint(&mut self, checkpoint: u64, checkpoint_data: &PrunedCheckpointData,
+            full_checkpoint_data: Option<&CheckpointData>) {
+        let epoch = self.epoch.expect("begin_epoch() must be called before observing checkpoints");
+        for (obj_id, obj_counts) in checkpoint_data.shared_objects.iter() {
+            // collect unique shared objects in the Sui network and some data about them
+            let shared_object = self.shared_objects_set
+                .shared_objects
+                .entry(*obj_id)
+                .or_insert_with(|| SharedObjectSetData {
+                    num_txs: 0,
+                    num_mut_refs: 0,
+                    first_touched_at_epoch: epoch,
+                    first_touched_at_checkpoint: checkpoint,
+                    last_touched_at_epoch: epoch,
+                    last_touched_at_checkpoint: checkpoint,
+                    num_active_checkpoints: 0,
+                    num_active_epochs: 0,
+                    longest_idle_gap: 0,
+                    first_touched_by_txs: full_checkpoint_data
+                        .and_then(|data| data.shared_objects.get(obj_id).cloned())
+                        .unwrap_or_default(),
+                });
+            shared_object.num_txs += obj_counts.num_txs;
+            shared_object.num_mut_refs += obj_counts.num_mut_refs;
+
+            // Update the lifetime of the object
+            if shared_object.num_active_checkpoints == 0 || shared_object.last_touched_at_epoch != epoch {
+                shared_object.num_active_epochs += 1;
+            }
+            if shared_object.num_active_checkpoints > 0 {
+                let idle_gap = checkpoint.saturating_sub(shared_object.last_touched_at_checkpoint + 1);
+                shared_object.longest_idle_gap = shared_object.longest_idle_gap.max(idle_gap);
+            }
+            shared_object.last_touched_at_epoch = epoch;
+            shared_object.last_touched_at_checkpoint = checkpoint;
+            shared_object.num_active_checkpoints += 1;
+        }
+    }
+
+    fn finish_epoch(&mut self) {
+        self.epoch = None;
+    }
+
+    fn fork(&self) -> Option<Box<dyn Metric>> {
+        Some(Box::new(Lifetimes::new()))
+    }
+
+    // Epochs do not overlap, so neither do the lifetimes of an object within
+    // them. First-touch data is resolved by the earliest checkpoint, so that
+    // it does not depend on the order of merging
+    fn merge_epoch(&mut self, part: Box<dyn Metric>) {
+        let part = downcast::<Lifetimes>(part);
+        for (obj_id, obj) in part.shared_objects_set.shared_objects.into_iter() {
+            let total = match self.shared_objects_set.shared_objects.entry(obj_id) {
+                Entry::Vacant(entry) => {
+                    entry.insert(obj);
+                    continue;
+                },
+                Entry::Occupied(entry) => entry.into_mut(),
+            };
+            total.num_txs += obj.num_txs;
+            total.num_mut_refs += obj.num_mut_refs;
+
+            // the idle gap between the epochs is from the last touch in the
+            // earlier one to the first touch in the later one
+            let idle_gap = if obj.first_touched_at_checkpoint < total.first_touched_at_checkpoint {
+                total.first_touched_at_checkpoint.saturating_sub(obj.last_touched_at_checkpoint + 1)
+            } else {
+                obj.first_touched_at_checkpoint.saturating_sub(total.last_touched_at_checkpoint + 1)
+            };
+            total.longest_idle_gap = total.longest_idle_gap.max(obj.longest_idle_gap).max(idle_gap);
+            total.num_active_checkpoints += obj.num_active_checkpoints;
+            total.num_active_epochs += obj.num_active_epochs;
+            if obj.last_touched_at_checkpoint > total.last_touched_at_checkpoint {
+                total.last_touched_at_epoch = obj.last_touched_at_epoch;
+                total.last_touched_at_checkpoint = obj.last_touched_at_checkpoint;
+            }
+            if obj.first_touched_at_checkpoint < total.first_touched_at_checkpoint {
+                total.first_touched_at_epoch = obj.first_touched_at_epoch;
+                total.first_touched_at_checkpoint = obj.first_touched_at_checkpoint;
+                total.first_touched_by_txs = obj.first_touched_by_txs;
+            }
+        }
+        self.shared_objects_set.epochs.extend(part.shared_objects_set.epochs);
+    }
+
+    // Count shared objects appearing and retiring in every epoch. Whether an
+    // object is retired depends on the epochs following, so they are counted
+    // once all epochs are processed
+    fn finish_workspace(&mut self) {
+        let epochs = &mut self.shared_objects_set.epochs;
+        let last_epoch = epochs.keys().last().copied();
+        for epoch_data in epochs.values_mut() {
+            *epoch_data = LifetimeEpochData::default();
+        }
+        for obj in self.shared_objects_set.shared_objects.values() {
+            if let Some(epoch_data) = epochs.get_mut(&obj.first_touched_at_epoch) {
+                epoch_data.num_new_shared_objects += 1;
+                if obj.num_active_checkpoints == 1 {
+                    epoch_data.num_one_shot_shared_objects += 1;
+                }
+            }
+            if Some(obj.last_touched_at_epoch) != last_epoch {
+                if let Some(epoch_data) = epochs.get_mut(&obj.last_touched_at_epoch) {
+                    epoch_data.num_retired_shared_objects += 1;
+                }
+            }
+        }
+    }
+
+    fn resume(&mut self, results_dir: &Path) -> anyhow::Result<Option<Vec<usize>>> {
+        let Some(shared_objects_set) = read_saved::<SharedObjectsSetData>(results_dir,
+                SHARED_OBJECTS_SET_FILENAME)? else {
+            return Ok(None);
+        };
+        self.shared_objects_set = shared_objects_set;
+        Ok(Some(self.shared_objects_set.epochs.keys().copied().collect()))
+    }
+
+    fn serialize(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.shared_objects_set)
+    }
+}
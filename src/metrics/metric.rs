@@ -0,0 +1,306 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+use std::sync::{
+    mpsc,
+    Condvar,
+    Mutex,
+};
+use std::thread;
+
+use anyhow::{
+    anyhow,
+    bail,
+    ensure,
+    Context,
+};
+use serde::de::DeserializeOwned;
+
+use crate::types::{
+    CheckpointData,
+    EpochFile,
+    ObjectFilter,
+    PrunedCheckpointData,
+};
+
+// A metric calculated in a single pass over the epochs of a workspace.
+// Epochs have to be fed in ascending order, and checkpoints of an epoch in
+// ascending order too:
+//
+//     metric.begin_epoch(epoch, start_checkpoint, end_checkpoint);
+//     metric.observe_checkpoint(checkpoint, &checkpoint_data.prune(), Some(&checkpoint_data));
+//     ...
+//     metric.finish_epoch();
+//     metric.finish_workspace();
+//     let result = metric.serialize()?;
+//
+// or simply `observe_epoch(&mut metrics, &epoch_file, &object_filter)` for
+// every epoch data file, to feed it to several metrics at once, or
+// `process_epochs(...)` to feed all epochs while reading them in parallel.
+// Metrics that do not depend on the epochs before can calculate epochs on
+// their own in parallel instead, to be merged in ascending order:
+//
+//     let mut part = metric.fork().unwrap();
+//     observe_epoch(&mut [part.as_mut()], &epoch_file, &object_filter)?;
+//     metric.merge_epoch(part);
+pub trait Metric: Any + Send {
+    // name of the metric, as selected by `--metrics`
+    fn name(&self) -> &'static str;
+
+    // file (in the results directory of the workspace) to store the result in
+    fn filename(&self) -> &'static str;
+
+    fn begin_epoch(&mut self, epoch: usize, start_checkpoint: usize, end_checkpoint: usize);
+
+    // Counts of a checkpoint, and its data with TX digests unless the epoch
+    // data is pruned. Shared objects left out of metrics are already left out
+    // of both
+    fn observe_checkpoint(&mut self, checkpoint: u64, checkpoint_data: &PrunedCheckpointData,
+        full_checkpoint_data: Option<&CheckpointData>);
+
+    fn finish_epoch(&mut self) {}
+
+    // A new metric of the same kind and options, to calculate a single epoch
+    // on its own and merge it by `merge_epoch`. `None` if the metric depends
+    // on the epochs before (e.g., windows carried over in continuous mode),
+    // so that epochs have to be fed to it in order
+    fn fork(&self) -> Option<Box<dyn Metric>> {
+        None
+    }
+
+    // Merge an epoch calculated by a metric returned by `fork`, after all
+    // epochs before it
+    fn merge_epoch(&mut self, _part: Box<dyn Metric>) {
+        panic!("{} cannot merge epochs calculated on their own", self.name());
+    }
+
+    // Called once all epochs are processed
+    fn finish_workspace(&mut self) {}
+
+    // Warnings to print once all epochs are processed, e.g., about epochs
+    // lacking data the metric needs
+    fn warnings(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    // Continue from the result saved by a previous run in `results_dir`, so
+    // that only epochs following the ones it covers have to be fed, and
+    // return these epochs. `None` if the metric cannot be resumed (e.g., it
+    // depends on all epochs) or nothing was saved; an error if the saved
+    // result cannot be parsed
+    fn resume(&mut self, _results_dir: &Path) -> anyhow::Result<Option<Vec<usize>>> {
+        Ok(None)
+    }
+
+    fn serialize(&self) -> serde_json::Result<String>;
+}
+
+// Take back a metric returned by `fork` to merge it
+pub fn downcast<M: Metric>(part: Box<dyn Metric>) -> Box<M> {
+    (part as Box<dyn Any>)
+        .downcast()
+        .expect("merging an epoch calculated by a metric of another kind")
+}
+
+// Read a result saved by a previous run, `None` if there is none. Results
+// saved by an older version lacking some of the metrics fail to parse
+pub fn read_saved<T: DeserializeOwned>(results_dir: &Path, filename: &str) -> anyhow::Result<Option<T>> {
+    let content = match fs::read_to_string(results_dir.join(filename)) {
+        Ok(content) => content,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err).with_context(|| format!("Couldn't read \"{}\"", filename)),
+    };
+    let saved = serde_json::from_str(&content)
+        .with_context(|| format!("Couldn't parse \"{}\" saved by a previous run", filename))?;
+    Ok(Some(saved))
+}
+
+// Feed a whole epoch data file (of either format) to metrics, pruning every
+// checkpoint once for all of them, and check the epoch data is consistent
+pub fn observe_epoch(metrics: &mut [&mut dyn Metric], epoch_file: &EpochFile, object_filter: &ObjectFilter)
+        -> anyhow::Result<()> {
+    let (epoch, num_txs_scanned) = match epoch_file {
+        EpochFile::Full(result) => (result.epoch, result.num_txs_scanned),
+        EpochFile::Pruned(result) => (result.epoch, result.num_txs_scanned),
+    };
+    let mut num_txs_total = 0;
+    let mut num_txs_touching_shared_objs = 0;
+    let mut num_txs_by_mut = 0;
+    let mut count = |checkpoint_data: &PrunedCheckpointData| {
+        num_txs_total += checkpoint_data.num_txs_total;
+        num_txs_touching_shared_objs += checkpoint_data.num_txs_touching_shared_objs;
+        num_txs_by_mut += checkpoint_data.num_txs_touching_at_least_one_shared_obj_by_mut;
+    };
+
+    match epoch_file {
+        EpochFile::Full(result) => {
+            for metric in metrics.iter_mut() {
+                metric.begin_epoch(result.epoch, result.start_checkpoint, result.end_checkpoint);
+            }
+            for (checkpoint, checkpoint_data) in result.checkpoints.iter() {
+                let filtered;
+                let checkpoint_data = if object_filter.is_all() {
+                    checkpoint_data
+                } else {
+                    filtered = object_filter.apply(checkpoint_data);
+                    &filtered
+                };
+                let pruned = checkpoint_data.prune();
+                count(&pruned);
+                for metric in metrics.iter_mut() {
+                    metric.observe_checkpoint(*checkpoint, &pruned, Some(checkpoint_data));
+                }
+            }
+        },
+        EpochFile::Pruned(result) => {
+            if !object_filter.is_all() {
+                bail!("Epoch {}: filtering shared objects needs TX digests, which pruned epoch data lacks",
+                    result.epoch);
+            }
+            for metric in metrics.iter_mut() {
+                metric.begin_epoch(result.epoch, result.start_checkpoint, result.end_checkpoint);
+            }
+            for (checkpoint, checkpoint_data) in result.checkpoints.iter() {
+                count(checkpoint_data);
+                for metric in metrics.iter_mut() {
+                    metric.observe_checkpoint(*checkpoint, checkpoint_data, None);
+                }
+            }
+        },
+    }
+
+    // total number of scanned TXs per epoch must be equal to the sum of TXs from
+    // all checkpoints for that epoch
+    ensure!(num_txs_total == num_txs_scanned,
+        "Epoch {}: {} TXs in checkpoints, but {} TXs scanned", epoch, num_txs_total, num_txs_scanned);
+    ensure!(num_txs_by_mut <= num_txs_touching_shared_objs,
+        "Epoch {}: more TXs touching shared objects by mut ref than TXs touching shared objects", epoch);
+    for metric in metrics.iter_mut() {
+        metric.finish_epoch();
+    }
+    Ok(())
+}
+
+// An epoch calculated within a worker thread: parts to merge of the metrics
+// that can calculate epochs on their own (see `Metric::fork`), and the epoch
+// data if other metrics still have to be fed it
+struct CalculatedEpoch {
+    parts: Vec<Option<Box<dyn Metric>>>,
+    epoch_file: Option<EpochFile>,
+}
+
+// Calculate an epoch on its own by forks of the metrics that can
+fn calculate_epoch(forks: &[Option<Box<dyn Metric>>], epoch_file: EpochFile, object_filter: &ObjectFilter)
+        -> anyhow::Result<CalculatedEpoch> {
+    let mut parts: Vec<Option<Box<dyn Metric>>> = forks
+        .iter()
+        .map(|fork| fork.as_ref().and_then(|fork| fork.fork()))
+        .collect();
+    let mut metrics: Vec<&mut dyn Metric> = parts.iter_mut().flatten().map(|part| part.as_mut()).collect();
+    if !metrics.is_empty() {
+        observe_epoch(&mut metrics, &epoch_file, object_filter)?;
+    }
+    let fed_in_order = parts.iter().any(Option::is_none);
+    Ok(CalculatedEpoch {
+        parts,
+        epoch_file: fed_in_order.then_some(epoch_file),
+    })
+}
+
+// Feed epochs to metrics in ascending order. Epoch data is read by `load` in
+// `num_workers` worker threads in parallel, at most `num_workers` epochs
+// ahead of the epoch being fed (which bounds the epoch data held in memory).
+// Metrics that can calculate epochs on their own do so within the worker
+// threads too, and their parts are merged in ascending order of epochs; all
+// others are fed in the calling thread. `on_done` is called whenever an
+// epoch has been fed. Stops at the first epoch that cannot be read or fed
+pub fn process_epochs<L, D>(metrics: &mut [&mut dyn Metric], epochs: &[usize], num_workers: usize,
+        object_filter: &ObjectFilter, load: L, mut on_done: D) -> anyhow::Result<()>
+where
+    L: Fn(usize) -> anyhow::Result<EpochFile> + Sync,
+    D: FnMut(usize),
+{
+    let num_workers = num_workers.clamp(1, epochs.len().max(1));
+    // index of the next epoch to read, and the number of epochs fed so far,
+    // `None` once feeding stopped
+    let next = AtomicUsize::new(0);
+    let num_fed = (Mutex::new(Some(0)), Condvar::new());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..num_workers {
+            let sender = sender.clone();
+            // every worker forks the metrics for each epoch from forks of its own
+            let forks: Vec<Option<Box<dyn Metric>>> = metrics.iter().map(|metric| metric.fork()).collect();
+            let (next, num_fed, load) = (&next, &num_fed, &load);
+            scope.spawn(move || loop {
+                let k = next.fetch_add(1, Ordering::Relaxed);
+                let Some(epoch) = epochs.get(k) else {
+                    break;
+                };
+                let (lock, fed) = num_fed;
+                let mut guard = lock.lock().unwrap();
+                while guard.is_some_and(|num_fed| k >= num_fed + num_workers) {
+                    guard = fed.wait(guard).unwrap();
+                }
+                if guard.is_none() {
+                    break;
+                }
+                drop(guard);
+                let calculated = load(*epoch)
+                    .and_then(|epoch_file| calculate_epoch(&forks, epoch_file, object_filter));
+                if sender.send((k, calculated)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        // epochs calculated ahead of the one to feed next
+        let mut calculated = BTreeMap::new();
+        let mut feed = || -> anyhow::Result<()> {
+            for (k, epoch) in epochs.iter().enumerate() {
+                let calculated_epoch = loop {
+                    if let Some(calculated_epoch) = calculated.remove(&k) {
+                        break calculated_epoch;
+                    }
+                    let (j, calculated_epoch) = receiver
+                        .recv()
+                        .map_err(|_| anyhow!("Epoch {}: worker threads stopped before reading it", epoch))?;
+                    calculated.insert(j, calculated_epoch);
+                };
+                let CalculatedEpoch { parts, epoch_file } = calculated_epoch?;
+                let mut fed_in_order = Vec::new();
+                for (metric, part) in metrics.iter_mut().zip(parts) {
+                    match part {
+                        Some(part) => metric.merge_epoch(part),
+                        None => fed_in_order.push(&mut **metric),
+                    }
+                }
+                if let Some(epoch_file) = epoch_file {
+                    observe_epoch(&mut fed_in_order, &epoch_file, object_filter)?;
+                }
+                on_done(*epoch);
+
+                let (lock, fed) = &num_fed;
+                *lock.lock().unwrap() = Some(k + 1);
+                fed.notify_all();
+            }
+            Ok(())
+        };
+        let result = feed();
+
+        // stop worker threads still waiting to read epochs
+        let (lock, fed) = &num_fed;
+        *lock.lock().unwrap() = None;
+        fed.notify_all();
+        drop(receiver);
+        result
+    })
+}
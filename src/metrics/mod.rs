@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::types::{
+    EpochHeader,
+    ObjectFilter,
+    ProcessedEpoch,
+    WorkspaceEpoch,
+};
+
+pub mod clock;
+pub mod concentration;
+pub mod conflicts;
+pub mod density;
+pub mod distributions;
+pub mod exact;
+mod filter;
+pub mod hot;
+pub mod lifetimes;
+pub mod metric;
+pub mod pyth;
+pub mod read_mostly;
+pub mod single_writer;
+pub mod stats;
+mod window;
+
+// Options of metrics calculation
+#[derive(Debug, Clone, Default)]
+pub struct MetricsConfig {
+    // intervals (in checkpoints) for contention degree and object touchability
+    pub intervals: Vec<u64>,
+    // if set, contention degree and object touchability are calculated over
+    // sliding windows of `interval` checkpoints moving by `stride` checkpoints
+    // instead of tumbling windows
+    pub stride: Option<u64>,
+    // intervals (in seconds) for contention degree and object touchability
+    // over tumbling windows of wall-clock time, based on checkpoint timestamps
+    pub interval_secs: Vec<u64>,
+    // package of every shared object (as found by `query-obj`) to calculate
+    // concentration of TXs over packages; objects without a known package
+    // are left out
+    pub packages: Option<Arc<HashMap<ObjectID, String>>>,
+    // type of every shared object (as found by `query-obj`), to group
+    // read-mostly and single-writer objects by
+    pub types: Option<Arc<HashMap<ObjectID, String>>>,
+    // whether epochs are one continuous stream of checkpoints, i.e., windows
+    // are carried over to the next epoch (if it follows right after) rather
+    // than cut at the end of every epoch
+    pub continuous: bool,
+    // shared objects to calculate metrics for; TXs touching none of them do
+    // not count as TXs touching shared objects
+    pub object_filter: ObjectFilter,
+}
+
+impl MetricsConfig {
+    // Fingerprint of the packages of shared objects, if known, to tell
+    // whether they changed since metrics were saved (e.g., after `query-obj`
+    // was run again); FNV-1a over the sorted entries, stable across runs
+    pub fn packages_fingerprint(&self) -> Option<u64> {
+        let packages = self.packages.as_ref()?;
+        let mut entries: Vec<(String, &String)> = packages
+            .iter()
+            .map(|(obj_id, package)| (obj_id.to_string(), package))
+            .collect();
+        entries.sort_unstable();
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for (obj_id, package) in entries.iter() {
+            // separators keep ("ab", "c") and ("a", "bc") apart
+            for byte in obj_id.bytes().chain([0]).chain(package.bytes()).chain([0]) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        Some(hash)
+    }
+}
+
+impl From<&EpochHeader> for ProcessedEpoch {
+    fn from(header: &EpochHeader) -> Self {
+        ProcessedEpoch {
+            start_checkpoint: header.start_checkpoint,
+            end_checkpoint: header.end_checkpoint,
+            num_txs_scanned: header.num_txs_scanned,
+            pruned: header.pruned,
+        }
+    }
+}
+
+impl From<&WorkspaceEpoch> for ProcessedEpoch {
+    fn from(entry: &WorkspaceEpoch) -> Self {
+        ProcessedEpoch {
+            start_checkpoint: entry.start_checkpoint,
+            end_checkpoint: entry.end_checkpoint,
+            num_txs_scanned: entry.num_txs_scanned,
+            pruned: entry.pruned,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,91 @@
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use sui_sdk::types::base_types::ObjectID;
+
+use crate::types::{
+    CheckpointData,
+    PrunedCheckpointData,
+    PythStateData,
+};
+use crate::consts::{
+    PYTH_STATE_FILENAME,
+    PYTH_STATE_OBJECT,
+};
+use crate::metrics::metric::Metric;
+
+// TXs touching the State object of the Pyth oracle, and the digests of those
+// mutating it, which are only known for epochs with TX digests
+#[derive(Debug)]
+pub struct PythState {
+    state: ObjectID,
+    epoch: Option<usize>,
+    epochs_without_digests: BTreeSet<usize>,
+    data: PythStateData,
+}
+
+impl PythState {
+    pub fn new() -> Self {
+        PythState {
+            state: ObjectID::from_str(PYTH_STATE_OBJECT).unwrap(),
+            epoch: None,
+            epochs_without_digests: BTreeSet::new(),
+            data: PythStateData::default(),
+        }
+    }
+
+    pub fn data(&self) -> &PythStateData {
+        &self.data
+    }
+}
+
+impl Default for PythState {
+    fn default() -> Self {
+        PythState::new()
+    }
+}
+
+impl Metric for PythState {
+    fn name(&self) -> &'static str {
+        "pyth-state"
+    }
+
+    fn filename(&self) -> &'static str {
+        PYTH_STATE_FILENAME
+    }
+
+    fn begin_epoch(&mut self, epoch: usize, _start_checkpoint: usize, _end_checkpoint: usize) {
+        self.epoch = Some(epoch);
+    }
+
+    fn observe_checkpoint(&mut self, _checkpoint: u64, checkpoint_data: &PrunedCheckpointData,
+            full_checkpoint_data: Option<&CheckpointData>) {
+        let epoch = self.epoch.expect("begin_epoch() must be called before observing checkpoints");
+        let Some(obj_counts) = checkpoint_data.shared_objects.get(&self.state) else {
+            return;
+        };
+        self.data.num_txs += obj_counts.num_txs;
+        self.data.num_mut_refs += obj_counts.num_mut_refs;
+        match full_checkpoint_data.and_then(|data| data.shared_objects.get(&self.state)) {
+            Some(txs) => self.data.mut_txs.extend(txs
+                .iter()
+                .filter(|(_, mut_ref)| **mut_ref)
+                .map(|(tx_digest, _)| *tx_digest)),
+            None => {
+                self.epochs_without_digests.insert(epoch);
+            },
+        }
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        if self.epochs_without_digests.is_empty() {
+            return Vec::new();
+        }
+        vec![format!("No TX digests in epochs {:?}, skipping TXs mutating the State object of Pyth for them",
+            self.epochs_without_digests)]
+    }
+
+    fn serialize(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.data)
+    }
+}
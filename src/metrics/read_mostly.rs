@@ -0,0 +1,216 @@
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+use std::sync::Arc;
+
+use sui_sdk::types::base_types::{
+    ObjectID,
+    TransactionDigest,
+};
+
+use crate::types::{
+    CheckpointData,
+    PrunedCheckpointData,
+    ReadMostlyData,
+    ReadMostlyEpochData,
+    ReadMostlyObjectData,
+    ReadMostlyTypeData,
+};
+use crate::consts::READ_MOSTLY_FILENAME;
+use crate::metrics::metric::Metric;
+use crate::metrics::stats::ratio;
+use crate::utils::Interner;
+
+// TXs of an epoch, those touching shared objects, and the number of TXs
+// touching each set of shared objects (unless some checkpoint has no TX
+// digests). Sets are interned, as most TXs touch one of a few sets
+#[derive(Debug)]
+struct EpochCounts {
+    num_txs_total: usize,
+    num_txs_touching_shared_objs: usize,
+    txs_per_object_set: Option<HashMap<u32, usize>>,
+}
+
+// Shared objects whose ratio of mutable references to TXs touching them is at
+// most a threshold, and the numbers of TXs per epoch that would still touch
+// shared objects needing consensus ordering without them. Which objects are
+// read-mostly is only known once all epochs are observed, so TXs are counted
+// by the sets of shared objects they touch until then
+#[derive(Debug)]
+pub struct ReadMostly {
+    max_mut_ratio: f64,
+    // types of shared objects, if known (see `query-obj`)
+    types: Option<Arc<HashMap<ObjectID, String>>>,
+    // shared objects touched so far (interned) and the numbers of TXs and
+    // mutable references to each of them
+    object_ids: Interner<ObjectID>,
+    totals: Vec<(usize, usize)>,
+    // sets of (interned) shared objects touched by TXs, sorted
+    object_sets: Interner<Vec<u32>>,
+    epoch: Option<usize>,
+    epochs: BTreeMap<usize, EpochCounts>,
+}
+
+impl ReadMostly {
+    pub fn new(max_mut_ratio: f64, types: Option<Arc<HashMap<ObjectID, String>>>) -> ReadMostly {
+        ReadMostly {
+            max_mut_ratio,
+            types,
+            object_ids: Interner::new(),
+            totals: Vec::new(),
+            object_sets: Interner::new(),
+            epoch: None,
+            epochs: BTreeMap::new(),
+        }
+    }
+
+    // Report of all epochs observed, with read-mostly objects most touched
+    // first (ties by ID); densities without read-mostly objects are missing
+    // for epochs without TX digests
+    pub fn data(&self) -> ReadMostlyData {
+        let types = self.types.as_deref();
+        let read_mostly: Vec<bool> = self.totals
+            .iter()
+            .map(|(num_txs, num_mut_refs)| *num_txs > 0
+                && *num_mut_refs as f64 / *num_txs as f64 <= self.max_mut_ratio)
+            .collect();
+        let mut objects: Vec<ReadMostlyObjectData> = self.totals
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| read_mostly[*index])
+            .map(|(index, (num_txs, num_mut_refs))| {
+                let obj_id = self.object_ids.value(index as u32);
+                ReadMostlyObjectData {
+                    object: *obj_id,
+                    num_txs: *num_txs,
+                    num_mut_refs: *num_mut_refs,
+                    mut_ratio: *num_mut_refs as f64 / *num_txs as f64,
+                    type_: types.and_then(|types| types.get(obj_id).cloned()),
+                }
+            })
+            .collect();
+        objects.sort_by(|a, b| b.num_txs.cmp(&a.num_txs).then(a.object.cmp(&b.object)));
+
+        let types = types.map(|_| {
+            let mut types: BTreeMap<String, ReadMostlyTypeData> = BTreeMap::new();
+            for obj in objects.iter() {
+                // objects touched after `query-obj` was run have no known type
+                let Some(type_) = &obj.type_ else {
+                    continue;
+                };
+                let type_data = types.entry(type_.clone()).or_default();
+                type_data.num_objects += 1;
+                type_data.num_txs += obj.num_txs;
+                type_data.num_mut_refs += obj.num_mut_refs;
+            }
+            types
+        });
+
+        // TXs touching only read-mostly objects would not need consensus
+        // ordering anymore
+        let only_read_mostly = |set: &u32| self.object_sets
+            .value(*set)
+            .iter()
+            .all(|obj| read_mostly[*obj as usize]);
+        let epochs = self.epochs
+            .iter()
+            .map(|(epoch, counts)| {
+                let num_txs = counts.txs_per_object_set.as_ref().map(|txs_per_object_set| {
+                    let num_txs_only_read_mostly: usize = txs_per_object_set
+                        .iter()
+                        .filter(|(set, _)| only_read_mostly(set))
+                        .map(|(_, num_txs)| num_txs)
+                        .sum();
+                    counts.num_txs_touching_shared_objs.saturating_sub(num_txs_only_read_mostly)
+                });
+                (*epoch, ReadMostlyEpochData {
+                    density: ratio(counts.num_txs_touching_shared_objs, counts.num_txs_total),
+                    num_txs_touching_shared_objs_without_read_mostly: num_txs,
+                    density_without_read_mostly: num_txs.map(|num_txs| ratio(num_txs, counts.num_txs_total)),
+                })
+            })
+            .collect();
+
+        ReadMostlyData {
+            max_mut_ratio: self.max_mut_ratio,
+            num_objects: objects.len(),
+            num_read_only_objects: objects.iter().filter(|obj| obj.num_mut_refs == 0).count(),
+            num_txs: objects.iter().map(|obj| obj.num_txs).sum(),
+            objects,
+            types,
+            epochs,
+        }
+    }
+}
+
+impl Metric for ReadMostly {
+    fn name(&self) -> &'static str {
+        "read-mostly"
+    }
+
+    fn filename(&self) -> &'static str {
+        READ_MOSTLY_FILENAME
+    }
+
+    fn begin_epoch(&mut self, epoch: usize, _start_checkpoint: usize, _end_checkpoint: usize) {
+        self.epoch = Some(epoch);
+        self.epochs.insert(epoch, EpochCounts {
+            num_txs_total: 0,
+            num_txs_touching_shared_objs: 0,
+            txs_per_object_set: Some(HashMap::new()),
+        });
+    }
+
+    fn observe_checkpoint(&mut self, _checkpoint: u64, checkpoint_data: &PrunedCheckpointData,
+            full_checkpoint_data: Option<&CheckpointData>) {
+        let epoch = self.epoch.expect("begin_epoch() must be called before observing checkpoints");
+        for (obj_id, obj_counts) in checkpoint_data.shared_objects.iter() {
+            let index = self.object_ids.intern(obj_id) as usize;
+            if index == self.totals.len() {
+                self.totals.push((0, 0));
+            }
+            self.totals[index].0 += obj_counts.num_txs;
+            self.totals[index].1 += obj_counts.num_mut_refs;
+        }
+
+        let counts = self.epochs.get_mut(&epoch).unwrap();
+        counts.num_txs_total += checkpoint_data.num_txs_total;
+        counts.num_txs_touching_shared_objs += checkpoint_data.num_txs_touching_shared_objs;
+        let Some(full_checkpoint_data) = full_checkpoint_data else {
+            counts.txs_per_object_set = None;
+            return;
+        };
+        let Some(txs_per_object_set) = counts.txs_per_object_set.as_mut() else {
+            return;
+        };
+        let mut objects_per_tx: HashMap<&TransactionDigest, Vec<u32>> = HashMap::new();
+        for (obj_id, txs) in full_checkpoint_data.shared_objects.iter() {
+            let index = self.object_ids.intern(obj_id);
+            for tx_digest in txs.keys() {
+                objects_per_tx.entry(tx_digest).or_default().push(index);
+            }
+        }
+        for mut objects in objects_per_tx.into_values() {
+            objects.sort_unstable();
+            *txs_per_object_set.entry(self.object_sets.intern(&objects)).or_default() += 1;
+        }
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        let without_digests: Vec<usize> = self.epochs
+            .iter()
+            .filter(|(_, counts)| counts.txs_per_object_set.is_none())
+            .map(|(epoch, _)| *epoch)
+            .collect();
+        if without_digests.is_empty() {
+            return Vec::new();
+        }
+        vec![format!("No TX digests in epochs {:?}, skipping densities without read-mostly objects for them",
+            without_digests)]
+    }
+
+    fn serialize(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.data())
+    }
+}
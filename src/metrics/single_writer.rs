@@ -0,0 +1,187 @@
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+    HashMap,
+    HashSet,
+};
+use std::sync::Arc;
+
+use sui_sdk::types::base_types::{
+    ObjectID,
+    SuiAddress,
+};
+
+use crate::types::{
+    CheckpointData,
+    PrunedCheckpointData,
+    SingleWriterData,
+    SingleWriterGroupData,
+    SingleWriterObjectData,
+};
+use crate::consts::SINGLE_WRITER_FILENAME;
+use crate::metrics::metric::Metric;
+
+// Senders of TXs touching a single shared object observed so far
+#[derive(Debug, Default)]
+struct ObjectSenders {
+    num_txs: usize,
+    num_mut_refs: usize,
+    senders: HashSet<SuiAddress>,
+    // only kept up to one more than the most allowed, which is enough to
+    // tell an object is no candidate
+    mut_senders: BTreeSet<SuiAddress>,
+}
+
+// Shared objects mutated by only a few distinct senders, i.e., candidates
+// for a redesign as owned objects
+#[derive(Debug)]
+pub struct SingleWriters {
+    max_senders: usize,
+    // types and packages of shared objects, if known (see `query-obj`)
+    types: Option<Arc<HashMap<ObjectID, String>>>,
+    packages: Option<Arc<HashMap<ObjectID, String>>>,
+    objects: HashMap<ObjectID, ObjectSenders>,
+    num_txs_touching_shared_objs: usize,
+    num_checkpoints_without_senders: usize,
+    epoch: Option<usize>,
+    epochs_without_digests: BTreeSet<usize>,
+}
+
+impl SingleWriters {
+    pub fn new(max_senders: usize, types: Option<Arc<HashMap<ObjectID, String>>>,
+            packages: Option<Arc<HashMap<ObjectID, String>>>) -> SingleWriters {
+        SingleWriters {
+            max_senders,
+            types,
+            packages,
+            objects: HashMap::new(),
+            num_txs_touching_shared_objs: 0,
+            num_checkpoints_without_senders: 0,
+            epoch: None,
+            epochs_without_digests: BTreeSet::new(),
+        }
+    }
+
+    // Count the senders of TXs of a checkpoint; checkpoints of epoch data
+    // queried before senders were recorded are skipped
+    fn observe(&mut self, checkpoint_data: &CheckpointData) {
+        if checkpoint_data.senders.is_empty() {
+            if !checkpoint_data.shared_objects.is_empty() {
+                self.num_checkpoints_without_senders += 1;
+            }
+            return;
+        }
+        self.num_txs_touching_shared_objs += checkpoint_data.num_txs_touching_shared_objs;
+        for (obj_id, txs) in checkpoint_data.shared_objects.iter() {
+            let obj = self.objects.entry(*obj_id).or_default();
+            for (tx_digest, mut_ref) in txs.iter() {
+                obj.num_txs += 1;
+                obj.num_mut_refs += *mut_ref as usize;
+                let Some(sender) = checkpoint_data.senders.get(tx_digest) else {
+                    continue;
+                };
+                obj.senders.insert(*sender);
+                if *mut_ref && obj.mut_senders.len() <= self.max_senders {
+                    obj.mut_senders.insert(*sender);
+                }
+            }
+        }
+    }
+
+    // Report candidates, most touched first (ties by ID), grouped by their
+    // types and packages if known
+    pub fn data(&self) -> SingleWriterData {
+        let (types, packages) = (self.types.as_deref(), self.packages.as_deref());
+        let tx_share = |num_txs: usize| match self.num_txs_touching_shared_objs {
+            0 => 0.0,
+            total => num_txs as f64 / total as f64,
+        };
+        let mut objects: Vec<SingleWriterObjectData> = self.objects
+            .iter()
+            .filter(|(_, obj)| (1..=self.max_senders).contains(&obj.mut_senders.len()))
+            .map(|(obj_id, obj)| SingleWriterObjectData {
+                object: *obj_id,
+                num_txs: obj.num_txs,
+                num_mut_refs: obj.num_mut_refs,
+                num_senders: obj.senders.len(),
+                mut_senders: obj.mut_senders.iter().copied().collect(),
+                tx_share: tx_share(obj.num_txs),
+                type_: types.and_then(|types| types.get(obj_id).cloned()),
+                package: packages.and_then(|packages| packages.get(obj_id).cloned()),
+            })
+            .collect();
+        objects.sort_by(|a, b| b.num_txs.cmp(&a.num_txs).then(a.object.cmp(&b.object)));
+
+        // objects touched after `query-obj` was run have no known type and
+        // package
+        let group = |key: fn(&SingleWriterObjectData) -> Option<&String>| {
+            let mut groups: BTreeMap<String, SingleWriterGroupData> = BTreeMap::new();
+            for obj in objects.iter() {
+                let Some(key) = key(obj) else {
+                    continue;
+                };
+                let group = groups.entry(key.clone()).or_default();
+                group.num_objects += 1;
+                group.num_txs += obj.num_txs;
+            }
+            for group in groups.values_mut() {
+                group.tx_share = tx_share(group.num_txs);
+            }
+            groups
+        };
+        let types = types.map(|_| group(|obj| obj.type_.as_ref()));
+        let packages = packages.map(|_| group(|obj| obj.package.as_ref()));
+
+        SingleWriterData {
+            max_senders: self.max_senders,
+            num_checkpoints_without_senders: self.num_checkpoints_without_senders,
+            num_txs_touching_shared_objs: self.num_txs_touching_shared_objs,
+            num_objects: objects.len(),
+            objects,
+            types,
+            packages,
+        }
+    }
+}
+
+impl Metric for SingleWriters {
+    fn name(&self) -> &'static str {
+        "single-writer"
+    }
+
+    fn filename(&self) -> &'static str {
+        SINGLE_WRITER_FILENAME
+    }
+
+    fn begin_epoch(&mut self, epoch: usize, _start_checkpoint: usize, _end_checkpoint: usize) {
+        self.epoch = Some(epoch);
+    }
+
+    fn observe_checkpoint(&mut self, _checkpoint: u64, _checkpoint_data: &PrunedCheckpointData,
+            full_checkpoint_data: Option<&CheckpointData>) {
+        let epoch = self.epoch.expect("begin_epoch() must be called before observing checkpoints");
+        match full_checkpoint_data {
+            Some(full_checkpoint_data) => self.observe(full_checkpoint_data),
+            None => {
+                self.epochs_without_digests.insert(epoch);
+            },
+        }
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !self.epochs_without_digests.is_empty() {
+            warnings.push(format!("No TX digests in epochs {:?}, skipping them for single-writer objects",
+                self.epochs_without_digests.iter().collect::<Vec<_>>()));
+        }
+        if self.num_checkpoints_without_senders > 0 {
+            warnings.push(format!("No senders in {} checkpoints, skipping them for single-writer objects",
+                self.num_checkpoints_without_senders));
+        }
+        warnings
+    }
+
+    fn serialize(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.data())
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::BTreeMap;
+
+use crate::types::{
+    ConcentrationData,
+    Distribution,
+    HistogramData,
+};
+use crate::consts::CONCENTRATION_TOP_K;
+
+// Mean of values, 0 if there are none
+pub fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+// Ratio of two counts, 0 if the denominator is 0 (e.g., the density of an
+// epoch without TXs), which would otherwise be NaN and saved as `null`
+pub fn ratio(numerator: usize, denominator: usize) -> f64 {
+    if denominator == 0 {
+        return 0.0;
+    }
+    numerator as f64 / denominator as f64
+}
+
+// Percentile `p` (0..=100) of sorted values using the nearest-rank method
+pub fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+// Percentile `p` (0..=100) of values given as value -> number of occurrences
+// (with at least one value) using the nearest-rank method
+pub fn percentile_of_counts(counts: &BTreeMap<u64, u64>, p: f64) -> u64 {
+    let num_values: u64 = counts.values().sum();
+    let rank = ((p / 100.0 * num_values as f64).ceil() as u64).clamp(1, num_values);
+    let mut num_values_so_far = 0;
+    for (value, count) in counts.iter() {
+        num_values_so_far += count;
+        if num_values_so_far >= rank {
+            return *value;
+        }
+    }
+    unreachable!("rank {} out of {} values", rank, num_values)
+}
+
+// Burstiness coefficient (std - mean) / (std + mean) of values given as value
+// -> number of occurrences, `None` if there are none or all of them are 0
+pub fn burstiness_of_counts(counts: &BTreeMap<u64, u64>) -> Option<f64> {
+    let num_values = counts.values().sum::<u64>() as f64;
+    let mean = counts.iter().fold(0.0, |sum, (value, count)| sum + (value * count) as f64) / num_values;
+    let variance = counts
+        .iter()
+        .fold(0.0, |sum, (value, count)| sum + (*value as f64 - mean).powi(2) * *count as f64) / num_values;
+    let std = variance.sqrt();
+    if num_values == 0.0 || std + mean == 0.0 {
+        return None;
+    }
+    Some((std - mean) / (std + mean))
+}
+
+impl Distribution {
+    // Describe the distribution of values, `None` if there are none
+    pub fn of(values: &[f64]) -> Option<Distribution> {
+        if values.is_empty() {
+            return None;
+        }
+        let mut sorted = values.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        Some(Distribution {
+            min: sorted[0],
+            p50: percentile(&sorted, 50.0),
+            p90: percentile(&sorted, 90.0),
+            p99: percentile(&sorted, 99.0),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+
+    // Describe the distribution of integer values given as value -> number
+    // of occurrences, `None` if there are none
+    pub fn of_counts(counts: &BTreeMap<u64, u64>) -> Option<Distribution> {
+        let (min, max) = (counts.keys().next()?, counts.keys().last()?);
+        Some(Distribution {
+            min: *min as f64,
+            p50: percentile_of_counts(counts, 50.0) as f64,
+            p90: percentile_of_counts(counts, 90.0) as f64,
+            p99: percentile_of_counts(counts, 99.0) as f64,
+            max: *max as f64,
+        })
+    }
+
+    // The same distribution in other units, e.g., in seconds rather than
+    // milliseconds
+    pub fn scaled(&self, factor: f64) -> Distribution {
+        Distribution {
+            min: self.min * factor,
+            p50: self.p50 * factor,
+            p90: self.p90 * factor,
+            p99: self.p99 * factor,
+            max: self.max * factor,
+        }
+    }
+}
+
+impl HistogramData {
+    // Histogram of integer values given as value -> number of occurrences,
+    // i.e., with bins of width 1, `None` if there are none
+    pub fn of_counts(counts: &BTreeMap<u64, u64>) -> Option<HistogramData> {
+        Some(HistogramData {
+            distribution: Distribution::of_counts(counts)?,
+            bin_width: 1.0,
+            histogram: counts.clone(),
+        })
+    }
+
+    // Histogram of non-negative values with bins of `bin_width`, `None` if
+    // there are none
+    pub fn of_values(values: &[f64], bin_width: f64) -> Option<HistogramData> {
+        let mut histogram = BTreeMap::new();
+        for value in values.iter() {
+            *histogram.entry((value / bin_width).floor() as u64).or_default() += 1;
+        }
+        Some(HistogramData {
+            distribution: Distribution::of(values)?,
+            bin_width,
+            histogram,
+        })
+    }
+}
+
+impl ConcentrationData {
+    // Concentration of TXs over objects given the number of TXs touching
+    // each of them, `None` if there are no TXs
+    pub fn of(num_txs: &[u64]) -> Option<ConcentrationData> {
+        let total: u64 = num_txs.iter().sum();
+        if total == 0 {
+            return None;
+        }
+        let mut sorted = num_txs.to_vec();
+        sorted.sort_unstable();
+        let n = sorted.len() as f64;
+        let total = total as f64;
+
+        // Gini coefficient of values sorted in ascending order
+        let weighted_sum = sorted
+            .iter()
+            .enumerate()
+            .fold(0.0, |sum, (k, value)| sum + (k + 1) as f64 * *value as f64);
+        let gini = 2.0 * weighted_sum / (n * total) - (n + 1.0) / n;
+
+        let hhi = sorted.iter().fold(0.0, |sum, value| sum + (*value as f64 / total).powi(2));
+        let top_shares = CONCENTRATION_TOP_K
+            .iter()
+            .map(|k| (*k, sorted.iter().rev().take(*k).sum::<u64>() as f64 / total))
+            .collect();
+        Some(ConcentrationData {
+            num_touched: sorted.len(),
+            num_txs: total as u64,
+            gini,
+            hhi,
+            top_shares,
+        })
+    }
+}
@@ -0,0 +1,1361 @@
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+    HashMap,
+};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use sui_sdk::types::base_types::SuiAddress;
+
+use super::clock::Clock;
+use super::concentration::Concentration;
+use super::conflicts::ConflictGraph;
+use super::density::Density;
+use super::distributions::Distributions;
+use super::exact::Exact;
+use super::hot::HotObjects;
+use super::lifetimes::Lifetimes;
+use super::metric::{
+    observe_epoch,
+    process_epochs,
+    read_saved,
+    Metric,
+};
+use super::pyth::PythState;
+use super::read_mostly::ReadMostly;
+use super::single_writer::SingleWriters;
+use super::{
+    stats,
+    MetricsConfig,
+};
+use crate::types::{
+    CheckpointData,
+    ConcentrationData,
+    ConflictGraphData,
+    Distribution,
+    EpochFile,
+    EpochsData,
+    HistogramData,
+    ObjectFilter,
+    OwnedSelector,
+    ReadMostlyObjectData,
+    ReadMostlyTypeData,
+    ResultData,
+    SharedObjectData,
+    SharedObjectsData,
+    SingleWriterGroupData,
+};
+use crate::consts::{
+    CLOCK_OBJECT,
+    PYTH_STATE_OBJECT,
+};
+use crate::utils::fixtures::{
+    checkpoint,
+    obj,
+    tx,
+};
+use crate::utils::matches_pattern;
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+}
+
+// Epoch 0 spans checkpoints 0..=3:
+// - checkpoint 0: TX 1 touches A (mut), TX 2 touches A and B;
+// - checkpoint 1: TX 3 touches A;
+// - checkpoint 2: no TXs touching shared objects;
+// - checkpoint 3: TX 4 touches C (mut), TX 5 touches A (mut).
+fn epoch0() -> Vec<(u64, CheckpointData)> {
+    vec![
+        (0, checkpoint(3, 2, &[("0xa", &[(1, true), (2, false)]), ("0xb", &[(2, false)])])),
+        (1, checkpoint(2, 1, &[("0xa", &[(3, false)])])),
+        (2, checkpoint(1, 0, &[])),
+        (3, checkpoint(4, 2, &[("0xc", &[(4, true)]), ("0xa", &[(5, true)])])),
+    ]
+}
+
+// Epoch 1 spans checkpoints 4..=5:
+// - checkpoint 4: TX 6 touches D;
+// - checkpoint 5: TX 7 touches A (mut).
+fn epoch1() -> Vec<(u64, CheckpointData)> {
+    vec![
+        (4, checkpoint(1, 1, &[("0xd", &[(6, false)])])),
+        (5, checkpoint(1, 1, &[("0xa", &[(7, true)])])),
+    ]
+}
+
+fn tumbling(intervals: &[u64]) -> MetricsConfig {
+    MetricsConfig {
+        intervals: intervals.to_vec(),
+        ..Default::default()
+    }
+}
+
+fn sliding(intervals: &[u64], stride: u64) -> MetricsConfig {
+    MetricsConfig {
+        intervals: intervals.to_vec(),
+        stride: Some(stride),
+        ..Default::default()
+    }
+}
+
+fn run(intervals: &[u64]) -> Density {
+    run_with_config(&tumbling(intervals))
+}
+
+fn run_with_config(config: &MetricsConfig) -> Density {
+    let mut density = Density::new(config);
+    observe_all_filtered(&mut [&mut density], &config.object_filter);
+    density
+}
+
+fn run_lifetimes() -> Lifetimes {
+    let mut lifetimes = Lifetimes::new();
+    observe_all(&mut [&mut lifetimes]);
+    lifetimes.finish_workspace();
+    lifetimes
+}
+
+// Feed both epochs to metrics in a single pass
+fn observe_all(metrics: &mut [&mut dyn Metric]) {
+    observe_all_filtered(metrics, &ObjectFilter::All);
+}
+
+fn observe_all_filtered(metrics: &mut [&mut dyn Metric], object_filter: &ObjectFilter) {
+    for epoch_file in epoch_files() {
+        observe_epoch(metrics, &epoch_file, object_filter).unwrap();
+    }
+}
+
+fn epoch_files() -> [EpochFile; 2] {
+    [
+        EpochFile::Full(result_data(0, 0, 3, epoch0())),
+        EpochFile::Full(result_data(1, 4, 5, epoch1())),
+    ]
+}
+
+// Feed the checkpoints of a single epoch to metrics
+fn observe_checkpoints(metrics: &mut [&mut dyn Metric], epoch: usize, start: usize, end: usize,
+        checkpoints: Vec<(u64, CheckpointData)>) {
+    let epoch_file = EpochFile::Full(result_data(epoch, start, end, checkpoints));
+    observe_epoch(metrics, &epoch_file, &ObjectFilter::All).unwrap();
+}
+
+fn result_data(epoch: usize, start: usize, end: usize, checkpoints: Vec<(u64, CheckpointData)>)
+        -> ResultData {
+    let num_txs: usize = checkpoints.iter().map(|(_, data)| data.num_txs_total).sum();
+    ResultData {
+        network: String::from("Mainnet"),
+        version: String::from("1.24.0"),
+        epoch,
+        start_checkpoint: start,
+        end_checkpoint: end,
+        last_cursor: String::new(),
+        num_txs_in_epoch: num_txs,
+        num_txs_scanned: num_txs,
+        num_txs_touching_0_shared_objs: 0,
+        num_txs_touching_0_objs: 0,
+        pruned: false,
+        queried_at_ms: None,
+        checkpoints: checkpoints.into_iter().collect(),
+    }
+}
+
+#[test]
+fn tx_counts() {
+    let density = run(&[1]);
+    let epoch = &density.data().epochs[&0];
+    assert_eq!(epoch.num_checkpoints, 4);
+    assert_eq!(epoch.num_txs_total, 10);
+    assert_eq!(epoch.num_txs_touching_shared_objs, 5);
+    // TXs 1, 4 and 5
+    assert_eq!(epoch.num_txs_touching_at_least_one_shared_obj_by_mut, 3);
+}
+
+#[test]
+fn density() {
+    let density = run(&[1]);
+    assert_close(density.data().epochs[&0].density, 5.0 / 10.0);
+    assert_close(density.data().epochs[&1].density, 1.0);
+}
+
+#[test]
+fn density_of_epoch_without_txs() {
+    let mut density = Density::new(&tumbling(&[1]));
+    observe_checkpoints(&mut [&mut density], 0, 0, 0, vec![(0, checkpoint(0, 0, &[]))]);
+    assert_eq!(density.data().epochs[&0].density, 0.0);
+    // NaN would be saved as `null`, failing to parse when resuming
+    let saved = density.serialize().unwrap();
+    assert!(serde_json::from_str::<EpochsData>(&saved).is_ok());
+}
+
+#[test]
+fn density_mut() {
+    let density = run(&[1]);
+    assert_close(density.data().epochs[&0].density_mut, 3.0 / 10.0);
+    assert_close(density.data().epochs[&1].density_mut, 1.0 / 2.0);
+}
+
+#[test]
+fn num_shared_objects_per_tx() {
+    let density = run(&[1]);
+    // (3/2 + 1/1 + 2/2) averaged over all 4 checkpoints, including the empty one
+    assert_close(density.data().epochs[&0].num_shared_objects_per_tx, 3.5 / 4.0);
+}
+
+#[test]
+fn contention_degree() {
+    let density = run(&[1, 2, 3]);
+    let epoch = &density.data().epochs[&0];
+    // intervals of 1 checkpoint: 2/2, 1/1, (none), 2/2 over 4 intervals
+    assert_close(epoch.avg_interval_data[&1].contention_degree, 3.0 / 4.0);
+    // intervals of 2 checkpoints: 3/2, 2/2 over 2 intervals
+    assert_close(epoch.avg_interval_data[&2].contention_degree, 2.5 / 2.0);
+    // intervals of 3 checkpoints: 3/2 over 4/3 intervals, checkpoint 3 is dropped
+    assert_close(epoch.avg_interval_data[&3].contention_degree, 1.5 / (4.0 / 3.0));
+
+    // counters of the unfinished interval are not carried over to epoch 1
+    let epoch = &density.data().epochs[&1];
+    assert_close(epoch.avg_interval_data[&2].contention_degree, 1.0);
+    assert_close(epoch.avg_interval_data[&3].contention_degree, 0.0);
+}
+
+#[test]
+fn obj_touchability() {
+    let density = run(&[1, 2]);
+    let epoch = &density.data().epochs[&0];
+    // only A in checkpoint 0 is touched by more than one TX: 1/2, 0, (none), 0
+    assert_close(epoch.avg_interval_data[&1].obj_touchability, 0.5 / 4.0);
+    // A is not counted in the interval of checkpoints 2..=3, where it is
+    // touched by a single TX
+    assert_close(epoch.avg_interval_data[&2].obj_touchability, 0.5 / 2.0);
+    assert_close(density.data().epochs[&1].avg_interval_data[&2].obj_touchability, 0.0);
+}
+
+#[test]
+fn conflict_contention_degree() {
+    let density = run(&[1, 2, 4]);
+    let epoch = &density.data().epochs[&0];
+    // intervals of 1 checkpoint: only A is written in checkpoints 0 and 3
+    // (by 2 TXs and 1 TX), A and C in checkpoint 3: 2/1, 0, (none), 2/2
+    assert_close(epoch.avg_interval_data[&1].conflict_contention_degree, 3.0 / 4.0);
+    // intervals of 2 checkpoints: accesses to A only (3/1), to A and C (2/2)
+    assert_close(epoch.avg_interval_data[&2].conflict_contention_degree, 4.0 / 2.0);
+    // B is never written, so its access does not count
+    assert_close(epoch.avg_interval_data[&4].conflict_contention_degree, 5.0 / 2.0);
+    // D is only read in epoch 1
+    assert_close(density.data().epochs[&1].avg_interval_data[&1].conflict_contention_degree, 1.0 / 2.0);
+}
+
+#[test]
+fn conflicting_obj_fraction() {
+    let density = run(&[1, 4]);
+    let epoch = &density.data().epochs[&0];
+    // only A in checkpoint 0 is both written and touched by more than one TX
+    assert_close(epoch.avg_interval_data[&1].conflicting_obj_fraction, 0.5 / 4.0);
+    // A is written and touched by 4 TXs within the whole epoch, while B and
+    // C are touched by a single TX each
+    assert_close(epoch.avg_interval_data[&4].conflicting_obj_fraction, 1.0 / 3.0);
+}
+
+#[test]
+fn collisions() {
+    let density = run(&[1]);
+    // TXs 1 (mut) and 2 touch A in checkpoint 0; TXs touching the same
+    // object in different checkpoints do not collide
+    let epoch = &density.data().epochs[&0];
+    assert_eq!(epoch.num_read_read_collisions, 0);
+    assert_eq!(epoch.num_read_write_collisions, 1);
+    assert_eq!(epoch.num_write_write_collisions, 0);
+
+    // TXs 1 and 2 read A, TXs 3, 4 and 5 write it
+    let mut density = Density::new(&tumbling(&[1]));
+    observe_checkpoints(&mut [&mut density], 0, 0, 0, vec![(0, checkpoint(5, 5,
+        &[("0xa", &[(1, false), (2, false), (3, true), (4, true), (5, true)])]))]);
+    let epoch = &density.data().epochs[&0];
+    assert_eq!(epoch.num_read_read_collisions, 1);
+    assert_eq!(epoch.num_read_write_collisions, 6);
+    assert_eq!(epoch.num_write_write_collisions, 3);
+    assert_close(epoch.avg_interval_data[&1].conflict_contention_degree, 5.0);
+    assert_close(epoch.avg_interval_data[&1].conflicting_obj_fraction, 1.0);
+}
+
+#[test]
+fn conflict_graph() {
+    let density = run(&[1]);
+    let graph = density.data().epochs[&0].conflict_graph.as_ref().unwrap();
+    // checkpoint 0: TX 2 reads A after TX 1 writes it, a single component;
+    // checkpoint 1: TX 3 alone;
+    // checkpoint 3: TXs 4 and 5 write different objects, two components
+    assert_close(graph.avg_longest_chain, 4.0 / 3.0);
+    assert_eq!(graph.max_longest_chain, 2);
+    assert_close(graph.avg_num_components, 4.0 / 3.0);
+    assert_close(graph.avg_speedup_bound, (1.0 + 1.0 + 2.0) / 3.0);
+    assert_close(graph.speedup_bound, 5.0 / 4.0);
+}
+
+#[test]
+fn conflict_graph_of_windows() {
+    let density = run(&[2, 3]);
+    let avg_interval_data = &density.data().epochs[&0].avg_interval_data;
+    // checkpoints 0 and 1: TXs 2 and 3 read A after TX 1 writes it;
+    // checkpoints 2 and 3: TXs 4 and 5 write different objects
+    let graph = avg_interval_data[&2].conflict_graph.as_ref().unwrap();
+    assert_close(graph.avg_longest_chain, 1.5);
+    assert_eq!(graph.max_longest_chain, 2);
+    assert_close(graph.avg_num_components, 1.5);
+    assert_close(graph.avg_speedup_bound, (1.5 + 2.0) / 2.0);
+    assert_close(graph.speedup_bound, 5.0 / 3.0);
+    // checkpoints 0 to 2 (a chain of 2 TXs), then the unfinished window of
+    // checkpoint 3 counting as a third of a window, like for contention degree
+    let graph = avg_interval_data[&3].conflict_graph.as_ref().unwrap();
+    assert_close(graph.avg_longest_chain, 2.0 / (4.0 / 3.0));
+    assert_close(graph.avg_num_components, 1.0 / (4.0 / 3.0));
+    assert_close(graph.avg_speedup_bound, 1.5 / (4.0 / 3.0));
+    assert_close(graph.speedup_bound, 3.0 / 2.0);
+    // no window of 3 checkpoints fits into epoch 1
+    let graph = density.data().epochs[&1].avg_interval_data[&3].conflict_graph.as_ref().unwrap();
+    assert_eq!(*graph, ConflictGraphData::default());
+
+    // sliding windows of checkpoints 0..=1, 1..=2 and 2..=3
+    let density = run_with_config(&sliding(&[2], 1));
+    let graph = density.data().epochs[&0].avg_interval_data[&2].conflict_graph.as_ref().unwrap();
+    assert_close(graph.avg_longest_chain, 4.0 / 3.0);
+    assert_close(graph.avg_num_components, 4.0 / 3.0);
+    assert_close(graph.avg_speedup_bound, (1.5 + 1.0 + 2.0) / 3.0);
+    assert_close(graph.speedup_bound, 6.0 / 4.0);
+}
+
+#[test]
+fn continuous_conflict_graph_of_windows() {
+    let density = run_with_config(&continuous(&[3]));
+    // window of checkpoints 0..=2, and checkpoint 3 is still open
+    let avg_interval_data = &density.data().epochs[&0].avg_interval_data[&3];
+    assert_close(avg_interval_data.conflict_graph.as_ref().unwrap().avg_longest_chain, 2.0);
+    let partial = avg_interval_data.partial_window.as_ref().unwrap().conflict_graph.as_ref().unwrap();
+    assert_eq!((partial.max_longest_chain, partial.avg_num_components), (1, 2.0));
+    // the window of checkpoints 3..=5 spanning both epochs: TX 7 writes A
+    // after TX 5, TXs 4 and 6 touch C and D alone
+    let graph = density.data().epochs[&1].avg_interval_data[&3].conflict_graph.as_ref().unwrap();
+    assert_eq!(graph.max_longest_chain, 2);
+    assert_close(graph.avg_num_components, 3.0);
+    assert_close(graph.speedup_bound, 2.0);
+
+    // given pruned epoch data of epoch 0, the window spanning both epochs is
+    // left out, while conflict graphs of checkpoints of epoch 1 are known
+    let mut density = Density::new(&continuous(&[3]));
+    let [_, epoch_file1] = epoch_files();
+    let epoch_file0 = EpochFile::Pruned(result_data(0, 0, 3, epoch0()).prune());
+    observe_epoch(&mut [&mut density], &epoch_file0, &ObjectFilter::All).unwrap();
+    observe_epoch(&mut [&mut density], &epoch_file1, &ObjectFilter::All).unwrap();
+    let epoch = &density.data().epochs[&1];
+    assert!(epoch.conflict_graph.is_some());
+    assert!(epoch.avg_interval_data[&3].conflict_graph.is_none());
+    assert!(density.data().epochs[&0].conflict_graph.is_none());
+}
+
+#[test]
+fn conflict_graph_of_checkpoint() {
+    // TXs 1 and 2 read A in parallel, TX 3 writes A after them and TX 4
+    // reads A after TX 3
+    let graph = ConflictGraph::of(&checkpoint(4, 4,
+        &[("0xa", &[(1, false), (2, false), (3, true), (4, false)])]));
+    assert_eq!(graph, ConflictGraph {
+        num_txs: 4,
+        longest_chain: 3,
+        num_components: 1,
+    });
+    assert_close(graph.speedup_bound(), 4.0 / 3.0);
+
+    // TXs only reading the same object do not conflict; TX 3 writes B after
+    // reading A in parallel with TX 1
+    let graph = ConflictGraph::of(&checkpoint(3, 3,
+        &[("0xa", &[(1, false), (3, false)]), ("0xb", &[(2, true), (3, true)])]));
+    assert_eq!(graph, ConflictGraph {
+        num_txs: 3,
+        longest_chain: 2,
+        num_components: 2,
+    });
+}
+
+#[test]
+fn exact_counts() {
+    let mut exact = Exact::new(&tumbling(&[1]));
+    observe_all(&mut [&mut exact]);
+    let epoch = exact.data().epochs[&0].as_ref().unwrap();
+    assert_eq!(epoch.num_txs_touching_shared_objs, 5);
+    assert_eq!(epoch.num_txs_touching_at_least_one_shared_obj_by_mut, 3);
+    // A is touched by 4 TXs, B and C by one each
+    assert_close(epoch.num_shared_objects_per_tx, 6.0 / 5.0);
+    assert_close(epoch.density, 5.0 / 10.0);
+    assert_close(epoch.density_mut, 3.0 / 10.0);
+    // no TX is counted twice, so exact and reported counts agree
+    assert_close(epoch.avg_interval_data[&1].contention_degree.unwrap(), 3.0 / 4.0);
+    assert!(epoch.avg_interval_secs_data.is_empty());
+}
+
+#[test]
+fn exact_counts_of_duplicate_txs() {
+    // TX 1 is reported in both checkpoints, and TX 2 is reported once but
+    // counted twice by the checkpoint
+    let mut density = Density::new(&tumbling(&[2]));
+    let mut exact = Exact::new(&tumbling(&[2]));
+    observe_checkpoints(&mut [&mut density, &mut exact], 0, 0, 1, vec![
+        (0, checkpoint(2, 1, &[("0xa", &[(1, true)])])),
+        (1, checkpoint(3, 3, &[("0xa", &[(1, true), (2, false)])])),
+    ]);
+
+    let epoch = &density.data().epochs[&0];
+    assert_eq!(epoch.num_txs_touching_shared_objs, 4);
+    assert_close(epoch.avg_interval_data[&2].contention_degree, 4.0);
+    let exact_epoch = exact.data().epochs[&0].as_ref().unwrap();
+    assert_eq!(exact_epoch.num_txs_touching_shared_objs, 2);
+    assert_close(exact_epoch.avg_interval_data[&2].contention_degree.unwrap(), 2.0);
+}
+
+#[test]
+fn excluded_objects() {
+    let object_filter = ObjectFilter::Exclude(BTreeSet::from([obj("0xa")]));
+    let mut density = Density::new(&tumbling(&[1]));
+    let mut lifetimes = Lifetimes::new();
+    observe_all_filtered(&mut [&mut density, &mut lifetimes], &object_filter);
+    let epoch = &density.data().epochs[&0];
+    // TXs 1, 3 and 5 only touch A
+    assert_eq!(epoch.num_txs_touching_shared_objs, 2);
+    assert_eq!(epoch.num_txs_touching_at_least_one_shared_obj_by_mut, 1);
+    assert_close(epoch.density, 2.0 / 10.0);
+    assert_eq!(epoch.num_shared_objects_per_epoch, 2);
+    // B in checkpoint 0, C in checkpoint 3
+    assert_close(epoch.avg_interval_data[&1].contention_degree, 2.0 / 4.0);
+    assert!(!lifetimes.data().shared_objects.contains_key(&obj("0xa")));
+}
+
+#[test]
+fn only_some_objects() {
+    let density = run_with_config(&MetricsConfig {
+        intervals: vec![1],
+        object_filter: ObjectFilter::Only(BTreeSet::from([obj("0xa")])),
+        ..Default::default()
+    });
+    let epoch = &density.data().epochs[&0];
+    // TX 4 only touches C, TX 2 still touches A
+    assert_eq!(epoch.num_txs_touching_shared_objs, 4);
+    assert_eq!(epoch.num_shared_objects_per_epoch, 1);
+    assert_close(epoch.num_shared_objects_per_tx, 3.0 / 4.0);
+}
+
+#[test]
+fn pruned_epochs_cannot_be_filtered() {
+    let mut density = Density::new(&tumbling(&[1]));
+    let object_filter = ObjectFilter::Exclude(BTreeSet::from([obj("0xa")]));
+    let result = result_data(0, 0, 3, epoch0());
+    assert!(observe_epoch(&mut [&mut density], &EpochFile::Pruned(result.prune()), &object_filter).is_err());
+    assert!(observe_epoch(&mut [&mut density], &EpochFile::Full(result), &object_filter).is_ok());
+}
+
+#[test]
+fn metrics_in_one_pass() {
+    let mut density = Density::new(&tumbling(&[1]));
+    let mut hot_objects = HotObjects::new(1);
+    let mut read_mostly = ReadMostly::new(0.0, None);
+    observe_all(&mut [&mut density, &mut hot_objects, &mut read_mostly]);
+
+    // the same results as each metric on its own
+    assert_eq!(density.serialize().unwrap(), run(&[1]).serialize().unwrap());
+    let mut hot_object = HotObjects::new(1);
+    observe_all(&mut [&mut hot_object]);
+    assert_eq!(hot_objects.serialize().unwrap(), hot_object.serialize().unwrap());
+    assert_close(read_mostly.data().epochs[&0].density, density.data().epochs[&0].density);
+
+    // pruned epoch data cannot be filtered for any of them
+    let epoch_file = EpochFile::Pruned(result_data(0, 0, 3, epoch0()).prune());
+    let filter = ObjectFilter::Exclude(BTreeSet::from([obj("0xb")]));
+    assert!(observe_epoch(&mut [&mut hot_objects, &mut read_mostly], &epoch_file, &filter).is_err());
+}
+
+#[test]
+fn type_patterns() {
+    assert!(matches_pattern("pool.Pool", "pool.Pool"));
+    assert!(matches_pattern("pool.*", "pool.Pool"));
+    assert!(matches_pattern("*.Pool", "pool.Pool"));
+    assert!(matches_pattern("*o*l.*", "pool.Pool"));
+    assert!(matches_pattern("*", ""));
+    assert!(!matches_pattern("pool.*", "clock.Clock"));
+    assert!(!matches_pattern("pool", "pool.Pool"));
+    assert!(!matches_pattern("*.Poo", "pool.Pool"));
+}
+
+#[test]
+fn objects_treated_as_owned() {
+    // A and B are pools of package 0x2, C is a clock of package 0x3
+    let shared_object = |package: &str, type_: &str| SharedObjectData {
+        address: obj(package).to_string(),
+        type_: type_.to_string(),
+        is_resource: false,
+        num_txs: 1,
+        num_mut_refs: 0,
+        first_touched_at_epoch: 0,
+        first_touched_at_checkpoint: 0,
+        first_touched_by_txs: BTreeMap::new(),
+    };
+    let data = SharedObjectsData {
+        total_num_shared_objects: 3,
+        total_num_resources: 0,
+        shared_objects: [
+            (obj("0xa"), shared_object("0x2", "pool.Pool")),
+            (obj("0xb"), shared_object("0x2", "pool.Pool")),
+            (obj("0xc"), shared_object("0x3", "clock.Clock")),
+        ].into_iter().collect(),
+    };
+    let select = |entries: &[&str], data: Option<&SharedObjectsData>| {
+        let selectors: Vec<OwnedSelector> = entries
+            .iter()
+            .flat_map(|entry| OwnedSelector::parse(entry).unwrap())
+            .collect();
+        OwnedSelector::select(&selectors, data)
+    };
+
+    assert_eq!(select(&["0xc"], Some(&data)).unwrap(), BTreeSet::from([obj("0xc")]));
+    // a package selects all its shared objects
+    assert_eq!(select(&["0x2"], Some(&data)).unwrap(), BTreeSet::from([obj("0x2"), obj("0xa"), obj("0xb")]));
+    assert_eq!(select(&["*.Clock", "0xd"], Some(&data)).unwrap(), BTreeSet::from([obj("0xc"), obj("0xd")]));
+    // objects are selected by ID only without the data of `query-obj`
+    assert_eq!(select(&["0xa"], None).unwrap(), BTreeSet::from([obj("0xa")]));
+    assert!(select(&["pool.*"], None).is_err());
+    assert!(OwnedSelector::parse("0xnot-an-id").is_err());
+
+    // files list selectors line by line
+    let path = std::env::temp_dir().join(format!("treat-as-owned-{}.txt", std::process::id()));
+    std::fs::write(&path, "# pools\npool.*\n\n0xc\n").unwrap();
+    let selectors = OwnedSelector::parse(path.to_str().unwrap());
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(selectors.unwrap(), vec![OwnedSelector::Type(String::from("pool.*")), OwnedSelector::Id(obj("0xc"))]);
+}
+
+#[test]
+fn filters_without_objects() {
+    let objects = BTreeSet::from([obj("0xa")]);
+    assert_eq!(ObjectFilter::All.without(&objects), ObjectFilter::Exclude(objects.clone()));
+    assert_eq!(ObjectFilter::All.without(&BTreeSet::new()), ObjectFilter::All);
+    assert_eq!(ObjectFilter::Exclude(BTreeSet::from([obj("0xb")])).without(&objects),
+        ObjectFilter::Exclude(BTreeSet::from([obj("0xa"), obj("0xb")])));
+    assert_eq!(ObjectFilter::Only(BTreeSet::from([obj("0xa"), obj("0xb")])).without(&objects),
+        ObjectFilter::Only(BTreeSet::from([obj("0xb")])));
+
+    // treating A as owned leaves TXs 1, 3, 5 and 7 touching no shared objects
+    let density = run_with_config(&MetricsConfig {
+        intervals: vec![1],
+        object_filter: ObjectFilter::All.without(&objects),
+        ..Default::default()
+    });
+    let epochs = &density.data().epochs;
+    assert_eq!(epochs[&0].num_txs_touching_shared_objs, 2);
+    assert_eq!(epochs[&1].num_txs_touching_shared_objs, 1);
+}
+
+#[test]
+fn num_shared_objects() {
+    let density = run(&[1]);
+    assert_eq!(density.data().epochs[&0].num_shared_objects_per_epoch, 3);
+    assert_eq!(density.data().epochs[&0].num_shared_objects_total, 3);
+    assert_eq!(density.data().epochs[&1].num_shared_objects_per_epoch, 2);
+    assert_eq!(density.data().epochs[&1].num_shared_objects_total, 4);
+}
+
+#[test]
+fn shared_objects_set() {
+    let lifetimes = run_lifetimes();
+    let shared_objects_set = lifetimes.data();
+    let a = &shared_objects_set.shared_objects[&obj("0xa")];
+    assert_eq!(a.num_txs, 5);
+    assert_eq!(a.num_mut_refs, 3);
+    assert_eq!(a.first_touched_at_epoch, 0);
+    assert_eq!(a.first_touched_at_checkpoint, 0);
+    assert_eq!(a.first_touched_by_txs, BTreeMap::from([(tx(1), true), (tx(2), false)]));
+
+    let c = &shared_objects_set.shared_objects[&obj("0xc")];
+    assert_eq!((c.num_txs, c.num_mut_refs), (1, 1));
+    assert_eq!(c.first_touched_at_checkpoint, 3);
+
+    let d = &shared_objects_set.shared_objects[&obj("0xd")];
+    assert_eq!((d.first_touched_at_epoch, d.first_touched_at_checkpoint), (1, 4));
+}
+
+#[test]
+fn lifetimes() {
+    let lifetimes = run_lifetimes();
+    let shared_objects_set = lifetimes.data();
+    // A is touched in checkpoints 0, 1, 3 and 5
+    let a = &shared_objects_set.shared_objects[&obj("0xa")];
+    assert_eq!((a.last_touched_at_epoch, a.last_touched_at_checkpoint), (1, 5));
+    assert_eq!(a.num_active_checkpoints, 4);
+    assert_eq!(a.num_active_epochs, 2);
+    assert_eq!(a.longest_idle_gap, 1);
+    let b = &shared_objects_set.shared_objects[&obj("0xb")];
+    assert_eq!((b.num_active_checkpoints, b.num_active_epochs, b.longest_idle_gap), (1, 1, 0));
+
+    // B and C are only touched once in epoch 0, D in epoch 1, which is the
+    // last one, so D is not retired (yet)
+    let epoch = &shared_objects_set.epochs[&0];
+    assert_eq!(epoch.num_new_shared_objects, 3);
+    assert_eq!(epoch.num_retired_shared_objects, 2);
+    assert_eq!(epoch.num_one_shot_shared_objects, 2);
+    let epoch = &shared_objects_set.epochs[&1];
+    assert_eq!(epoch.num_new_shared_objects, 1);
+    assert_eq!(epoch.num_retired_shared_objects, 0);
+    assert_eq!(epoch.num_one_shot_shared_objects, 1);
+}
+
+#[test]
+fn pruned_epochs_give_same_metrics() {
+    let full = [result_data(0, 0, 3, epoch0()), result_data(1, 4, 5, epoch1())];
+    let config = tumbling(&[1, 2]);
+    let (mut full_density, mut full_lifetimes) = (Density::new(&config), Lifetimes::new());
+    let (mut pruned_density, mut pruned_lifetimes) = (Density::new(&config), Lifetimes::new());
+    let mut exact = Exact::new(&config);
+    for result in full {
+        observe_epoch(&mut [&mut pruned_density, &mut pruned_lifetimes, &mut exact],
+            &EpochFile::Pruned(result.prune()), &ObjectFilter::All).unwrap();
+        observe_epoch(&mut [&mut full_density, &mut full_lifetimes], &EpochFile::Full(result),
+            &ObjectFilter::All).unwrap();
+    }
+    full_lifetimes.finish_workspace();
+    pruned_lifetimes.finish_workspace();
+
+    // conflict graphs and exact counts need TX digests
+    assert!(exact.data().epochs.values().all(Option::is_none));
+    assert_eq!(exact.warnings().len(), 1);
+    let mut full_data = full_density.into_data();
+    for epoch_data in full_data.epochs.values_mut() {
+        assert!(epoch_data.conflict_graph.take().is_some());
+        for avg_interval_data in epoch_data.avg_interval_data.values_mut() {
+            assert!(avg_interval_data.conflict_graph.take().is_some());
+        }
+    }
+
+    assert_eq!(serde_json::to_string_pretty(&full_data).unwrap(), pruned_density.serialize().unwrap());
+    let (full_set, pruned_set) = (full_lifetimes.data(), pruned_lifetimes.data());
+    assert_eq!(full_set.epochs, pruned_set.epochs);
+    for (obj_id, pruned_obj) in pruned_set.shared_objects.iter() {
+        let full_obj = &full_set.shared_objects[obj_id];
+        assert_eq!(pruned_obj.num_txs, full_obj.num_txs);
+        assert_eq!(pruned_obj.num_mut_refs, full_obj.num_mut_refs);
+        assert_eq!(pruned_obj.first_touched_at_checkpoint, full_obj.first_touched_at_checkpoint);
+        assert!(pruned_obj.first_touched_by_txs.is_empty());
+    }
+}
+
+#[test]
+fn inconsistent_epoch_is_rejected() {
+    let mut result = result_data(0, 0, 3, epoch0());
+    result.num_txs_scanned += 1;
+    let mut density = Density::new(&tumbling(&[1]));
+    assert!(observe_epoch(&mut [&mut density], &EpochFile::Full(result), &ObjectFilter::All).is_err());
+}
+
+// Metrics that can be resumed from the results of a previous run
+fn resumable_metrics(config: &MetricsConfig) -> Vec<Box<dyn Metric>> {
+    vec![
+        Box::new(Density::new(config)),
+        Box::new(Distributions::new(config)),
+        Box::new(Concentration::new(config)),
+        Box::new(Exact::new(config)),
+        Box::new(Lifetimes::new()),
+    ]
+}
+
+fn as_dyn(metrics: &mut [Box<dyn Metric>]) -> Vec<&mut dyn Metric> {
+    metrics.iter_mut().map(|metric| -> &mut dyn Metric { metric.as_mut() }).collect()
+}
+
+#[test]
+fn resumed_metrics_give_same_results() {
+    let config = tumbling(&[1, 2]);
+    let results_dir = std::env::temp_dir().join(format!("resumed-metrics-{}", std::process::id()));
+    std::fs::create_dir_all(&results_dir).unwrap();
+
+    // metrics are saved to and read back from files between runs
+    let mut metrics = resumable_metrics(&config);
+    for metric in metrics.iter_mut() {
+        assert_eq!(metric.resume(&results_dir).unwrap(), None);
+    }
+    let [epoch_file0, epoch_file1] = epoch_files();
+    observe_epoch(&mut as_dyn(&mut metrics), &epoch_file0, &ObjectFilter::All).unwrap();
+    for metric in metrics.iter_mut() {
+        metric.finish_workspace();
+        std::fs::write(results_dir.join(metric.filename()), metric.serialize().unwrap()).unwrap();
+    }
+
+    let mut resumed = resumable_metrics(&config);
+    for metric in resumed.iter_mut() {
+        assert_eq!(metric.resume(&results_dir).unwrap(), Some(vec![0]), "{}", metric.name());
+    }
+    observe_epoch(&mut as_dyn(&mut resumed), &epoch_file1, &ObjectFilter::All).unwrap();
+
+    let mut metrics = resumable_metrics(&config);
+    observe_all(&mut as_dyn(&mut metrics));
+    for (metric, resumed) in metrics.iter_mut().zip(resumed.iter_mut()) {
+        metric.finish_workspace();
+        resumed.finish_workspace();
+        assert_eq!(resumed.serialize().unwrap(), metric.serialize().unwrap(), "{}", metric.name());
+    }
+
+    // results saved by an older version fail to parse
+    std::fs::write(results_dir.join(metrics[0].filename()), "{\"epochs\": 0}").unwrap();
+    assert!(metrics[0].resume(&results_dir).is_err());
+    assert!(read_saved::<EpochsData>(&results_dir, "missing.json").unwrap().is_none());
+    std::fs::remove_dir_all(&results_dir).unwrap();
+}
+
+#[test]
+fn epochs_read_in_parallel_give_same_metrics() {
+    let config = tumbling(&[1, 2]);
+    let mut metrics = resumable_metrics(&config);
+    observe_all(&mut as_dyn(&mut metrics));
+
+    for num_workers in [1, 2, 4] {
+        let mut parallel = resumable_metrics(&config);
+        let mut done = Vec::new();
+        process_epochs(&mut as_dyn(&mut parallel), &[0, 1], num_workers, &ObjectFilter::All,
+            |epoch| Ok(EpochFile::Full(match epoch {
+                0 => result_data(0, 0, 3, epoch0()),
+                _ => result_data(1, 4, 5, epoch1()),
+            })), |epoch| done.push(epoch)).unwrap();
+        // epochs are fed in order, whichever is read first
+        assert_eq!(done, vec![0, 1]);
+        for (metric, parallel) in metrics.iter_mut().zip(parallel.iter_mut()) {
+            metric.finish_workspace();
+            parallel.finish_workspace();
+            assert_eq!(parallel.serialize().unwrap(), metric.serialize().unwrap(), "{}", metric.name());
+        }
+    }
+}
+
+#[test]
+fn epochs_calculated_out_of_order_are_merged_in_order() {
+    let mut density = Density::new(&tumbling(&[1]));
+    let mut lifetimes = Lifetimes::new();
+    process_epochs(&mut [&mut density, &mut lifetimes], &[0, 1], 2, &ObjectFilter::All, |epoch| match epoch {
+        0 => {
+            // calculated after epoch 1
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            Ok(EpochFile::Full(result_data(0, 0, 3, epoch0())))
+        },
+        _ => Ok(EpochFile::Full(result_data(1, 4, 5, epoch1()))),
+    }, |_| ()).unwrap();
+    // A, B and C in epoch 0, D in epoch 1
+    let num_shared_objects_total: Vec<usize> = density.data()
+        .epochs
+        .values()
+        .map(|epoch_data| epoch_data.num_shared_objects_total)
+        .collect();
+    assert_eq!(num_shared_objects_total, vec![3, 4]);
+    let a = &lifetimes.data().shared_objects[&obj("0xa")];
+    assert_eq!((a.first_touched_at_epoch, a.first_touched_at_checkpoint), (0, 0));
+}
+
+#[test]
+fn first_touch_is_resolved_by_earliest_checkpoint() {
+    let calculate = |epoch_file: EpochFile| {
+        let mut part = Lifetimes::new().fork().unwrap();
+        observe_epoch(&mut [part.as_mut()], &epoch_file, &ObjectFilter::All).unwrap();
+        part
+    };
+    let epoch0 = calculate(EpochFile::Full(result_data(0, 0, 3, epoch0())));
+    let epoch1 = calculate(EpochFile::Full(result_data(1, 4, 5, epoch1())));
+
+    // merged out of order on purpose
+    let mut lifetimes = Lifetimes::new();
+    lifetimes.merge_epoch(epoch1);
+    assert_eq!(lifetimes.data().shared_objects[&obj("0xa")].first_touched_at_checkpoint, 5);
+    lifetimes.merge_epoch(epoch0);
+    let a = &lifetimes.data().shared_objects[&obj("0xa")];
+    assert_eq!((a.first_touched_at_epoch, a.first_touched_at_checkpoint), (0, 0));
+    assert_eq!(a.first_touched_by_txs, BTreeMap::from([(tx(1), true), (tx(2), false)]));
+    assert_eq!(a.num_txs, 5);
+    assert_eq!((a.last_touched_at_epoch, a.last_touched_at_checkpoint), (1, 5));
+    assert_eq!((a.num_active_checkpoints, a.num_active_epochs, a.longest_idle_gap), (4, 2, 1));
+}
+
+#[test]
+fn failed_epoch_is_reported() {
+    let mut density = Density::new(&tumbling(&[1]));
+    let mut done = Vec::new();
+    let result = process_epochs(&mut [&mut density], &[0, 1, 2], 2, &ObjectFilter::All, |epoch| match epoch {
+        0 => Ok(EpochFile::Full(result_data(0, 0, 3, epoch0()))),
+        _ => anyhow::bail!("epoch {} not found", epoch),
+    }, |epoch| done.push(epoch));
+    assert!(result.is_err());
+    assert_eq!(done, vec![0]);
+}
+
+#[test]
+fn sliding_windows_with_stride_of_interval_match_tumbling_windows() {
+    let tumbling = run(&[1, 2]);
+    let sliding = run_with_config(&sliding(&[1, 2], 2));
+    for epoch in [0, 1] {
+        for interval in [1, 2] {
+            let expected = &tumbling.data().epochs[&epoch].avg_interval_data[&interval];
+            let actual = &sliding.data().epochs[&epoch].avg_interval_data[&interval];
+            if interval == 2 {
+                assert_close(actual.contention_degree, expected.contention_degree);
+                assert_close(actual.obj_touchability, expected.obj_touchability);
+            }
+            assert!(actual.contention_degree_distribution.is_some());
+            assert!(expected.contention_degree_distribution.is_none());
+        }
+    }
+}
+
+#[test]
+fn sliding_windows() {
+    let density = run_with_config(&sliding(&[2, 5], 1));
+    let avg_interval_data = &density.data().epochs[&0].avg_interval_data[&2];
+    // windows of checkpoints 0..=1, 1..=2 and 2..=3: 3/2, 1/1, 2/2
+    assert_close(avg_interval_data.contention_degree, 3.5 / 3.0);
+    assert_eq!(avg_interval_data.contention_degree_distribution, Some(Distribution {
+        min: 1.0,
+        p50: 1.0,
+        p90: 1.5,
+        p99: 1.5,
+        max: 1.5,
+    }));
+    // A is touched by more than one TX in checkpoint 0 only
+    assert_close(avg_interval_data.obj_touchability, 0.5 / 3.0);
+    assert_eq!(avg_interval_data.obj_touchability_distribution.as_ref().unwrap().max, 0.5);
+
+    // windows are not carried over to the next epoch
+    let avg_interval_data = &density.data().epochs[&1].avg_interval_data[&2];
+    assert_close(avg_interval_data.contention_degree, 1.0);
+
+    // no window of 5 checkpoints fits into an epoch
+    let avg_interval_data = &density.data().epochs[&0].avg_interval_data[&5];
+    assert_close(avg_interval_data.contention_degree, 0.0);
+    assert!(avg_interval_data.contention_degree_distribution.is_none());
+}
+
+fn continuous(intervals: &[u64]) -> MetricsConfig {
+    MetricsConfig {
+        intervals: intervals.to_vec(),
+        continuous: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn continuous_windows() {
+    let density = run_with_config(&continuous(&[2, 3]));
+    let epoch = &density.data().epochs[&0];
+    // windows of checkpoints 0..=1 and 2..=3: 3/2, 2/2
+    assert_eq!(epoch.avg_interval_data[&2].num_windows, Some(2));
+    assert_close(epoch.avg_interval_data[&2].contention_degree, 2.5 / 2.0);
+    assert!(epoch.avg_interval_data[&2].partial_window.is_none());
+    // window of checkpoints 0..=2: 3/2, and checkpoint 3 is still open
+    assert_eq!(epoch.avg_interval_data[&3].num_windows, Some(1));
+    assert_close(epoch.avg_interval_data[&3].contention_degree, 1.5);
+    let partial = epoch.avg_interval_data[&3].partial_window.as_ref().unwrap();
+    assert_eq!(partial.num_checkpoints, 1);
+    assert_close(partial.contention_degree, 1.0);
+
+    // the window of checkpoints 3..=5 spans both epochs: C, A and D touched
+    // by 4 TXs
+    let epoch = &density.data().epochs[&1];
+    assert_eq!(epoch.avg_interval_data[&3].num_windows, Some(1));
+    assert_close(epoch.avg_interval_data[&3].contention_degree, 4.0 / 3.0);
+    assert!(epoch.avg_interval_data[&3].partial_window.is_none());
+    assert_close(epoch.avg_interval_data[&2].contention_degree, 1.0);
+
+    // without continuous mode, the same metrics are not reported
+    let density = run(&[3]);
+    let epoch = &density.data().epochs[&1];
+    assert!(epoch.avg_interval_data[&3].num_windows.is_none());
+    assert_close(epoch.avg_interval_data[&3].contention_degree, 0.0);
+}
+
+#[test]
+fn continuous_windows_are_cut_by_gaps() {
+    let mut density = Density::new(&continuous(&[3]));
+    observe_checkpoints(&mut [&mut density], 0, 0, 3, epoch0());
+    // checkpoints 4..=5 are missing, so checkpoint 3 is not carried over
+    observe_checkpoints(&mut [&mut density], 1, 6, 7, vec![
+        (6, checkpoint(1, 1, &[("0xd", &[(6, false)])])),
+        (7, checkpoint(1, 1, &[("0xa", &[(7, true)])])),
+    ]);
+
+    let avg_interval_data = &density.data().epochs[&1].avg_interval_data[&3];
+    assert_eq!(avg_interval_data.num_windows, Some(0));
+    assert_close(avg_interval_data.contention_degree, 0.0);
+    let partial = avg_interval_data.partial_window.as_ref().unwrap();
+    assert_eq!(partial.num_checkpoints, 2);
+    assert_close(partial.contention_degree, 1.0);
+}
+
+#[test]
+fn continuous_exact_windows() {
+    let config = continuous(&[2, 3]);
+    let [epoch_file0, epoch_file1] = epoch_files();
+    let mut exact = Exact::new(&config);
+    observe_epoch(&mut [&mut exact], &epoch_file0, &ObjectFilter::All).unwrap();
+    // the open window of checkpoint 3 is exact too, and only digests of its
+    // TXs 4 and 5 are still kept
+    let epoch = exact.data().epochs[&0].as_ref().unwrap();
+    assert_close(epoch.avg_interval_data[&3].partial_window.unwrap(), 1.0);
+    assert_eq!(exact.num_tx_ids(), 2);
+    observe_epoch(&mut [&mut exact], &epoch_file1, &ObjectFilter::All).unwrap();
+    assert_eq!(exact.num_tx_ids(), 0);
+
+    // given pruned epoch data of epoch 0, the window of checkpoints 3..=5
+    // spanning both epochs is left out of exact values, unlike the window of
+    // checkpoints 4..=5
+    let mut exact = Exact::new(&config);
+    let epoch_file0 = EpochFile::Pruned(result_data(0, 0, 3, epoch0()).prune());
+    observe_epoch(&mut [&mut exact], &epoch_file0, &ObjectFilter::All).unwrap();
+    assert!(exact.data().epochs[&0].is_none());
+    observe_epoch(&mut [&mut exact], &epoch_file1, &ObjectFilter::All).unwrap();
+    let epoch = exact.data().epochs[&1].as_ref().unwrap();
+    assert!(epoch.avg_interval_data[&3].contention_degree.is_none());
+    assert_close(epoch.avg_interval_data[&2].contention_degree.unwrap(), 1.0);
+}
+
+#[test]
+fn continuous_sliding_windows() {
+    // with a stride of the interval, sliding windows are the same as
+    // tumbling windows spanning epochs
+    let density = run_with_config(&MetricsConfig {
+        stride: Some(3),
+        ..continuous(&[3])
+    });
+    for (epoch, expected) in [(0, 1.5), (1, 4.0 / 3.0)] {
+        let avg_interval_data = &density.data().epochs[&epoch].avg_interval_data[&3];
+        assert_eq!(avg_interval_data.num_windows, Some(1));
+        assert_close(avg_interval_data.contention_degree, expected);
+    }
+}
+
+#[test]
+fn continuous_epochs_are_processed_in_order() {
+    let density = run_with_config(&continuous(&[3]));
+    let mut parallel_density = Density::new(&continuous(&[3]));
+    let mut done = Vec::new();
+    process_epochs(&mut [&mut parallel_density], &[0, 1], 4, &ObjectFilter::All,
+        |epoch| Ok(EpochFile::Full(match epoch {
+            0 => result_data(0, 0, 3, epoch0()),
+            _ => result_data(1, 4, 5, epoch1()),
+        })), |epoch| done.push(epoch)).unwrap();
+    assert_eq!(done, vec![0, 1]);
+    assert_eq!(parallel_density.serialize().unwrap(), density.serialize().unwrap());
+}
+
+#[test]
+fn percentiles() {
+    let values: Vec<f64> = (1..=100).map(|i| i as f64).collect();
+    assert_eq!(stats::percentile(&values, 50.0), 50.0);
+    assert_eq!(stats::percentile(&values, 99.0), 99.0);
+    assert_eq!(stats::percentile(&values, 0.0), 1.0);
+    assert_eq!(stats::percentile(&[3.0], 90.0), 3.0);
+    assert_close(stats::mean(&values), 50.5);
+    assert_eq!(stats::mean(&[]), 0.0);
+}
+
+#[test]
+fn percentiles_of_counts() {
+    // 1 (x5), 2 (x4), 10 (x1)
+    let counts = BTreeMap::from([(1, 5), (2, 4), (10, 1)]);
+    assert_eq!(stats::percentile_of_counts(&counts, 50.0), 1);
+    assert_eq!(stats::percentile_of_counts(&counts, 90.0), 2);
+    assert_eq!(stats::percentile_of_counts(&counts, 99.0), 10);
+    assert_eq!(Distribution::of_counts(&counts), Some(Distribution {
+        min: 1.0,
+        p50: 1.0,
+        p90: 2.0,
+        p99: 10.0,
+        max: 10.0,
+    }));
+    assert_eq!(Distribution::of_counts(&BTreeMap::new()), None);
+}
+
+#[test]
+fn burstiness() {
+    // perfectly regular
+    assert_close(stats::burstiness_of_counts(&BTreeMap::from([(3, 10)])).unwrap(), -1.0);
+    // 0 (x3), 4 (x1): mean 1, std sqrt(3)
+    let burstiness = (3f64.sqrt() - 1.0) / (3f64.sqrt() + 1.0);
+    assert_close(stats::burstiness_of_counts(&BTreeMap::from([(0, 3), (4, 1)])).unwrap(), burstiness);
+    assert_eq!(stats::burstiness_of_counts(&BTreeMap::from([(0, 2)])), None);
+    assert_eq!(stats::burstiness_of_counts(&BTreeMap::new()), None);
+}
+
+#[test]
+fn hot_objects() {
+    // A is touched by the most TXs, then B, C and D by one each
+    let mut hot_objects = HotObjects::new(2);
+    observe_all(&mut [&mut hot_objects]);
+    let data = hot_objects.data();
+    assert_eq!(data.objects.iter().map(|object| object.object).collect::<Vec<_>>(), vec![obj("0xa"), obj("0xb")]);
+
+    // A is touched by 2 TXs in checkpoint 0 and by 1 TX in checkpoints 1, 3
+    // and 5: inter-arrival times of 0, 1, 2 and 2 checkpoints
+    let a = &data.objects[0];
+    assert_eq!(a.num_txs, 5);
+    assert_eq!(a.num_active_checkpoints, 4);
+    assert_eq!(a.txs_per_epoch, BTreeMap::from([(0, 4), (1, 1)]));
+    assert_eq!((a.peak_txs_per_checkpoint, a.peak_checkpoint), (2, 0));
+    assert_eq!(a.inter_arrival_checkpoints, HistogramData::of_counts(&BTreeMap::from([(0, 1), (1, 1), (2, 2)])));
+    let (mean, std) = (1.25, 0.6875f64.sqrt());
+    assert_close(a.burstiness_checkpoints.unwrap(), (std - mean) / (std + mean));
+    // no timestamps
+    assert_eq!(a.inter_arrival_secs, None);
+    assert_eq!(a.burstiness_secs, None);
+
+    // B is touched by a single TX
+    let b = &data.objects[1];
+    assert_eq!(b.num_txs, 1);
+    assert_eq!(b.inter_arrival_checkpoints, None);
+    assert_eq!(b.burstiness_checkpoints, None);
+}
+
+#[test]
+fn hot_objects_over_wall_clock_time() {
+    // checkpoints touching A are created at 0, 0.4, 3 and 5 seconds
+    let mut checkpoints: Vec<(u64, CheckpointData)> = epoch0().into_iter().chain(epoch1()).collect();
+    for (checkpoint, checkpoint_data) in checkpoints.iter_mut() {
+        checkpoint_data.timestamp_ms = Some([0, 400, 1500, 3000, 4000, 5000][*checkpoint as usize]);
+    }
+    let mut hot_objects = HotObjects::new(1);
+    hot_objects.begin_epoch(0, 0, 5);
+    for (checkpoint, checkpoint_data) in checkpoints.iter() {
+        hot_objects.observe_checkpoint(*checkpoint, &checkpoint_data.prune(), None);
+    }
+    let a = &hot_objects.data().objects[0];
+    // 0, 0.4, 2.6 and 2 seconds
+    assert_eq!(a.inter_arrival_secs, Some(Distribution {
+        min: 0.0,
+        p50: 0.4,
+        p90: 2.6,
+        p99: 2.6,
+        max: 2.6,
+    }));
+    let counts = BTreeMap::from([(0, 1), (400, 1), (2000, 1), (2600, 1)]);
+    assert_eq!(a.burstiness_secs, stats::burstiness_of_counts(&counts));
+
+    // a single checkpoint without timestamp leaves out times in seconds
+    checkpoints[1].1.timestamp_ms = None;
+    let mut hot_objects = HotObjects::new(1);
+    hot_objects.begin_epoch(0, 0, 5);
+    for (checkpoint, checkpoint_data) in checkpoints.iter() {
+        hot_objects.observe_checkpoint(*checkpoint, &checkpoint_data.prune(), None);
+    }
+    let a = &hot_objects.data().objects[0];
+    assert!(a.inter_arrival_checkpoints.is_some());
+    assert_eq!(a.inter_arrival_secs, None);
+    assert_eq!(a.burstiness_secs, None);
+}
+
+#[test]
+fn read_mostly_objects() {
+    // only B's type is known
+    let types = HashMap::from([(obj("0xb"), String::from("0x2::m::T"))]);
+    let mut read_mostly = ReadMostly::new(0.0, Some(Arc::new(types)));
+    observe_all(&mut [&mut read_mostly]);
+    let data = read_mostly.data();
+
+    // A (3 out of 5 TXs mutate it) and C (1 out of 1) are not read-only
+    assert_eq!(data.objects, vec![
+        ReadMostlyObjectData {
+            object: obj("0xb"),
+            num_txs: 1,
+            num_mut_refs: 0,
+            mut_ratio: 0.0,
+            type_: Some(String::from("0x2::m::T")),
+        },
+        ReadMostlyObjectData {
+            object: obj("0xd"),
+            num_txs: 1,
+            num_mut_refs: 0,
+            mut_ratio: 0.0,
+            type_: None,
+        },
+    ]);
+    assert_eq!((data.num_objects, data.num_read_only_objects, data.num_txs), (2, 2, 2));
+    assert_eq!(data.types, Some(BTreeMap::from([(String::from("0x2::m::T"), ReadMostlyTypeData {
+        num_objects: 1,
+        num_txs: 1,
+        num_mut_refs: 0,
+    })])));
+
+    // TX 2 still touches A, but TX 6 touches D only
+    assert_eq!(data.epochs[&0].num_txs_touching_shared_objs_without_read_mostly, Some(5));
+    assert_close(data.epochs[&0].density_without_read_mostly.unwrap(), data.epochs[&0].density);
+    assert_eq!(data.epochs[&1].num_txs_touching_shared_objs_without_read_mostly, Some(1));
+    assert_close(data.epochs[&1].density, 1.0);
+    assert_close(data.epochs[&1].density_without_read_mostly.unwrap(), 0.5);
+
+    // A is read-mostly given a higher threshold, so TXs 6 and 7 touch
+    // read-mostly objects only; pruned epochs have no densities without
+    // read-mostly objects
+    let mut read_mostly = ReadMostly::new(0.6, None);
+    let epoch_file = EpochFile::Pruned(result_data(0, 0, 3, epoch0()).prune());
+    observe_epoch(&mut [&mut read_mostly], &epoch_file, &ObjectFilter::All).unwrap();
+    observe_checkpoints(&mut [&mut read_mostly], 1, 4, 5, epoch1());
+    let data = read_mostly.data();
+    assert_eq!(data.objects.iter().map(|obj| obj.object).collect::<Vec<_>>(),
+        vec![obj("0xa"), obj("0xb"), obj("0xd")]);
+    assert_eq!(data.types, None);
+    assert_close(data.epochs[&0].density, 5.0 / 10.0);
+    assert_eq!(data.epochs[&0].density_without_read_mostly, None);
+    assert_eq!(data.epochs[&1].num_txs_touching_shared_objs_without_read_mostly, Some(0));
+    assert_eq!(read_mostly.warnings().len(), 1);
+}
+
+#[test]
+fn single_writer_objects() {
+    // TXs 1, 3 and 5 are sent by S1, TXs 2, 6 and 7 by S2, and TX 4 by S3
+    let sender = |n: u8| SuiAddress::from_str(match n {
+        1 | 3 | 5 => "0x1",
+        2 | 6 | 7 => "0x2",
+        _ => "0x3",
+    }).unwrap();
+    let mut checkpoints: Vec<CheckpointData> = epoch0().into_iter().chain(epoch1()).map(|(_, data)| data).collect();
+    for checkpoint_data in checkpoints.iter_mut() {
+        checkpoint_data.senders = (1..=7).map(|n| (tx(n), sender(n)))
+            .filter(|(tx_digest, _)| checkpoint_data.shared_objects.values().any(|txs| txs.contains_key(tx_digest)))
+            .collect();
+    }
+    // another checkpoint of epoch data queried before senders were recorded
+    checkpoints.push(checkpoint(1, 1, &[("0xc", &[(8, true)])]));
+
+    let types = HashMap::from([(obj("0xa"), String::from("0x2::m::T")), (obj("0xc"), String::from("0x3::n::U"))]);
+    let packages = HashMap::from([(obj("0xa"), String::from("0x2")), (obj("0xc"), String::from("0x3"))]);
+    let (types, packages) = (Arc::new(types), Arc::new(packages));
+    let single_writers = |max_senders: usize| {
+        let mut single_writers = SingleWriters::new(max_senders, Some(types.clone()), Some(packages.clone()));
+        single_writers.begin_epoch(0, 0, checkpoints.len() - 1);
+        for (checkpoint, checkpoint_data) in checkpoints.iter().enumerate() {
+            single_writers.observe_checkpoint(checkpoint as u64, &checkpoint_data.prune(), Some(checkpoint_data));
+        }
+        single_writers.data()
+    };
+
+    // A is mutated by S1 and S2, B and D are never mutated, and C is only
+    // mutated by S3
+    let data = single_writers(1);
+    assert_eq!((data.num_txs_touching_shared_objs, data.num_checkpoints_without_senders), (7, 1));
+    assert_eq!(data.objects.len(), 1);
+    let c = &data.objects[0];
+    assert_eq!((c.object, c.num_txs, c.num_mut_refs, c.num_senders), (obj("0xc"), 1, 1, 1));
+    assert_eq!(c.mut_senders, vec![sender(4)]);
+    assert_close(c.tx_share, 1.0 / 7.0);
+    assert_eq!((c.type_.as_deref(), c.package.as_deref()), (Some("0x3::n::U"), Some("0x3")));
+
+    let data = single_writers(2);
+    assert_eq!(data.objects.iter().map(|obj| obj.object).collect::<Vec<_>>(), vec![obj("0xa"), obj("0xc")]);
+    let a = &data.objects[0];
+    assert_eq!((a.num_txs, a.num_mut_refs, a.num_senders), (5, 3, 2));
+    assert_eq!(a.mut_senders, vec![sender(1), sender(2)]);
+    assert_eq!(data.packages.unwrap()[&String::from("0x2")], SingleWriterGroupData {
+        num_objects: 1,
+        num_txs: 5,
+        tx_share: 5.0 / 7.0,
+    });
+    assert_eq!(data.types.unwrap().len(), 2);
+
+    // senders of TXs touching only dropped objects are dropped too
+    let filtered = ObjectFilter::Exclude(BTreeSet::from([obj("0xc")])).apply(&checkpoints[3]);
+    assert_eq!(filtered.senders, BTreeMap::from([(tx(5), sender(5))]));
+}
+
+#[test]
+fn clock_object() {
+    // TXs 1 and 2 touch the Clock in checkpoint 0, TX 2 also touches A;
+    // TXs 3 and 4 touch only the Clock in checkpoint 1
+    let mut clock = Clock::new();
+    observe_checkpoints(&mut [&mut clock], 0, 0, 1, vec![
+        (0, checkpoint(2, 2, &[(CLOCK_OBJECT, &[(1, false), (2, false)]), ("0xa", &[(2, true)])])),
+        (1, checkpoint(2, 2, &[(CLOCK_OBJECT, &[(3, false), (4, false)])])),
+    ]);
+    let epoch_file = EpochFile::Pruned(result_data(1, 4, 5, epoch1()).prune());
+    observe_epoch(&mut [&mut clock], &epoch_file, &ObjectFilter::All).unwrap();
+
+    let data = clock.data();
+    assert_eq!((data.num_txs, data.num_txs_touching_other_shared_objs, data.num_txs_touching_only_clock), (4, 1, 3));
+    // pruned epoch 1 is not counted
+    assert_eq!(data.num_txs_touching_only_clock_per_epoch, BTreeMap::from([(0, 3)]));
+    assert_eq!(clock.warnings().len(), 1);
+    // the first of the checkpoints with the most TXs touching the Clock
+    let peak = data.peak_checkpoint.as_ref().unwrap();
+    assert_eq!((peak.epoch, peak.checkpoint, peak.num_txs), (0, 0, 2));
+    assert_eq!(peak.txs, vec![tx(1), tx(2)]);
+}
+
+#[test]
+fn pyth_state_object() {
+    // TX 1 mutates the State object in epoch 0, which is pruned in epoch 1
+    let mut pyth_state = PythState::new();
+    observe_checkpoints(&mut [&mut pyth_state], 0, 0, 0, vec![
+        (0, checkpoint(2, 2, &[(PYTH_STATE_OBJECT, &[(1, true), (2, false)])])),
+    ]);
+    let epoch_file = EpochFile::Pruned(result_data(1, 1, 1, vec![
+        (1, checkpoint(1, 1, &[(PYTH_STATE_OBJECT, &[(3, true)])])),
+    ]).prune());
+    observe_epoch(&mut [&mut pyth_state], &epoch_file, &ObjectFilter::All).unwrap();
+
+    let data = pyth_state.data();
+    assert_eq!((data.num_txs, data.num_mut_refs), (3, 2));
+    assert_eq!(data.mut_txs, vec![tx(1)]);
+    assert_eq!(pyth_state.warnings().len(), 1);
+}
+
+#[test]
+fn histograms() {
+    let histogram = HistogramData::of_values(&[0.0, 0.2, 0.5, 0.75, 1.0], 0.25).unwrap();
+    assert_eq!(histogram.histogram, BTreeMap::from([(0, 2), (2, 1), (3, 1), (4, 1)]));
+    assert_eq!(histogram.distribution.p50, 0.5);
+    assert!(HistogramData::of_values(&[], 0.25).is_none());
+}
+
+#[test]
+fn distributions() {
+    let mut distributions = Distributions::new(&tumbling(&[1, 2]));
+    observe_all(&mut [&mut distributions]);
+    let epoch = &distributions.data().epochs[&0];
+
+    // TXs 1, 3, 4 and 5 touch one shared object, TX 2 touches two
+    let shared_objects_per_tx = epoch.shared_objects_per_tx.as_ref().unwrap();
+    assert_eq!(shared_objects_per_tx.histogram, BTreeMap::from([(1, 4), (2, 1)]));
+    assert_eq!(shared_objects_per_tx.distribution.p50, 1.0);
+    assert_eq!(shared_objects_per_tx.distribution.max, 2.0);
+
+    // A is touched by two TXs in checkpoint 0
+    let txs_per_object = epoch.txs_per_object_per_checkpoint.as_ref().unwrap();
+    assert_eq!(txs_per_object.histogram, BTreeMap::from([(1, 4), (2, 1)]));
+
+    // intervals of 1 checkpoint: 2/2, 1/1, (none), 2/2
+    let contention_degree = &epoch.contention_degree[&1];
+    assert_eq!(contention_degree.histogram, BTreeMap::from([(0, 1), (4, 3)]));
+    assert_eq!(contention_degree.distribution.min, 0.0);
+    assert_eq!(contention_degree.distribution.p50, 1.0);
+    // intervals of 2 checkpoints: 3/2, 2/2
+    assert_eq!(epoch.contention_degree[&2].histogram, BTreeMap::from([(4, 1), (6, 1)]));
+
+    // only known with TX digests
+    let mut distributions = Distributions::new(&tumbling(&[1]));
+    let epoch_file = EpochFile::Pruned(result_data(0, 0, 3, epoch0()).prune());
+    observe_epoch(&mut [&mut distributions], &epoch_file, &ObjectFilter::All).unwrap();
+    let epoch = &distributions.data().epochs[&0];
+    assert!(epoch.shared_objects_per_tx.is_none());
+    assert!(epoch.txs_per_object_per_checkpoint.is_some());
+}
+
+#[test]
+fn concentration() {
+    // A is touched by 4 TXs, B and C by one each
+    let data = ConcentrationData::of(&[4, 1, 1]).unwrap();
+    assert_eq!(data.num_touched, 3);
+    assert_eq!(data.num_txs, 6);
+    // (2 * (1 + 2 + 3 * 4) / (3 * 6)) - 4 / 3
+    assert_close(data.gini, 1.0 / 3.0);
+    assert_close(data.hhi, (16.0 + 1.0 + 1.0) / 36.0);
+    assert_eq!(data.top_shares.keys().copied().collect::<Vec<_>>(), vec![1, 10, 100]);
+    assert_close(data.top_shares[&1], 4.0 / 6.0);
+    assert_close(data.top_shares[&10], 1.0);
+
+    // evenly touched objects
+    let data = ConcentrationData::of(&[2, 2]).unwrap();
+    assert_close(data.gini, 0.0);
+    assert_close(data.hhi, 0.5);
+    assert!(ConcentrationData::of(&[]).is_none());
+}
+
+#[test]
+fn concentration_over_packages() {
+    // A and C belong to the same package, B to another one, and the package
+    // of D is unknown
+    let config = MetricsConfig {
+        intervals: vec![1],
+        packages: Some(Arc::new(HashMap::from([
+            (obj("0xa"), String::from("0x1")),
+            (obj("0xb"), String::from("0x2")),
+            (obj("0xc"), String::from("0x1")),
+        ]))),
+        ..Default::default()
+    };
+    let mut concentration = Concentration::new(&config);
+    observe_all(&mut [&mut concentration]);
+    let epoch = &concentration.data().epochs[&0];
+    assert_eq!(epoch.objects, ConcentrationData::of(&[4, 1, 1]));
+    assert_eq!(epoch.packages, ConcentrationData::of(&[5, 1]));
+
+    let epoch = &concentration.data().epochs[&1];
+    assert_eq!(epoch.objects, ConcentrationData::of(&[1, 1]));
+    assert_eq!(epoch.packages, ConcentrationData::of(&[1]));
+
+    let mut concentration = Concentration::new(&tumbling(&[1]));
+    observe_all(&mut [&mut concentration]);
+    assert!(concentration.data().epochs[&0].packages.is_none());
+}
+
+#[test]
+fn packages_fingerprint() {
+    let config = |packages: &[(&str, &str)]| MetricsConfig {
+        packages: Some(Arc::new(packages.iter().map(|(id, package)| (obj(id), package.to_string())).collect())),
+        ..Default::default()
+    };
+    let fingerprint = config(&[("0xa", "0x1"), ("0xb", "0x2")]).packages_fingerprint();
+    assert!(fingerprint.is_some());
+    assert_eq!(config(&[("0xb", "0x2"), ("0xa", "0x1")]).packages_fingerprint(), fingerprint);
+    assert_ne!(config(&[("0xa", "0x1"), ("0xb", "0x1")]).packages_fingerprint(), fingerprint);
+    assert_ne!(config(&[("0xa", "0x1")]).packages_fingerprint(), fingerprint);
+    assert_eq!(MetricsConfig::default().packages_fingerprint(), None);
+}
+
+#[test]
+fn wall_clock_windows() {
+    // checkpoints of epoch 0 are created at 0, 0.4, 1.5 and 3.2 seconds
+    let mut checkpoints = epoch0();
+    for ((_, checkpoint_data), timestamp_ms) in checkpoints.iter_mut().zip([0, 400, 1500, 3200]) {
+        checkpoint_data.timestamp_ms = Some(1_700_000_000_000 + timestamp_ms);
+    }
+    let config = MetricsConfig {
+        intervals: vec![1],
+        interval_secs: vec![1, 2],
+        ..Default::default()
+    };
+
+    for epoch_file in [
+        EpochFile::Full(result_data(0, 0, 3, checkpoints)),
+        EpochFile::Pruned(result_data(0, 0, 3, epoch0()).prune()),
+    ] {
+        let pruned = matches!(epoch_file, EpochFile::Pruned(_));
+        let mut density = Density::new(&config);
+        observe_epoch(&mut [&mut density], &epoch_file, &ObjectFilter::All).unwrap();
+        let avg_interval_secs_data = &density.data().epochs[&0].avg_interval_secs_data;
+        if pruned {
+            // no timestamps in this epoch data
+            assert!(avg_interval_secs_data.is_empty());
+            assert_eq!(density.warnings().len(), 1);
+            continue;
+        }
+
+        // windows of 1 s: checkpoints 0 and 1 (3/2), checkpoint 2 (no shared
+        // objects), no checkpoints; the window of checkpoint 3 is unfinished
+        // and 0.2 s of it elapsed
+        assert_close(avg_interval_secs_data[&1].contention_degree, 1.5 / 3.2);
+        assert_close(avg_interval_secs_data[&1].obj_touchability, 0.5 / 3.2);
+        // windows of 2 s: checkpoints 0 to 2 (3/2), then 1.2 s of the
+        // unfinished window of checkpoint 3
+        assert_close(avg_interval_secs_data[&2].contention_degree, 1.5 / 1.6);
+        assert_close(avg_interval_secs_data[&2].obj_touchability, 0.5 / 1.6);
+        // a chain of 2 TXs in the first window, the empty ones count as 0
+        let graph = avg_interval_secs_data[&1].conflict_graph.as_ref().unwrap();
+        assert_close(graph.avg_longest_chain, 2.0 / 3.2);
+        assert_eq!(graph.max_longest_chain, 2);
+    }
+}
+
+#[test]
+fn timestamps_are_kept_when_pruning() {
+    let mut checkpoint_data = checkpoint(1, 1, &[("0xa", &[(1, true)])]);
+    checkpoint_data.timestamp_ms = Some(42);
+    assert_eq!(checkpoint_data.prune().timestamp_ms, Some(42));
+}
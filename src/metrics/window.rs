@@ -0,0 +1,654 @@
+use std::collections::{
+    BTreeMap,
+    HashMap,
+    VecDeque,
+};
+
+use crate::types::{
+    AvgIntervalData,
+    ConflictGraphData,
+    Distribution,
+    PartialWindowData,
+};
+use crate::metrics::MetricsConfig;
+use crate::metrics::conflicts::{
+    ConflictGraph,
+    ConflictGraphCounts,
+};
+
+// A shared object touched within a checkpoint. Shared objects are interned
+// IDs (see `utils::Interner`)
+#[derive(Debug, Clone, Copy)]
+pub struct TouchedObject {
+    pub index: u32,
+    pub num_txs: usize,
+    pub num_mut_refs: usize,
+}
+
+// TXs of a checkpoint with TX digests, as far as a metric needs them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CheckpointTxs<'a> {
+    // interned digests of the TXs, to count TXs exactly
+    pub digests: &'a [u32],
+    // shared objects (interned IDs) touched by each TX, and whether by a
+    // mutable reference, in the order of `conflicts::tx_accesses`, to build
+    // conflict graphs
+    pub accesses: &'a [Vec<(u32, bool)>],
+}
+
+// Counts of a shared object within a window
+#[derive(Debug, Default)]
+struct ObjectCounts {
+    // the number of checkpoints within the window in which the object was
+    // touched, and in how many of those it was touched by more than one TX
+    num_checkpoints: usize,
+    num_checkpoints_touched_by_more_than_1tx: usize,
+    num_txs: usize,
+    num_mut_refs: usize,
+}
+
+// Counts of TXs touching shared objects and of the shared objects touched
+// within a window of checkpoints
+#[derive(Debug, Default)]
+pub struct WindowCounts {
+    num_checkpoints: u64,
+    num_txs: u64,
+    objects: HashMap<u32, ObjectCounts>,
+    // unique TXs (interned digests) and the number of checkpoints within the
+    // window they are in; only tracked when counting TXs exactly
+    txs: HashMap<u32, usize>,
+    // `CheckpointTxs::accesses` of every checkpoint within the window with
+    // TX digests; only tracked for conflict graphs
+    accesses: VecDeque<Vec<Vec<(u32, bool)>>>,
+    // the number of checkpoints within the window without TX digests
+    // (pruned), whose TXs cannot be counted exactly
+    num_pruned_checkpoints: u64,
+}
+
+impl WindowCounts {
+    // Add a checkpoint to the window; `txs` is `None` if the checkpoint has
+    // no TX digests
+    pub fn add(&mut self, num_txs: u64, objects: &[TouchedObject], txs: Option<CheckpointTxs>) {
+        self.num_checkpoints += 1;
+        self.num_txs += num_txs;
+        match txs {
+            Some(txs) => {
+                for tx in txs.digests.iter() {
+                    *self.txs.entry(*tx).or_default() += 1;
+                }
+                self.accesses.push_back(txs.accesses.to_vec());
+            },
+            None => self.num_pruned_checkpoints += 1,
+        }
+        for obj in objects.iter() {
+            let counts = self.objects.entry(obj.index).or_default();
+            counts.num_checkpoints += 1;
+            if obj.num_txs > 1 {
+                counts.num_checkpoints_touched_by_more_than_1tx += 1;
+            }
+            counts.num_txs += obj.num_txs;
+            counts.num_mut_refs += obj.num_mut_refs;
+        }
+    }
+
+    // Remove the first checkpoint added to the window
+    pub fn remove(&mut self, num_txs: u64, objects: &[TouchedObject], txs: Option<CheckpointTxs>) {
+        self.num_checkpoints -= 1;
+        self.num_txs -= num_txs;
+        match txs {
+            Some(txs) => {
+                for tx in txs.digests.iter() {
+                    let count = self.txs.get_mut(tx).unwrap();
+                    *count -= 1;
+                    if *count == 0 {
+                        self.txs.remove(tx);
+                    }
+                }
+                self.accesses.pop_front();
+            },
+            None => self.num_pruned_checkpoints -= 1,
+        }
+        for obj in objects.iter() {
+            let counts = self.objects.get_mut(&obj.index).unwrap();
+            counts.num_checkpoints -= 1;
+            if obj.num_txs > 1 {
+                counts.num_checkpoints_touched_by_more_than_1tx -= 1;
+            }
+            counts.num_txs -= obj.num_txs;
+            counts.num_mut_refs -= obj.num_mut_refs;
+            if counts.num_checkpoints == 0 {
+                self.objects.remove(&obj.index);
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.num_checkpoints = 0;
+        self.num_txs = 0;
+        self.objects.clear();
+        self.txs.clear();
+        self.accesses.clear();
+        self.num_pruned_checkpoints = 0;
+    }
+
+    // Whether a TX (interned digest) is within the window
+    pub fn contains_tx(&self, tx: u32) -> bool {
+        self.txs.contains_key(&tx)
+    }
+
+    // Metrics of an unfinished window, `None` if it has no checkpoints yet
+    pub fn partial(&self) -> Option<PartialWindowData> {
+        if self.num_checkpoints == 0 {
+            return None;
+        }
+        let mut series = WindowSeries::default();
+        series.record(self);
+        Some(PartialWindowData {
+            num_checkpoints: self.num_checkpoints,
+            contention_degree: series.contention_degrees[0],
+            obj_touchability: series.obj_touchabilities[0],
+            conflict_contention_degree: series.conflict_contention_degrees[0],
+            conflicting_obj_fraction: series.conflicting_obj_fractions[0],
+            conflict_graph: series.conflict_graph(1.0),
+        })
+    }
+
+    // Exact contention degree of an unfinished window, `None` if it has no
+    // checkpoints yet or includes checkpoints without TX digests
+    pub fn exact_contention_degree(&self) -> Option<f64> {
+        if self.num_checkpoints == 0 {
+            return None;
+        }
+        let mut series = WindowSeries::default();
+        series.record(self);
+        series.exact_contention_degrees[0]
+    }
+}
+
+// Metrics of every window so far
+#[derive(Debug, Default)]
+pub struct WindowSeries {
+    pub contention_degrees: Vec<f64>,
+    pub obj_touchabilities: Vec<f64>,
+    pub conflict_contention_degrees: Vec<f64>,
+    pub conflicting_obj_fractions: Vec<f64>,
+    // `None` for windows including checkpoints without TX digests
+    pub exact_contention_degrees: Vec<Option<f64>>,
+    pub conflict_graphs: Vec<Option<ConflictGraph>>,
+}
+
+impl WindowSeries {
+    // Record metrics of a window; all of them are 0 if no shared objects
+    // were touched within it
+    pub fn record(&mut self, counts: &WindowCounts) {
+        // TXs of a window are ordered by their checkpoints, then as in
+        // `conflicts::tx_accesses`
+        self.conflict_graphs.push((counts.num_pruned_checkpoints == 0)
+            .then(|| ConflictGraph::of_interned(counts.accesses.iter().flatten())));
+        if counts.objects.is_empty() {
+            self.record_objects_empty();
+            return;
+        }
+        let num_objects = counts.objects.len() as f64;
+
+        // Contention degree is the number of TXs touching shared objects
+        // divided by the number of unique touched shared objects
+        self.contention_degrees.push(counts.num_txs as f64 / num_objects);
+        self.exact_contention_degrees.push((counts.num_pruned_checkpoints == 0)
+            .then(|| counts.txs.len() as f64 / num_objects));
+
+        // Object touchability is the number of objects touched by more than
+        // one TX (within a checkpoint) divided by the number of shared objects
+        let num_touched_by_more_than_1tx = counts.objects
+            .values()
+            .filter(|obj| obj.num_checkpoints_touched_by_more_than_1tx > 0)
+            .count();
+        self.obj_touchabilities.push(num_touched_by_more_than_1tx as f64 / num_objects);
+
+        // Conflict contention degree only counts accesses to objects written
+        // (passed by a mutable reference) within the window, since accesses to
+        // objects that are only read never conflict
+        let written_objects = counts.objects.values().filter(|obj| obj.num_mut_refs > 0);
+        let (num_written, num_accesses) = written_objects
+            .fold((0, 0), |(num, accesses), obj| (num + 1, accesses + obj.num_txs));
+        self.conflict_contention_degrees.push(if num_written > 0 {
+            num_accesses as f64 / num_written as f64
+        } else {
+            0.0
+        });
+
+        // Conflicting objects are written and accessed by at least two TXs
+        let num_conflicting = counts.objects
+            .values()
+            .filter(|obj| obj.num_mut_refs > 0 && obj.num_txs > 1)
+            .count();
+        self.conflicting_obj_fractions.push(num_conflicting as f64 / num_objects);
+    }
+
+    // Record a window without any checkpoints
+    pub fn record_empty(&mut self) {
+        self.conflict_graphs.push(Some(ConflictGraph::default()));
+        self.record_objects_empty();
+    }
+
+    fn record_objects_empty(&mut self) {
+        self.contention_degrees.push(0.0);
+        self.obj_touchabilities.push(0.0);
+        self.conflict_contention_degrees.push(0.0);
+        self.conflicting_obj_fractions.push(0.0);
+        self.exact_contention_degrees.push(Some(0.0));
+    }
+
+    pub fn len(&self) -> usize {
+        self.contention_degrees.len()
+    }
+
+    // Average metrics over `num_windows` windows (which might be fractional,
+    // e.g., for an unfinished last window)
+    pub fn summarize(&self, avg_interval_data: &mut AvgIntervalData, num_windows: f64, distributions: bool) {
+        avg_interval_data.contention_degree = average(&self.contention_degrees, num_windows);
+        avg_interval_data.obj_touchability = average(&self.obj_touchabilities, num_windows);
+        avg_interval_data.conflict_contention_degree = average(&self.conflict_contention_degrees, num_windows);
+        avg_interval_data.conflicting_obj_fraction = average(&self.conflicting_obj_fractions, num_windows);
+        if distributions {
+            avg_interval_data.contention_degree_distribution = Distribution::of(&self.contention_degrees);
+            avg_interval_data.obj_touchability_distribution = Distribution::of(&self.obj_touchabilities);
+        }
+    }
+
+    // Exact contention degree averaged like `summarize` does. Windows
+    // including checkpoints without TX digests are left out, and it is
+    // `None` if no other windows are left
+    pub fn exact_contention_degree(&self, num_windows: f64) -> Option<f64> {
+        let exact_contention_degrees: Vec<f64> = self.exact_contention_degrees.iter().flatten().copied().collect();
+        let num_skipped = (self.exact_contention_degrees.len() - exact_contention_degrees.len()) as f64;
+        if num_skipped == 0.0 {
+            return Some(average(&exact_contention_degrees, num_windows));
+        }
+        (num_windows > num_skipped).then(|| average(&exact_contention_degrees, num_windows - num_skipped))
+    }
+
+    // Conflict graphs averaged like `summarize` does, windows without TXs
+    // counting as 0. Windows including checkpoints without TX digests are
+    // left out like in `exact_contention_degree`
+    pub fn conflict_graph(&self, num_windows: f64) -> Option<ConflictGraphData> {
+        let mut counts = ConflictGraphCounts::default();
+        let mut num_skipped = 0.0;
+        for graph in self.conflict_graphs.iter() {
+            match graph {
+                Some(graph) => counts.add(graph),
+                None => num_skipped += 1.0,
+            }
+        }
+        if num_skipped > 0.0 && num_windows <= num_skipped {
+            return None;
+        }
+        Some(counts.data_over(num_windows - num_skipped))
+    }
+
+    pub fn clear(&mut self) {
+        *self = WindowSeries::default();
+    }
+}
+
+// Sum of values divided by the number of windows, 0 without windows
+fn average(values: &[f64], num_windows: f64) -> f64 {
+    if num_windows > 0.0 {
+        values.iter().fold(0.0, |sum, value| sum + value) / num_windows
+    } else {
+        0.0
+    }
+}
+
+// Tumbling windows of `interval` checkpoints, starting with the first
+// checkpoint of the epoch (or of the continuous stream of checkpoints)
+#[derive(Debug, Default)]
+pub struct TumblingWindow {
+    pub counts: WindowCounts,
+    pub series: WindowSeries,
+}
+
+impl TumblingWindow {
+    // Add the `position`-th (starting from 1) checkpoint of the epoch (or
+    // stream), finishing the window every `interval` checkpoints
+    pub fn push(&mut self, position: u64, interval: u64, num_txs: u64, objects: &[TouchedObject],
+            txs: Option<CheckpointTxs>) {
+        self.counts.add(num_txs, objects, txs);
+        if position % interval == 0 {
+            self.series.record(&self.counts);
+            self.counts.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.counts.clear();
+        self.series.clear();
+    }
+}
+
+// A checkpoint within a sliding window
+#[derive(Debug)]
+struct WindowCheckpoint {
+    checkpoint: u64,
+    num_txs: u64,
+    objects: Vec<TouchedObject>,
+    // digests of `CheckpointTxs`, if it has TX digests
+    txs: Option<Vec<u32>>,
+}
+
+// Sliding window of `interval` checkpoints moving by `stride` checkpoints
+#[derive(Debug)]
+pub struct SlidingWindow {
+    interval: u64,
+    stride: u64,
+    checkpoints: VecDeque<WindowCheckpoint>,
+    pub counts: WindowCounts,
+    pub series: WindowSeries,
+}
+
+impl SlidingWindow {
+    pub fn new(interval: u64, stride: u64) -> Self {
+        SlidingWindow {
+            interval,
+            stride,
+            checkpoints: VecDeque::new(),
+            counts: WindowCounts::default(),
+            series: WindowSeries::default(),
+        }
+    }
+
+    // Add the `position`-th (starting from 1) checkpoint of the epoch (or
+    // stream), drop those that do not fit into `interval` checkpoints ending
+    // with it, and record the window every `stride` checkpoints once it is full
+    pub fn push(&mut self, checkpoint: u64, position: u64, num_txs: u64, objects: &[TouchedObject],
+            txs: Option<CheckpointTxs>) {
+        let (interval, stride) = (self.interval, self.stride);
+        while let Some(first) = self.checkpoints.front() {
+            if first.checkpoint + interval > checkpoint {
+                break;
+            }
+            let first = self.checkpoints.pop_front().unwrap();
+            let txs = first.txs.as_deref().map(|digests| CheckpointTxs {
+                digests,
+                ..CheckpointTxs::default()
+            });
+            self.counts.remove(first.num_txs, &first.objects, txs);
+        }
+
+        self.counts.add(num_txs, objects, txs);
+        self.checkpoints.push_back(WindowCheckpoint {
+            checkpoint,
+            num_txs,
+            objects: objects.to_vec(),
+            txs: txs.map(|txs| txs.digests.to_vec()),
+        });
+
+        if position >= interval && (position - interval) % stride == 0 {
+            self.series.record(&self.counts);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.checkpoints.clear();
+        self.counts.clear();
+        self.series.clear();
+    }
+}
+
+// Tumbling windows of wall-clock time: checkpoints are assigned to windows by
+// their timestamps, relative to the first checkpoint of the epoch (or of the
+// continuous stream of checkpoints). Windows
+// without any checkpoints count as windows without shared-object TXs
+#[derive(Debug, Default)]
+pub struct TimeWindow {
+    // timestamp of the first checkpoint and index of the current window
+    start_ms: Option<u64>,
+    index: u64,
+    // timestamp of the last checkpoint
+    last_ms: u64,
+    pub counts: WindowCounts,
+    // metrics of every finished window so far
+    pub series: WindowSeries,
+}
+
+impl TimeWindow {
+    // Add a checkpoint, finishing the current window (and any empty windows
+    // following it) if the checkpoint does not fall into it
+    pub fn push(&mut self, timestamp_ms: u64, interval_ms: u64, num_txs: u64, objects: &[TouchedObject],
+            txs: Option<CheckpointTxs>) {
+        let start_ms = *self.start_ms.get_or_insert(timestamp_ms);
+        let index = timestamp_ms.saturating_sub(start_ms) / interval_ms;
+        if index > self.index {
+            self.series.record(&self.counts);
+            for _ in self.index + 1..index {
+                self.series.record_empty();
+            }
+            self.index = index;
+            self.counts.clear();
+        }
+        self.counts.add(num_txs, objects, txs);
+        self.last_ms = self.last_ms.max(timestamp_ms);
+    }
+
+    // Number of finished windows plus the fraction of the current window
+    // elapsed until the last checkpoint, just like a trailing partial window
+    // of checkpoints counts by the fraction of its checkpoints
+    pub fn num_windows(&self, interval_ms: u64) -> f64 {
+        let Some(start_ms) = self.start_ms else {
+            return 0.0;
+        };
+        let window_start_ms = start_ms + self.index * interval_ms;
+        let elapsed_ms = self.last_ms.saturating_sub(window_start_ms);
+        self.series.len() as f64 + elapsed_ms as f64 / interval_ms as f64
+    }
+
+    pub fn clear(&mut self) {
+        self.start_ms = None;
+        self.index = 0;
+        self.last_ms = 0;
+        self.counts.clear();
+        self.series.clear();
+    }
+}
+
+// Windows of an interval at the end of an epoch
+#[derive(Debug)]
+pub struct WindowsSummary<'a> {
+    pub series: &'a WindowSeries,
+    // the number of windows to average metrics over (see `summarize`)
+    pub num_windows: f64,
+    // whether windows are sliding, whose distributions are reported too
+    pub sliding: bool,
+    // only in continuous mode: the number of windows ending within the
+    // epoch, and the tumbling window still open at the end of it
+    pub num_finished: Option<usize>,
+    pub open: Option<&'a WindowCounts>,
+}
+
+// Windows of every interval (in checkpoints and in seconds), cut at the end
+// of every epoch or, in continuous mode, carried over to the next epoch if
+// it follows right after. Tumbling windows of checkpoints are used unless a
+// stride is set, in which case sliding windows are used instead
+#[derive(Debug)]
+pub struct Windows {
+    continuous: bool,
+    // first checkpoint of the current windows, i.e., the start checkpoint of
+    // the epoch, or of the stream of epochs in continuous mode, and the end
+    // checkpoint of the last epoch
+    window_start: u64,
+    last_checkpoint: Option<u64>,
+    tumbling: BTreeMap<u64, TumblingWindow>,
+    sliding: BTreeMap<u64, SlidingWindow>,
+    time: BTreeMap<u64, TimeWindow>,
+    // whether a checkpoint of the current epoch has no timestamp
+    missing_timestamps: bool,
+}
+
+impl Windows {
+    pub fn new(config: &MetricsConfig) -> Self {
+        Windows {
+            continuous: config.continuous,
+            window_start: 0,
+            last_checkpoint: None,
+            tumbling: config.intervals
+                .iter()
+                .filter(|_| config.stride.is_none())
+                .map(|i| (*i, TumblingWindow::default()))
+                .collect(),
+            sliding: config.intervals
+                .iter()
+                .filter_map(|i| config.stride.map(|stride| (*i, SlidingWindow::new(*i, stride))))
+                .collect(),
+            time: config.interval_secs
+                .iter()
+                .map(|i| (*i, TimeWindow::default()))
+                .collect(),
+            missing_timestamps: false,
+        }
+    }
+
+    // Empty windows of the same intervals, to calculate an epoch on its own;
+    // `None` in continuous mode, where windows depend on the epochs before
+    pub fn fork(&self) -> Option<Windows> {
+        if self.continuous {
+            return None;
+        }
+        Some(Windows {
+            continuous: false,
+            window_start: 0,
+            last_checkpoint: None,
+            tumbling: self.tumbling
+                .keys()
+                .map(|i| (*i, TumblingWindow::default()))
+                .collect(),
+            sliding: self.sliding
+                .iter()
+                .map(|(i, window)| (*i, SlidingWindow::new(*i, window.stride)))
+                .collect(),
+            time: self.time
+                .keys()
+                .map(|i| (*i, TimeWindow::default()))
+                .collect(),
+            missing_timestamps: false,
+        })
+    }
+
+    // Start a new epoch spanning checkpoints `start_checkpoint..=end_checkpoint`
+    // and return whether the windows were cleared, i.e., not carried over
+    pub fn begin_epoch(&mut self, start_checkpoint: usize, end_checkpoint: usize) -> bool {
+        let follows_last = self.last_checkpoint.is_some_and(|last| last + 1 == start_checkpoint as u64);
+        let cleared = !self.continuous || !follows_last;
+        if cleared {
+            self.window_start = start_checkpoint as u64;
+            self.clear();
+        } else {
+            for window in self.tumbling.values_mut() {
+                window.series.clear();
+            }
+            for window in self.sliding.values_mut() {
+                window.series.clear();
+            }
+            for window in self.time.values_mut() {
+                window.series.clear();
+            }
+        }
+        self.last_checkpoint = Some(end_checkpoint as u64);
+        self.missing_timestamps = false;
+        cleared
+    }
+
+    // Add a checkpoint of the current epoch to all windows; `txs` are its TXs
+    // unless it has no TX digests
+    pub fn push(&mut self, checkpoint: u64, timestamp_ms: Option<u64>, num_txs: u64, objects: &[TouchedObject],
+            txs: Option<CheckpointTxs>) {
+        match timestamp_ms {
+            Some(timestamp_ms) => {
+                for (interval_secs, window) in self.time.iter_mut() {
+                    window.push(timestamp_ms, interval_secs * 1000, num_txs, objects, txs);
+                }
+            },
+            None => self.missing_timestamps = true,
+        }
+
+        // position of the checkpoint within the epoch (or stream), starting from 1
+        let position = checkpoint - self.window_start + 1;
+        for (interval, window) in self.tumbling.iter_mut() {
+            window.push(position, *interval, num_txs, objects, txs);
+        }
+        for window in self.sliding.values_mut() {
+            window.push(checkpoint, position, num_txs, objects, txs);
+        }
+    }
+
+    // Whether a checkpoint of the current epoch has no timestamp, so that
+    // windows of wall-clock time are not reported for it
+    pub fn missing_timestamps(&self) -> bool {
+        self.missing_timestamps
+    }
+
+    // Windows of every interval in checkpoints at the end of an epoch of
+    // `num_checkpoints` checkpoints. Without a stride and outside continuous
+    // mode, metrics are averaged over all intervals within the epoch, where
+    // the unfinished last interval counts by the fraction of its checkpoints
+    pub fn intervals(&self, num_checkpoints: usize) -> impl Iterator<Item = (u64, WindowsSummary<'_>)> {
+        let continuous = self.continuous;
+        let tumbling = self.tumbling.iter().map(move |(interval, window)| (*interval, WindowsSummary {
+            series: &window.series,
+            num_windows: if continuous {
+                window.series.len() as f64
+            } else {
+                num_checkpoints as f64 / *interval as f64
+            },
+            sliding: false,
+            num_finished: continuous.then(|| window.series.len()),
+            open: continuous.then_some(&window.counts),
+        }));
+        let sliding = self.sliding.iter().map(move |(interval, window)| (*interval, WindowsSummary {
+            series: &window.series,
+            num_windows: window.series.len() as f64,
+            sliding: true,
+            num_finished: continuous.then(|| window.series.len()),
+            open: None,
+        }));
+        tumbling.chain(sliding)
+    }
+
+    // Windows of every interval in seconds at the end of an epoch, like
+    // `intervals`, where the unfinished last window counts by the fraction of
+    // it elapsed until the last checkpoint
+    pub fn intervals_secs(&self) -> impl Iterator<Item = (u64, WindowsSummary<'_>)> {
+        let continuous = self.continuous;
+        self.time.iter().map(move |(interval_secs, window)| (*interval_secs, WindowsSummary {
+            series: &window.series,
+            num_windows: if continuous {
+                window.series.len() as f64
+            } else {
+                window.num_windows(interval_secs * 1000)
+            },
+            sliding: false,
+            num_finished: continuous.then(|| window.series.len()),
+            open: continuous.then_some(&window.counts),
+        }))
+    }
+
+    // Counts of all open windows
+    pub fn counts(&self) -> impl Iterator<Item = &WindowCounts> {
+        self.tumbling
+            .values()
+            .map(|window| &window.counts)
+            .chain(self.sliding.values().map(|window| &window.counts))
+            .chain(self.time.values().map(|window| &window.counts))
+    }
+
+    fn clear(&mut self) {
+        for window in self.tumbling.values_mut() {
+            window.clear();
+        }
+        for window in self.sliding.values_mut() {
+            window.clear();
+        }
+        for window in self.time.values_mut() {
+            window.clear();
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+
+use crate::metrics::conflicts::tx_accesses;
+use crate::types::{
+    CheckpointData,
+    CostModel,
+    ResultData,
+    SimulationData,
+};
+
+impl CostModel {
+    // Cost of a TX accessing the given shared objects
+    pub fn cost(&self, accesses: &[(usize, bool)]) -> f64 {
+        let num_mut_refs = accesses.iter().filter(|(_, mut_ref)| *mut_ref).count();
+        self.tx_cost + self.obj_cost * accesses.len() as f64 + self.mut_obj_cost * num_mut_refs as f64
+    }
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        CostModel {
+            tx_cost: 1.0,
+            obj_cost: 0.0,
+            mut_obj_cost: 0.0,
+        }
+    }
+}
+
+// Simulated execution of a checkpoint
+#[derive(Debug, PartialEq)]
+pub struct CheckpointSimulation {
+    pub num_txs: usize,
+    pub work: f64,
+    pub makespan: f64,
+}
+
+// Simulate executing TXs touching shared objects on a number of virtual
+// workers. TXs touching the same shared object conflict unless all of them
+// read it: any number of TXs can read an object at the same time, but a TX
+// writing it (passing it by a mutable reference) has exclusive access.
+//
+// TXs of a checkpoint are dispatched one by one, in the order of
+// `tx_accesses`, to the worker that can start them the earliest, i.e., TXs
+// are not reordered. Checkpoints are executed one after another, so the
+// makespan of an epoch is the sum of the makespans of its checkpoints.
+pub fn simulate_checkpoint(checkpoint_data: &CheckpointData, num_workers: usize, cost_model: &CostModel)
+        -> CheckpointSimulation {
+    let txs = tx_accesses(checkpoint_data);
+    let num_objects = checkpoint_data.shared_objects.len();
+
+    // times at which workers become free, and, for every object, at which
+    // the last TX writing it and all TXs reading it since then finish
+    let mut workers = vec![0.0_f64; num_workers.max(1)];
+    let mut write_ends = vec![0.0_f64; num_objects];
+    let mut read_ends = vec![0.0_f64; num_objects];
+    let mut work = 0.0;
+
+    for accesses in txs.iter() {
+        let ready = accesses
+            .iter()
+            .map(|(obj, mut_ref)| if *mut_ref {
+                write_ends[*obj].max(read_ends[*obj])
+            } else {
+                write_ends[*obj]
+            })
+            .fold(0.0, f64::max);
+
+        // prefer the worker that became free the latest among those able to
+        // start the TX the earliest, to keep the others for later TXs
+        let (worker, start) = workers
+            .iter()
+            .enumerate()
+            .map(|(worker, free)| (worker, free.max(ready)))
+            .reduce(|best, other| {
+                if other.1 < best.1 || (other.1 == best.1 && workers[other.0] > workers[best.0]) {
+                    other
+                } else {
+                    best
+                }
+            })
+            .unwrap();
+
+        let cost = cost_model.cost(accesses);
+        let end = start + cost;
+        workers[worker] = end;
+        work += cost;
+        for (obj, mut_ref) in accesses.iter() {
+            if *mut_ref {
+                write_ends[*obj] = end;
+            } else {
+                read_ends[*obj] = read_ends[*obj].max(end);
+            }
+        }
+    }
+
+    CheckpointSimulation {
+        num_txs: txs.len(),
+        work,
+        makespan: workers.into_iter().fold(0.0, f64::max),
+    }
+}
+
+// Simulated execution of a number of checkpoints one after another
+#[derive(Debug, Default)]
+pub struct SimulationCounts {
+    num_txs: usize,
+    work: f64,
+    makespan: f64,
+}
+
+impl SimulationCounts {
+    pub fn add(&mut self, num_txs: usize, work: f64, makespan: f64) {
+        self.num_txs += num_txs;
+        self.work += work;
+        self.makespan += makespan;
+    }
+
+    pub fn data(&self, num_workers: usize) -> SimulationData {
+        let (utilisation, speedup) = if self.makespan > 0.0 {
+            (self.work / (num_workers as f64 * self.makespan), self.work / self.makespan)
+        } else {
+            (0.0, 0.0)
+        };
+        SimulationData {
+            num_txs: self.num_txs,
+            work: self.work,
+            makespan: self.makespan,
+            utilisation,
+            speedup,
+        }
+    }
+}
+
+// Simulate executing the checkpoints of an epoch on every given number of workers
+pub fn simulate_epoch(result: &ResultData, workers: &[usize], cost_model: &CostModel)
+        -> BTreeMap<usize, SimulationCounts> {
+    let mut counts: BTreeMap<usize, SimulationCounts> = workers
+        .iter()
+        .map(|num_workers| (*num_workers, SimulationCounts::default()))
+        .collect();
+    for checkpoint_data in result.checkpoints.values() {
+        for (num_workers, counts) in counts.iter_mut() {
+            let simulation = simulate_checkpoint(checkpoint_data, *num_workers, cost_model);
+            counts.add(simulation.num_txs, simulation.work, simulation.makespan);
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,86 @@
+use super::{
+    simulate_checkpoint,
+    CheckpointSimulation,
+    SimulationCounts,
+};
+use crate::metrics::conflicts::ConflictGraph;
+use crate::types::{
+    CheckpointData,
+    CostModel,
+};
+use crate::utils::fixtures;
+
+fn checkpoint(shared_objects: &[(&str, &[(u8, bool)])]) -> CheckpointData {
+    fixtures::checkpoint(0, 0, shared_objects)
+}
+
+// - TXs 1 and 2 read A, then TX 3 writes A and B;
+// - TX 4 writes C, TX 5 reads B (after TX 3).
+fn checkpoint0() -> CheckpointData {
+    checkpoint(&[
+        ("0xa", &[(1, false), (2, false), (3, true)]),
+        ("0xb", &[(3, true), (5, false)]),
+        ("0xc", &[(4, true)]),
+    ])
+}
+
+fn makespan(checkpoint_data: &CheckpointData, num_workers: usize) -> f64 {
+    simulate_checkpoint(checkpoint_data, num_workers, &CostModel::default()).makespan
+}
+
+#[test]
+fn single_worker_executes_txs_one_by_one() {
+    assert_eq!(simulate_checkpoint(&checkpoint0(), 1, &CostModel::default()), CheckpointSimulation {
+        num_txs: 5,
+        work: 5.0,
+        makespan: 5.0,
+    });
+}
+
+#[test]
+fn reads_are_shared_and_writes_exclusive() {
+    let reads = checkpoint(&[("0xa", &[(1, false), (2, false), (3, false)])]);
+    assert_eq!(makespan(&reads, 3), 1.0);
+    assert_eq!(makespan(&reads, 2), 2.0);
+
+    let writes = checkpoint(&[("0xa", &[(1, true), (2, true), (3, true)])]);
+    assert_eq!(makespan(&writes, 3), 3.0);
+}
+
+#[test]
+fn unlimited_workers_give_longest_chain() {
+    // TX 3 waits for TXs 1 and 2, TX 5 for TX 3, TX 4 runs at once
+    assert_eq!(makespan(&checkpoint0(), 2), 3.0);
+    assert_eq!(makespan(&checkpoint0(), 64), 3.0);
+    assert_eq!(makespan(&checkpoint0(), 64), ConflictGraph::of(&checkpoint0()).longest_chain as f64);
+}
+
+#[test]
+fn cost_model() {
+    let cost_model = CostModel {
+        tx_cost: 1.0,
+        obj_cost: 0.5,
+        mut_obj_cost: 2.0,
+    };
+    assert_eq!(cost_model.cost(&[(0, false), (1, true)]), 1.0 + 2.0 * 0.5 + 2.0);
+
+    // TX 3 (cost 1 + 1 + 4) after TX 1 or 2 (cost 1.5), TX 5 (cost 1.5) after TX 3
+    let simulation = simulate_checkpoint(&checkpoint0(), 64, &cost_model);
+    assert_eq!(simulation.makespan, 1.5 + 6.0 + 1.5);
+    assert_eq!(simulation.work, 1.5 * 3.0 + 6.0 + 3.5);
+}
+
+#[test]
+fn utilisation() {
+    let mut counts = SimulationCounts::default();
+    for checkpoint_data in [checkpoint0(), checkpoint(&[])] {
+        let simulation = simulate_checkpoint(&checkpoint_data, 2, &CostModel::default());
+        counts.add(simulation.num_txs, simulation.work, simulation.makespan);
+    }
+    let data = counts.data(2);
+    assert_eq!((data.num_txs, data.work, data.makespan), (5, 5.0, 3.0));
+    assert_eq!(data.utilisation, 5.0 / 6.0);
+    assert_eq!(data.speedup, 5.0 / 3.0);
+
+    assert_eq!(SimulationCounts::default().data(2).utilisation, 0.0);
+}
@@ -1,18 +1,25 @@
 use serde::{Serialize, Deserialize};
 use std::collections::{
-    HashSet,
-    BTreeMap
+    HashMap,
+    BTreeMap,
+    BTreeSet,
 };
 use indexmap::IndexMap;
+use sui_sdk::types::base_types::{
+    ObjectID,
+    SuiAddress,
+    TransactionDigest,
+};
 
 #[derive(Debug)]
 pub struct SharedObjInfo {
-    pub id: String,
+    pub id: ObjectID,
     pub mutable: bool
 }
 
 #[derive(Debug)]
 pub struct TxInfo {
+    pub sender: SuiAddress,
     pub num_total: usize,
     pub num_shared: usize,
     pub shared_objects: Vec<SharedObjInfo>
@@ -24,21 +31,51 @@ pub struct TxInfo {
 //     pub mutates: bool
 // }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct CheckpointData {
     pub num_txs_total: usize,
     pub num_txs_touching_shared_objs: usize,
+    // time at which the checkpoint was created; missing in epoch data
+    // files queried before timestamps were recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_ms: Option<u64>,
     pub shared_objects: BTreeMap<
-        String,     // object ID
+        ObjectID,               // object ID
         BTreeMap<
-            String, // TX ID
-            bool,   // whether this TX mutates obj or not
+            TransactionDigest,  // TX ID
+            bool,               // whether this TX mutates obj or not
             >
-        >
+        >,
+    // senders of TXs touching shared objects; missing in epoch data files
+    // queried before senders were recorded
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub senders: BTreeMap<TransactionDigest, SuiAddress>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ResultData {
+// counts of accesses to a shared object within a checkpoint
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SharedObjCounts {
+    pub num_txs: usize,
+    pub num_mut_refs: usize,
+}
+
+// Pruned ("counts-only") version of `CheckpointData`: TX digests are dropped,
+// only the counts needed to calculate metrics are kept
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrunedCheckpointData {
+    pub num_txs_total: usize,
+    pub num_txs_touching_shared_objs: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp_ms: Option<u64>,
+    pub num_txs_touching_at_least_one_shared_obj_by_mut: usize,
+    pub num_txs_touching_more_than_1_shared_obj: usize,
+    pub shared_objects: BTreeMap<ObjectID, SharedObjCounts>,
+}
+
+// Data of an epoch data file; `checkpoints` hold either `CheckpointData`
+// or, for pruned epoch data files, `PrunedCheckpointData`
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResultData<C = CheckpointData> {
     pub network: String,
     pub version: String,
     pub epoch: usize,
@@ -49,7 +86,75 @@ pub struct ResultData {
     pub num_txs_scanned: usize,
     pub num_txs_touching_0_shared_objs: usize,
     pub num_txs_touching_0_objs: usize,
-    pub checkpoints: BTreeMap<u64, CheckpointData>
+    // only stored for pruned epoch data files, so that full ones keep the
+    // format of epoch data files queried before pruning was added
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pruned: bool,
+    // time of the last query adding TXs to the epoch data file (in ms since
+    // the Unix epoch); unknown for files queried before it was recorded and
+    // not kept in the SQLite database
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queried_at_ms: Option<u64>,
+    pub checkpoints: BTreeMap<u64, C>
+}
+
+pub type PrunedResultData = ResultData<PrunedCheckpointData>;
+
+// Epoch data file of either format
+#[derive(Debug)]
+pub enum EpochFile {
+    Full(ResultData),
+    Pruned(PrunedResultData),
+}
+
+// Header of an epoch data file, i.e., everything but the checkpoints.
+// Deserializing it from an epoch data file skips the checkpoints data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochHeader {
+    pub network: String,
+    pub version: String,
+    pub epoch: usize,
+    pub start_checkpoint: usize,
+    pub end_checkpoint: usize,
+    pub last_cursor: String,
+    pub num_txs_in_epoch: usize,
+    pub num_txs_scanned: usize,
+    pub num_txs_touching_0_shared_objs: usize,
+    pub num_txs_touching_0_objs: usize,
+    #[serde(default)]
+    pub pruned: bool,
+    #[serde(default)]
+    pub queried_at_ms: Option<u64>,
+}
+
+// describes an epoch data file in the workspace manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceEpoch {
+    pub file: String,
+    pub version: String,
+    pub start_checkpoint: usize,
+    pub end_checkpoint: usize,
+    pub num_txs_in_epoch: usize,
+    pub num_txs_scanned: usize,
+    pub complete: bool,
+    #[serde(default)]
+    pub pruned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queried_at_ms: Option<u64>,
+    // size and modification time (in ms since the Unix epoch) of the file
+    // when its header was read, to notice files changed on disk since
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub modified_ms: u64,
+}
+
+// defines the workspace manifest file structure
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceManifest {
+    pub network: String,
+    pub sdk_version: String,
+    pub epochs: BTreeMap<usize, WorkspaceEpoch>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -66,7 +171,7 @@ pub struct EpochToCheckpointData {
 }
 
 // contains data about a single epoch
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EpochData {
     pub num_txs_total: usize,
     pub num_txs_touching_shared_objs: usize,
@@ -77,30 +182,478 @@ pub struct EpochData {
     pub num_shared_objects_per_epoch: usize,
     pub num_shared_objects_total: usize,
     pub num_checkpoints: usize,
+    // pairs of TXs touching the same shared object within a checkpoint, by
+    // whether they read it (immutable reference) or write it (mutable reference)
+    pub num_read_read_collisions: u64,
+    pub num_read_write_collisions: u64,
+    pub num_write_write_collisions: u64,
+    // conflict graphs of TXs within checkpoints; only known for epoch data
+    // with TX digests, i.e., not pruned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict_graph: Option<ConflictGraphData>,
     pub avg_interval_data: BTreeMap<u64, AvgIntervalData>,
+    // same as `avg_interval_data`, but for intervals expressed in seconds;
+    // empty if the epoch data has no checkpoint timestamps
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub avg_interval_secs_data: BTreeMap<u64, AvgIntervalData>,
 }
 
-// Counts for different checkpoint intervals 
-#[derive(Debug)]
-pub struct IntervalCounts {
-    pub num_txs: u64,
-    pub unique_shared_objects: HashSet<String>,
-    pub unique_shared_objects_touched_by_more_than_1tx: HashSet<String>,
+// metrics of a single epoch calculated from unique TXs touching shared
+// objects rather than the numbers of such TXs reported per checkpoint;
+// `num_shared_objects_per_tx` is averaged over TXs rather than checkpoints
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExactEpochData {
+    pub num_txs_touching_shared_objs: usize,
+    pub num_txs_touching_at_least_one_shared_obj_by_mut: usize,
+    pub num_shared_objects_per_tx: f64,
+    pub density: f64,
+    pub density_mut: f64,
+    // contention degree counting unique TXs within each window, by interval
+    // in checkpoints and in seconds (see `AvgIntervalData`)
+    pub avg_interval_data: BTreeMap<u64, ExactIntervalData>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub avg_interval_secs_data: BTreeMap<u64, ExactIntervalData>,
+}
+
+// contention degree of an interval counting unique TXs, averaged over its
+// windows, and of the window still open at the end of the epoch in
+// continuous mode. In continuous mode, windows including checkpoints of
+// epochs without TX digests are left out, so it is missing if no other
+// windows are left
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExactIntervalData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contention_degree: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial_window: Option<f64>,
+}
+
+// stores metrics of all epochs counting unique TXs; `None` for epochs
+// without TX digests, i.e., pruned
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExactData {
+    pub epochs: BTreeMap<usize, Option<ExactEpochData>>,
 }
 
 // Data for a given interval of checkpoints
-#[derive(Debug, Serialize)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct AvgIntervalData {
     pub contention_degree: f64,
     pub obj_touchability: f64,
+    // same as `contention_degree`, but only over shared objects written
+    // (passed by a mutable reference) within the interval
+    pub conflict_contention_degree: f64,
+    // fraction of shared objects written and touched by more than one TX
+    // within the interval
+    pub conflicting_obj_fraction: f64,
+    // distributions over sliding windows, only calculated for sliding windows
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contention_degree_distribution: Option<Distribution>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obj_touchability_distribution: Option<Distribution>,
+    // conflict graphs of TXs within the windows, like `EpochData::conflict_graph`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict_graph: Option<ConflictGraphData>,
+    // only when windows span epoch boundaries: the number of windows ending
+    // within the epoch (over which the metrics above are averaged), and the
+    // window still open at the end of the epoch, carried over to the next one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_windows: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub partial_window: Option<PartialWindowData>,
+}
+
+// metrics of a window that has not been finished (yet)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartialWindowData {
+    pub num_checkpoints: u64,
+    pub contention_degree: f64,
+    pub obj_touchability: f64,
+    pub conflict_contention_degree: f64,
+    pub conflicting_obj_fraction: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub conflict_graph: Option<ConflictGraphData>,
+}
+
+// describes the distribution of a metric
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Distribution {
+    pub min: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+// describes the distribution of a metric together with its histogram: bin
+// `k` counts the values within `[k * bin_width, (k + 1) * bin_width)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistogramData {
+    pub distribution: Distribution,
+    pub bin_width: f64,
+    pub histogram: BTreeMap<u64, u64>,
+}
+
+// distributions of metrics within a single epoch
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EpochDistributionsData {
+    // the number of shared objects touched by each TX touching shared
+    // objects; only known for epoch data with TX digests, i.e., not pruned
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared_objects_per_tx: Option<HistogramData>,
+    // the number of TXs touching each shared object within each checkpoint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub txs_per_object_per_checkpoint: Option<HistogramData>,
+    // contention degree of every window (interval), by interval in
+    // checkpoints and in seconds
+    pub contention_degree: BTreeMap<u64, HistogramData>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub contention_degree_secs: BTreeMap<u64, HistogramData>,
+}
+
+// stores distributions of metrics for all epochs
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DistributionsData {
+    pub epochs: BTreeMap<usize, EpochDistributionsData>,
+}
+
+// describes how concentrated TXs are over shared objects (or packages):
+// `num_txs` counts every shared object a TX touches, so it is larger than
+// the number of TXs touching shared objects
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConcentrationData {
+    // the number of shared objects (or packages) touched
+    pub num_touched: usize,
+    pub num_txs: u64,
+    // 0 if every object is touched by the same number of TXs, approaching 1
+    // if a single object is touched by all of them
+    pub gini: f64,
+    // Herfindahl-Hirschman index, i.e., the sum of squared shares of TXs,
+    // between 1 / `num_touched` and 1
+    pub hhi: f64,
+    // share of TXs touching the `k` most touched objects, by `k`
+    pub top_shares: BTreeMap<usize, f64>,
+}
+
+// concentration of TXs within a single epoch; over packages only if the
+// packages of shared objects are known (see `query-obj`)
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EpochConcentrationData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub objects: Option<ConcentrationData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packages: Option<ConcentrationData>,
+}
+
+// stores concentration of TXs for all epochs
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConcentrationsData {
+    pub epochs: BTreeMap<usize, EpochConcentrationData>,
+}
+
+// describes accesses to a shared object touched by many TXs: inter-arrival
+// times of consecutive TXs touching it (0 within a checkpoint), in
+// checkpoints and in seconds (if all checkpoints have timestamps), and their
+// burstiness coefficient (std - mean) / (std + mean), which is -1 for
+// perfectly regular accesses, 0 for random (Poisson) ones and approaches 1
+// for bursts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HotObjectData {
+    pub object: ObjectID,
+    pub num_txs: u64,
+    pub num_active_checkpoints: u64,
+    pub txs_per_epoch: BTreeMap<usize, u64>,
+    pub peak_txs_per_checkpoint: u64,
+    pub peak_checkpoint: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inter_arrival_checkpoints: Option<HistogramData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burstiness_checkpoints: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inter_arrival_secs: Option<Distribution>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub burstiness_secs: Option<f64>,
+}
+
+// stores accesses to the shared objects touched by the most TXs, most
+// touched first
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HotObjectsData {
+    pub objects: Vec<HotObjectData>,
+}
+
+// describes a shared object (almost) never mutated, which could be frozen
+// or made immutable; its type is only known once `query-obj` has been run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadMostlyObjectData {
+    pub object: ObjectID,
+    pub num_txs: usize,
+    pub num_mut_refs: usize,
+    pub mut_ratio: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+}
+
+// describes read-mostly shared objects of the same type
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReadMostlyTypeData {
+    pub num_objects: usize,
+    pub num_txs: usize,
+    pub num_mut_refs: usize,
+}
+
+// density of an epoch as calculated, and as if TXs touching read-mostly
+// shared objects did not need consensus ordering for them (i.e., only TXs
+// touching other shared objects did); the latter needs TX digests, so it is
+// missing for pruned epoch data
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadMostlyEpochData {
+    pub density: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_txs_touching_shared_objs_without_read_mostly: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub density_without_read_mostly: Option<f64>,
+}
+
+// stores shared objects whose ratio of mutable references to TXs touching
+// them is at most `max_mut_ratio`, most touched first, grouped by type (if
+// known), and densities of all epochs without them
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ReadMostlyData {
+    pub max_mut_ratio: f64,
+    pub num_objects: usize,
+    pub num_read_only_objects: usize,
+    pub num_txs: usize,
+    pub objects: Vec<ReadMostlyObjectData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub types: Option<BTreeMap<String, ReadMostlyTypeData>>,
+    pub epochs: BTreeMap<usize, ReadMostlyEpochData>,
+}
+
+// describes a shared object mutated by only a few distinct senders, which
+// could be redesigned as an owned object; its type and package are only
+// known once `query-obj` has been run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SingleWriterObjectData {
+    pub object: ObjectID,
+    pub num_txs: usize,
+    pub num_mut_refs: usize,
+    // distinct senders of all TXs touching the object, and of those mutating it
+    pub num_senders: usize,
+    pub mut_senders: Vec<SuiAddress>,
+    // share of TXs touching shared objects that touch this object
+    pub tx_share: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub type_: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+}
+
+// describes single-writer candidates of the same type or package
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SingleWriterGroupData {
+    pub num_objects: usize,
+    pub num_txs: usize,
+    pub tx_share: f64,
+}
+
+// stores shared objects mutated by at least one and at most `max_senders`
+// distinct senders, most touched first, grouped by type and package (if
+// known). Only checkpoints with senders of TXs are taken into account; TXs
+// touching several candidates count towards each of them
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SingleWriterData {
+    pub max_senders: usize,
+    pub num_checkpoints_without_senders: usize,
+    pub num_txs_touching_shared_objs: usize,
+    pub num_objects: usize,
+    pub objects: Vec<SingleWriterObjectData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub types: Option<BTreeMap<String, SingleWriterGroupData>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packages: Option<BTreeMap<String, SingleWriterGroupData>>,
+}
+
+// TXs of the checkpoint with the most TXs touching the Clock (the first one
+// if there are several)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClockCheckpointData {
+    pub epoch: usize,
+    pub checkpoint: u64,
+    pub num_txs: usize,
+    pub txs: Vec<TransactionDigest>,
+}
+
+// describes TXs touching the Clock (0x6): those also touching other shared
+// objects, and those touching no other shared object (but possibly owned
+// objects), in total and per epoch. Only epochs with TX digests are counted
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ClockData {
+    pub num_txs: usize,
+    pub num_txs_touching_other_shared_objs: usize,
+    pub num_txs_touching_only_clock: usize,
+    pub num_txs_touching_only_clock_per_epoch: BTreeMap<usize, usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peak_checkpoint: Option<ClockCheckpointData>,
+}
+
+// describes TXs touching the State object of Pyth, and the TXs mutating it
+// (only known for epochs with TX digests)
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PythStateData {
+    pub num_txs: usize,
+    pub num_mut_refs: usize,
+    pub mut_txs: Vec<TransactionDigest>,
+}
+
+// describes conflict graphs of TXs within checkpoints of an epoch, averaged
+// over checkpoints with TXs touching shared objects, or within the windows
+// of an interval, averaged over windows like contention degree
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConflictGraphData {
+    pub avg_longest_chain: f64,
+    pub max_longest_chain: usize,
+    pub avg_num_components: f64,
+    pub avg_speedup_bound: f64,
+    // the number of TXs touching shared objects divided by the sum of the
+    // longest chains, i.e., when checkpoints (or windows) are executed one
+    // after another
+    pub speedup_bound: f64,
+}
+
+// cost of executing a TX in the scheduler simulator, in arbitrary units:
+// `tx_cost` plus `obj_cost` for every shared object it touches plus
+// `mut_obj_cost` for every shared object it touches by mutable reference
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostModel {
+    pub tx_cost: f64,
+    pub obj_cost: f64,
+    pub mut_obj_cost: f64,
+}
+
+// result of simulating the execution of TXs touching shared objects on a
+// number of workers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationData {
+    pub num_txs: usize,
+    // total cost of all TXs, i.e., the makespan on a single worker
+    pub work: f64,
+    pub makespan: f64,
+    // fraction of time workers are busy
+    pub utilisation: f64,
+    pub speedup: f64,
+}
+
+// stores results of the scheduler simulator for all epochs, by the number
+// of workers
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SimulationsData {
+    pub cost_model: CostModel,
+    pub epochs: BTreeMap<usize, BTreeMap<usize, SimulationData>>,
+    pub total: BTreeMap<usize, SimulationData>,
+}
+
+// shared objects touched together by a number of TXs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoAccessedObjects {
+    pub objects: Vec<ObjectID>,
+    pub num_txs: u64,
+}
+
+// a group of shared objects in the co-access graph, ordered by the number of
+// TXs touching them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectCluster {
+    pub num_objects: usize,
+    // sum of the numbers of TXs touching each object of the group
+    pub num_txs: u64,
+    pub objects: Vec<ObjectID>,
+}
+
+// describes the co-access graph of shared objects
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CoAccessData {
+    pub num_txs: u64,
+    pub num_txs_touching_more_than_1_shared_obj: u64,
+    pub num_objects: usize,
+    pub num_edges: usize,
+    pub num_components: usize,
+    pub top_pairs: Vec<CoAccessedObjects>,
+    pub top_tuples: Vec<CoAccessedObjects>,
+    pub components: Vec<ObjectCluster>,
+    pub communities: Vec<ObjectCluster>,
 }
 
 // stores data about all epochs
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct EpochsData {
     pub epochs: BTreeMap<usize, EpochData>,
 }
 
+// describes an epoch accounted for in the saved metrics, used to detect
+// whether its epoch data has changed since
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessedEpoch {
+    pub start_checkpoint: usize,
+    pub end_checkpoint: usize,
+    pub num_txs_scanned: usize,
+    pub pruned: bool,
+}
+
+// shared objects metrics are calculated for: all of them, all but some, or
+// only some of them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectFilter {
+    #[default]
+    All,
+    Exclude(BTreeSet<ObjectID>),
+    Only(BTreeSet<ObjectID>),
+}
+
+// selects shared objects to treat as owned in a what-if scenario: an ID
+// selects the shared object with that ID or all shared objects of the
+// package with that ID, a type pattern selects all shared objects whose type
+// ("module.Name") matches it, where `*` matches anything
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OwnedSelector {
+    Id(ObjectID),
+    Type(String),
+}
+
+// metrics of all epochs as if some shared objects were owned, i.e., TXs
+// touching only them no longer touch shared objects, stored apart from the
+// metrics of all shared objects to compare them
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScenarioData {
+    pub treat_as_owned: Vec<String>,
+    pub treated_as_owned: BTreeSet<ObjectID>,
+    pub epochs: BTreeMap<usize, EpochData>,
+}
+
+// state saved by `metrics` next to its results, so that later runs only
+// process epochs added since
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetricsState {
+    pub intervals: Vec<u64>,
+    #[serde(default)]
+    pub stride: Option<u64>,
+    #[serde(default)]
+    pub interval_secs: Vec<u64>,
+    // fingerprint of the packages of shared objects concentration over
+    // packages was calculated with, if any (see `MetricsConfig`)
+    #[serde(default)]
+    pub packages_fingerprint: Option<u64>,
+    #[serde(default)]
+    pub continuous: bool,
+    #[serde(default)]
+    pub object_filter: ObjectFilter,
+    // names of the metrics saved (see `Metric::name`)
+    #[serde(default)]
+    pub metrics: Vec<String>,
+    pub epochs: BTreeMap<usize, ProcessedEpoch>,
+}
+
 // stores data of interest about a shared object
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SharedObjectSetData {
@@ -108,17 +661,38 @@ pub struct SharedObjectSetData {
     pub num_mut_refs: usize,
     pub first_touched_at_epoch: usize,
     pub first_touched_at_checkpoint: u64,
-    pub first_touched_by_txs: BTreeMap<String, bool>,
+    pub last_touched_at_epoch: usize,
+    pub last_touched_at_checkpoint: u64,
+    // the number of checkpoints and epochs in which the object was touched
+    pub num_active_checkpoints: u64,
+    pub num_active_epochs: usize,
+    // the largest number of checkpoints between two consecutive checkpoints
+    // in which the object was touched
+    pub longest_idle_gap: u64,
+    pub first_touched_by_txs: BTreeMap<TransactionDigest, bool>,
 }
 
 // stores data of interest about the set of all shared objects
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SharedObjectsSetData {
-    pub shared_objects: BTreeMap<String, SharedObjectSetData>,
+    pub shared_objects: BTreeMap<ObjectID, SharedObjectSetData>,
+    // shared objects appearing and retiring in every epoch processed
+    #[serde(default)]
+    pub epochs: BTreeMap<usize, LifetimeEpochData>,
+}
+
+// shared objects touched for the first time within an epoch, those touched
+// for the last time so far (unless it is the last epoch), and those touched
+// for the first time and never touched again after that checkpoint
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LifetimeEpochData {
+    pub num_new_shared_objects: usize,
+    pub num_retired_shared_objects: usize,
+    pub num_one_shot_shared_objects: usize,
 }
 
 // stores more data of interest about a single shared object 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SharedObjectData {
     pub address: String,
     pub type_: String,
@@ -127,15 +701,15 @@ pub struct SharedObjectData {
     pub num_mut_refs: usize,
     pub first_touched_at_epoch: usize,
     pub first_touched_at_checkpoint: u64,
-    pub first_touched_by_txs: BTreeMap<String, bool>,
+    pub first_touched_by_txs: BTreeMap<TransactionDigest, bool>,
 }
 
 // stores data of interest about all shared objects
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SharedObjectsData {
     pub total_num_shared_objects: usize,
     pub total_num_resources: usize,
-    pub shared_objects: IndexMap<String, SharedObjectData>,
+    pub shared_objects: IndexMap<ObjectID, SharedObjectData>,
 }
 
 // stores data of interest about shared objects
@@ -148,7 +722,7 @@ pub struct ModuleAndNameData {
     pub is_resource: bool,
     pub first_touched_at_epoch: usize,
     pub first_touched_at_checkpoint: u64,
-    pub first_touched_by_txs: BTreeMap<String, bool>,
+    pub first_touched_by_txs: BTreeMap<TransactionDigest, bool>,
 }
 
 // stores data of interest about a package
@@ -170,3 +744,97 @@ pub struct PackagesData {
     pub total_num_resources: usize,
     pub packages: IndexMap<String, PackageData>,
 }
+
+impl CheckpointData {
+    // Drop TX digests and keep only counts
+    pub fn prune(&self) -> PrunedCheckpointData {
+        let mut txs: HashMap<&TransactionDigest, (usize, bool)> = HashMap::new();
+        let mut shared_objects = BTreeMap::new();
+        for (obj_id, tx_list) in self.shared_objects.iter() {
+            let mut counts = SharedObjCounts::default();
+            for (tx_id, mut_ref) in tx_list.iter() {
+                counts.num_txs += 1;
+                let tx = txs.entry(tx_id).or_insert((0, false));
+                tx.0 += 1;
+                if *mut_ref {
+                    counts.num_mut_refs += 1;
+                    tx.1 = true;
+                }
+            }
+            shared_objects.insert(*obj_id, counts);
+        }
+
+        PrunedCheckpointData {
+            num_txs_total: self.num_txs_total,
+            num_txs_touching_shared_objs: self.num_txs_touching_shared_objs,
+            timestamp_ms: self.timestamp_ms,
+            num_txs_touching_at_least_one_shared_obj_by_mut: txs
+                .values()
+                .filter(|(_, mut_ref)| *mut_ref)
+                .count(),
+            num_txs_touching_more_than_1_shared_obj: txs
+                .values()
+                .filter(|(num_objs, _)| *num_objs > 1)
+                .count(),
+            shared_objects,
+        }
+    }
+}
+
+impl PrunedCheckpointData {
+    // Add counts of TXs from the same checkpoint that were scanned separately
+    // (e.g., after resuming an incomplete scan)
+    pub fn add(&mut self, other: &PrunedCheckpointData) {
+        self.num_txs_total += other.num_txs_total;
+        self.num_txs_touching_shared_objs += other.num_txs_touching_shared_objs;
+        self.timestamp_ms = self.timestamp_ms.or(other.timestamp_ms);
+        self.num_txs_touching_at_least_one_shared_obj_by_mut
+            += other.num_txs_touching_at_least_one_shared_obj_by_mut;
+        self.num_txs_touching_more_than_1_shared_obj += other.num_txs_touching_more_than_1_shared_obj;
+        for (obj_id, counts) in other.shared_objects.iter() {
+            let total = self.shared_objects.entry(*obj_id).or_default();
+            total.num_txs += counts.num_txs;
+            total.num_mut_refs += counts.num_mut_refs;
+        }
+    }
+}
+
+impl ResultData {
+    // Convert to a pruned epoch data file
+    pub fn prune(&self) -> PrunedResultData {
+        ResultData {
+            network: self.network.clone(),
+            version: self.version.clone(),
+            epoch: self.epoch,
+            start_checkpoint: self.start_checkpoint,
+            end_checkpoint: self.end_checkpoint,
+            last_cursor: self.last_cursor.clone(),
+            num_txs_in_epoch: self.num_txs_in_epoch,
+            num_txs_scanned: self.num_txs_scanned,
+            num_txs_touching_0_shared_objs: self.num_txs_touching_0_shared_objs,
+            num_txs_touching_0_objs: self.num_txs_touching_0_objs,
+            pruned: true,
+            queried_at_ms: self.queried_at_ms,
+            checkpoints: self.checkpoints
+                .iter()
+                .map(|(checkpoint, checkpoint_data)| (*checkpoint, checkpoint_data.prune()))
+                .collect(),
+        }
+    }
+
+    // Convert TXs scanned after resuming the query of a pruned epoch data
+    // file to pruned epoch data, adding the counts of TXs scanned before
+    pub fn prune_onto(&self, previous: &PrunedResultData) -> PrunedResultData {
+        let mut pruned = self.prune();
+        for (checkpoint, checkpoint_data) in previous.checkpoints.iter() {
+            pruned.checkpoints
+                .entry(*checkpoint)
+                .or_default()
+                .add(checkpoint_data);
+        }
+        pruned
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,104 @@
+use std::str::FromStr;
+
+use sui_sdk::types::base_types::TransactionDigest;
+
+use super::{
+    EpochHeader,
+    ResultData,
+    SharedObjCounts,
+    SharedObjectsSetData,
+};
+use crate::utils::fixtures::{
+    checkpoint,
+    obj,
+    result_data,
+};
+
+const TX1: &str = "6xrEj4SX3NsmrHLSzPtThJCWgtQWtqqzLkchegAQa9Qm";
+const TX2: &str = "9PV3pafgNPgtirAg3D2k7FQupnvAggQ5xsmQNXVCn932";
+const TX3: &str = "9uubX6drnjfXPPjpXY8Cf6Xu6qBRzW5vaKiPs9FJFxrU";
+const CLOCK: &str = "0x0000000000000000000000000000000000000000000000000000000000000006";
+const OBJ: &str = "0x000000000000000000000000000000000000000000000000000000000000000c";
+
+// An epoch data file as queried before IDs and digests were typed, i.e.,
+// without timestamps, senders and the `pruned` flag
+fn baseline_epoch_file() -> String {
+    format!(concat!(
+        r#"{{"network":"Mainnet","version":"1.24.0","epoch":0,"start_checkpoint":100,"end_checkpoint":101,"#,
+        r#""last_cursor":"{tx3}","num_txs_in_epoch":13,"num_txs_scanned":13,"#,
+        r#""num_txs_touching_0_shared_objs":6,"num_txs_touching_0_objs":0,"checkpoints":{{"#,
+        r#""100":{{"num_txs_total":3,"num_txs_touching_shared_objs":0,"shared_objects":{{}}}},"#,
+        r#""101":{{"num_txs_total":10,"num_txs_touching_shared_objs":3,"shared_objects":{{"#,
+        r#""{clock}":{{"{tx1}":false,"{tx2}":false,"{tx3}":false}},"#,
+        r#""{obj}":{{"{tx1}":true,"{tx3}":true}}}}}}}}}}"#),
+        tx1 = TX1, tx2 = TX2, tx3 = TX3, clock = CLOCK, obj = OBJ)
+}
+
+#[test]
+fn baseline_epoch_file_round_trips() {
+    let content = baseline_epoch_file();
+    let result: ResultData = serde_json::from_str(&content).unwrap();
+    assert!(!result.pruned);
+    let checkpoint_data = &result.checkpoints[&101];
+    assert_eq!(checkpoint_data.timestamp_ms, None);
+    assert!(checkpoint_data.senders.is_empty());
+    let txs = &checkpoint_data.shared_objects[&obj("0x6")];
+    assert_eq!(txs.keys().map(|tx| tx.to_string()).collect::<Vec<_>>(), vec![TX1, TX2, TX3]);
+    assert!(checkpoint_data.shared_objects[&obj("0xc")][&TransactionDigest::from_str(TX3).unwrap()]);
+    assert_eq!(serde_json::to_string(&result).unwrap(), content);
+
+    // the header is read without the checkpoints
+    let header: EpochHeader = serde_json::from_str(&content).unwrap();
+    assert!(header.is_complete());
+    assert!(!header.pruned);
+}
+
+#[test]
+fn shared_objects_set_round_trips() {
+    let content = format!(concat!(
+        r#"{{"shared_objects":{{"{clock}":{{"num_txs":3,"num_mut_refs":0,"first_touched_at_epoch":0,"#,
+        r#""first_touched_at_checkpoint":101,"last_touched_at_epoch":0,"last_touched_at_checkpoint":101,"#,
+        r#""num_active_checkpoints":1,"num_active_epochs":1,"longest_idle_gap":0,"#,
+        r#""first_touched_by_txs":{{"{tx1}":false,"{tx2}":false,"{tx3}":false}}}}}},"#,
+        r#""epochs":{{"0":{{"num_new_shared_objects":1,"num_retired_shared_objects":0,"#,
+        r#""num_one_shot_shared_objects":1}}}}}}"#),
+        tx1 = TX1, tx2 = TX2, tx3 = TX3, clock = CLOCK);
+    let shared_objects_set: SharedObjectsSetData = serde_json::from_str(&content).unwrap();
+    let clock = &shared_objects_set.shared_objects[&obj("0x6")];
+    assert_eq!(clock.first_touched_by_txs.len(), 3);
+    assert_eq!(serde_json::to_string(&shared_objects_set).unwrap(), content);
+}
+
+#[test]
+fn resumed_pruned_query_adds_counts() {
+    // scanned before: checkpoint 0 and TX 2 of checkpoint 1
+    let mut checkpoint1 = checkpoint(1, 1, &[("0xa", &[(2, true)]), ("0xb", &[(2, false)])]);
+    checkpoint1.timestamp_ms = Some(1_000);
+    let mut previous = result_data(0, 0, vec![checkpoint(2, 1, &[("0xa", &[(1, false)])]), checkpoint1]);
+    previous.num_txs_in_epoch = 6;
+    let previous = previous.prune();
+
+    // scanned after resuming: TXs 3 and 4 of checkpoint 1, TX 5 of checkpoint 2
+    let mut resumed = result_data(0, 1, vec![
+        checkpoint(2, 2, &[("0xa", &[(3, true), (4, false)])]),
+        checkpoint(1, 1, &[("0xa", &[(5, true)])]),
+    ]);
+    resumed.start_checkpoint = 0;
+    resumed.num_txs_in_epoch = 6;
+    resumed.num_txs_scanned = 6;
+    resumed.last_cursor = String::from("resumed");
+    let pruned = resumed.prune_onto(&previous);
+
+    assert!(pruned.pruned);
+    assert_eq!((pruned.num_txs_scanned, pruned.last_cursor.as_str()), (6, "resumed"));
+    assert_eq!(pruned.checkpoints.keys().copied().collect::<Vec<_>>(), vec![0, 1, 2]);
+    assert_eq!(pruned.checkpoints[&0], previous.checkpoints[&0]);
+    let checkpoint1 = &pruned.checkpoints[&1];
+    assert_eq!((checkpoint1.num_txs_total, checkpoint1.num_txs_touching_shared_objs), (3, 3));
+    assert_eq!(checkpoint1.timestamp_ms, Some(1_000));
+    assert_eq!(checkpoint1.num_txs_touching_at_least_one_shared_obj_by_mut, 2);
+    assert_eq!(checkpoint1.num_txs_touching_more_than_1_shared_obj, 1);
+    assert_eq!(checkpoint1.shared_objects[&obj("0xa")], SharedObjCounts {num_txs: 3, num_mut_refs: 2});
+    assert_eq!(checkpoint1.shared_objects[&obj("0xb")], SharedObjCounts {num_txs: 1, num_mut_refs: 0});
+    assert_eq!(pruned.checkpoints[&2], resumed.checkpoints[&2].prune());
+}
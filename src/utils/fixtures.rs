@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use sui_sdk::types::base_types::{
+    ObjectID,
+    TransactionDigest,
+};
+
+use crate::types::{
+    CheckpointData,
+    ResultData,
+};
+
+pub fn obj(id: &str) -> ObjectID {
+    ObjectID::from_str(id).unwrap()
+}
+
+pub fn tx(n: u8) -> TransactionDigest {
+    TransactionDigest::new([n; 32])
+}
+
+// TXs touching every shared object of a checkpoint, by mutable reference or not
+pub fn checkpoint(num_txs_total: usize, num_txs_touching_shared_objs: usize,
+        shared_objects: &[(&str, &[(u8, bool)])]) -> CheckpointData {
+    CheckpointData {
+        num_txs_total,
+        num_txs_touching_shared_objs,
+        timestamp_ms: None,
+        shared_objects: shared_objects
+            .iter()
+            .map(|(id, txs)| (obj(id), txs.iter().map(|(n, mut_ref)| (tx(*n), *mut_ref)).collect()))
+            .collect(),
+        senders: BTreeMap::new(),
+    }
+}
+
+// Shared objects touched (not by mutable reference) by every TX of a checkpoint
+pub fn checkpoint_of_txs(txs: &[(u8, &[&str])]) -> CheckpointData {
+    let mut checkpoint_data = checkpoint(txs.len(), txs.len(), &[]);
+    for (n, objects) in txs.iter() {
+        for id in objects.iter() {
+            checkpoint_data.shared_objects
+                .entry(obj(id))
+                .or_default()
+                .insert(tx(*n), false);
+        }
+    }
+    checkpoint_data
+}
+
+// Complete epoch data of Mainnet with consecutive checkpoints from `start`
+pub fn result_data(epoch: usize, start: usize, checkpoints: Vec<CheckpointData>) -> ResultData {
+    let num_txs: usize = checkpoints.iter().map(|data| data.num_txs_total).sum();
+    ResultData {
+        network: String::from("Mainnet"),
+        version: String::from("1.24.0"),
+        epoch,
+        start_checkpoint: start,
+        end_checkpoint: start + checkpoints.len() - 1,
+        last_cursor: String::from("cursor"),
+        num_txs_in_epoch: num_txs,
+        num_txs_scanned: num_txs,
+        num_txs_touching_0_shared_objs: 0,
+        num_txs_touching_0_objs: 0,
+        pruned: false,
+        queried_at_ms: None,
+        checkpoints: (start as u64..).zip(checkpoints).collect(),
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
 use sui_sdk::rpc_types::SuiCallArg;
 use sui_sdk::rpc_types::SuiObjectArg;
 use sui_sdk::rpc_types::SuiTransactionBlock;
@@ -6,6 +9,9 @@ use sui_sdk::rpc_types::SuiTransactionBlockKind;
 
 use crate::types::{TxInfo, SharedObjInfo};
 
+#[cfg(test)]
+pub mod fixtures;
+
 
 // print type of variable
 pub fn print_type_of<T>(_: &T) {
@@ -53,19 +59,21 @@ pub fn process_tx_inputs(tx_block: &Option<SuiTransactionBlock>) -> TxInfo {
                 if let SuiObjectArg::SharedObject{object_id, mutable, ..} = obj {
                     count = count + 1;
                     shared_objects.push(SharedObjInfo {
-                        id: object_id.to_string(),
+                        id: *object_id,
                         mutable: *mutable
                     })
                 }
             }
         }
         return TxInfo {
+            sender: tx_data_v1.sender,
             num_total: prog_tx.inputs.len(),
             num_shared: count,
             shared_objects: shared_objects
         };
     }
     TxInfo {
+        sender: tx_data_v1.sender,
         num_total: 0,
         num_shared: 0,
         shared_objects: Vec::new()
@@ -90,3 +98,143 @@ pub fn get_imm_or_owned_input_objects(tx_block: &Option<SuiTransactionBlock>) ->
     }
     Vec::new()
 }
+
+
+// Maps values (e.g., object IDs) to compact, consecutive u32 indices,
+// so that sets and maps of them are cheaper to store and hash. Indices of
+// values released by `retain` are reused for values seen later
+#[derive(Debug, Default)]
+pub struct Interner<T> {
+    indices: HashMap<T, u32>,
+    values: Vec<Option<T>>,
+    free: Vec<u32>,
+}
+
+impl<T: Hash + Eq + Clone> Interner<T> {
+    pub fn new() -> Self {
+        Interner {
+            indices: HashMap::new(),
+            values: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    // Return the index of a value, assigning a new one if it is seen first time
+    pub fn intern(&mut self, value: &T) -> u32 {
+        if let Some(index) = self.indices.get(value) {
+            return *index;
+        }
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.values[index as usize] = Some(value.clone());
+                index
+            },
+            None => {
+                self.values.push(Some(value.clone()));
+                self.values.len() as u32 - 1
+            },
+        };
+        self.indices.insert(value.clone(), index);
+        index
+    }
+
+    // Return the value for a given index
+    pub fn value(&self, index: u32) -> &T {
+        self.values[index as usize].as_ref().expect("index of a released value")
+    }
+
+    // Values interned (and not released), in no particular order
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.indices.keys()
+    }
+
+    // Release the values whose indices are not kept, e.g., once nothing
+    // refers to them anymore
+    pub fn retain(&mut self, keep: impl Fn(u32) -> bool) {
+        let values = &mut self.values;
+        let free = &mut self.free;
+        self.indices.retain(|_, index| {
+            if keep(*index) {
+                return true;
+            }
+            values[*index as usize] = None;
+            free.push(*index);
+            false
+        });
+    }
+
+    // Number of values interned (and not released)
+    pub fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.indices.is_empty()
+    }
+}
+
+// Disjoint sets of consecutive indices (union-find), e.g., to find connected
+// components of a graph
+#[derive(Debug)]
+pub struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    pub fn new(len: usize) -> Self {
+        DisjointSets {
+            parents: (0..len).collect(),
+        }
+    }
+
+    // Return the representative of the set containing an index
+    pub fn find(&mut self, index: usize) -> usize {
+        let mut root = index;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        // compress the path for later lookups
+        let mut index = index;
+        while self.parents[index] != root {
+            index = std::mem::replace(&mut self.parents[index], root);
+        }
+        root
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.parents[a] = b;
+    }
+
+    // Return the number of disjoint sets
+    pub fn count(&mut self) -> usize {
+        (0..self.parents.len()).filter(|index| self.find(*index) == *index).count()
+    }
+}
+
+// Whether a text matches a pattern in which `*` stands for any (possibly
+// empty) sequence of characters, e.g., "pool.*" matches "pool.Pool"
+pub fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let (pattern, text): (Vec<char>, Vec<char>) = (pattern.chars().collect(), text.chars().collect());
+    // position in the pattern after the last `*`, and in the text where
+    // matching it resumed
+    let mut backtrack: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p + 1, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // let the last `*` match one more character
+            backtrack = Some((star_p, star_t + 1));
+            p = star_p;
+            t = star_t + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
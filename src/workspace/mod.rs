@@ -0,0 +1,491 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::RangeBounds;
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::time::UNIX_EPOCH;
+
+use anyhow::{
+    bail,
+    Context,
+};
+use serde::de::DeserializeOwned;
+
+use crate::consts::{
+    DATA_DIR,
+    SUI_SDK_VERSION,
+    WORKSPACE_MANIFEST_FILENAME,
+};
+use crate::types::{
+    EpochFile,
+    EpochHeader,
+    PrunedResultData,
+    ResultData,
+    WorkspaceEpoch,
+    WorkspaceManifest,
+};
+
+pub mod source;
+
+// A workspace is a directory in the "data" folder with epoch data files,
+// one file per epoch, described by a manifest file (`workspace.json`).
+// Epoch data files are identified by their headers, not by their names,
+// and all of them must come from the same network.
+#[derive(Debug)]
+pub struct Workspace {
+    pub name: String,
+    pub manifest: WorkspaceManifest,
+    dir: PathBuf,
+}
+
+// Result of merging an epoch into a workspace
+#[derive(Debug)]
+pub struct MergedEpoch {
+    pub epoch: usize,
+    // workspace the epoch data file was taken from
+    pub source: String,
+    // all workspaces containing that epoch
+    pub candidates: Vec<String>,
+}
+
+// Ordering used to choose between epoch data files for the same epoch:
+// complete first, then more scanned TXs, then not pruned, then the newer one.
+// Epoch data files queried before query times were recorded are as new as
+// their modification time, which copies between workspaces keep
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct EpochPreference {
+    complete: bool,
+    num_txs_scanned: usize,
+    full: bool,
+    queried_at_ms: u64,
+}
+
+impl EpochPreference {
+    fn new(entry: &WorkspaceEpoch) -> Self {
+        EpochPreference {
+            complete: entry.complete,
+            num_txs_scanned: entry.num_txs_scanned,
+            full: !entry.pruned,
+            queried_at_ms: entry.queried_at_ms.unwrap_or(entry.modified_ms),
+        }
+    }
+}
+
+// Size and modification time (in ms since the Unix epoch) of a file
+fn file_stamp(path: &Path) -> anyhow::Result<(u64, u64)> {
+    let metadata = fs::metadata(path).with_context(|| format!("Couldn't access file {:?}", path))?;
+    let modified_ms = metadata.modified()?
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |modified| modified.as_millis() as u64);
+    Ok((metadata.len(), modified_ms))
+}
+
+// Name of the epoch data file for a given epoch and its boundaries,
+// for example, `epoch=021_1584197-1668109.json`
+pub fn epoch_file_name(epoch: usize, start_checkpoint: usize, end_checkpoint: usize) -> String {
+    format!("epoch={:0>3}_{}-{}.json", epoch, start_checkpoint, end_checkpoint)
+}
+
+// Whether a file is named like an epoch data file, see `epoch_file_name`
+fn is_epoch_file_name(file_name: &str) -> bool {
+    let Some(name) = file_name.strip_prefix("epoch=").and_then(|name| name.strip_suffix(".json")) else {
+        return false;
+    };
+    let Some((epoch, checkpoints)) = name.split_once('_') else {
+        return false;
+    };
+    let Some((start_checkpoint, end_checkpoint)) = checkpoints.split_once('-') else {
+        return false;
+    };
+    [epoch, start_checkpoint, end_checkpoint]
+        .iter()
+        .all(|number| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+}
+
+// Read a JSON file by memory-mapping it
+pub fn read_json<T: DeserializeOwned, P: AsRef<Path>>(path: P) -> anyhow::Result<T> {
+    let file = fs::File::open(path.as_ref())
+        .with_context(|| format!("Couldn't open file {:?}", path.as_ref()))?;
+    let mmap = unsafe {memmap::Mmap::map(&file)}?;
+    let content = std::str::from_utf8(&mmap)?;
+    serde_json::from_str(content)
+        .with_context(|| format!("File {:?} was not properly formatted", path.as_ref()))
+}
+
+impl<C> From<&ResultData<C>> for EpochHeader {
+    fn from(result: &ResultData<C>) -> Self {
+        EpochHeader {
+            network: result.network.clone(),
+            version: result.version.clone(),
+            epoch: result.epoch,
+            start_checkpoint: result.start_checkpoint,
+            end_checkpoint: result.end_checkpoint,
+            last_cursor: result.last_cursor.clone(),
+            num_txs_in_epoch: result.num_txs_in_epoch,
+            num_txs_scanned: result.num_txs_scanned,
+            num_txs_touching_0_shared_objs: result.num_txs_touching_0_shared_objs,
+            num_txs_touching_0_objs: result.num_txs_touching_0_objs,
+            pruned: result.pruned,
+            queried_at_ms: result.queried_at_ms,
+        }
+    }
+}
+
+impl From<&EpochFile> for EpochHeader {
+    fn from(epoch_file: &EpochFile) -> Self {
+        match epoch_file {
+            EpochFile::Full(result) => EpochHeader::from(result),
+            EpochFile::Pruned(result) => EpochHeader::from(result),
+        }
+    }
+}
+
+impl EpochHeader {
+    // whether all TXs of the epoch were scanned
+    pub fn is_complete(&self) -> bool {
+        self.num_txs_scanned == self.num_txs_in_epoch
+    }
+}
+
+impl Workspace {
+    // Open an existing workspace. If the manifest does not exist yet
+    // (e.g., for workspaces created before manifests were introduced),
+    // it is built from the headers of the epoch data files. The manifest
+    // is only brought up to date in memory; it is written back by commands
+    // changing the workspace (see `save`).
+    pub fn open(name: &str) -> anyhow::Result<Self> {
+        let dir = Path::new(DATA_DIR).join(name);
+        if !dir.is_dir() {
+            bail!("Workspace \"{}\" does not exist", dir.display());
+        }
+        Self::open_dir(name, dir, None)
+    }
+
+    // Open a workspace for storing epoch data files queried from `network`,
+    // creating it if it does not exist yet
+    pub fn open_or_create(name: &str, network: &str) -> anyhow::Result<Self> {
+        let dir = Path::new(DATA_DIR).join(name);
+        fs::create_dir_all(&dir)?;
+        let mut workspace = Self::open_dir(name, dir, Some(network))?;
+        workspace.manifest.sdk_version = SUI_SDK_VERSION.to_string();
+        workspace.save()?;
+        Ok(workspace)
+    }
+
+    fn open_dir(name: &str, dir: PathBuf, network: Option<&str>) -> anyhow::Result<Self> {
+        let manifest_file = dir.join(WORKSPACE_MANIFEST_FILENAME);
+        let manifest = if manifest_file.exists() {
+            read_json(&manifest_file)?
+        } else {
+            WorkspaceManifest {
+                network: network.unwrap_or_default().to_string(),
+                sdk_version: SUI_SDK_VERSION.to_string(),
+                epochs: BTreeMap::new(),
+            }
+        };
+        if let Some(network) = network {
+            if !manifest.network.is_empty() && manifest.network != network {
+                bail!("Workspace \"{}\" contains {} data, refusing to add {} data",
+                    dir.display(), manifest.network, network);
+            }
+        }
+
+        let mut workspace = Workspace {
+            name: name.to_string(),
+            manifest,
+            dir,
+        };
+        workspace.sync()?;
+        Ok(workspace)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    // Make the manifest reflect the epoch data files in the workspace directory:
+    // read headers of files not in the manifest yet or changed since (by
+    // size or modification time, e.g., queried further until complete) and
+    // drop entries of files that no longer exist. Files not named like epoch
+    // data files (e.g., notes or exported results) are left alone
+    pub fn sync(&mut self) -> anyhow::Result<()> {
+        let mut known_files: BTreeMap<String, usize> = self.manifest
+            .epochs
+            .iter()
+            .map(|(epoch, entry)| (entry.file.clone(), *epoch))
+            .collect();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let file_name = match path.file_name().and_then(|f| f.to_str()) {
+                Some(file_name) => file_name.to_string(),
+                None => continue,
+            };
+            if !is_epoch_file_name(&file_name) || !path.is_file() {
+                continue;
+            }
+            if let Some(epoch) = known_files.remove(&file_name) {
+                let entry = &self.manifest.epochs[&epoch];
+                if (entry.size, entry.modified_ms) == file_stamp(&path)? {
+                    continue;
+                }
+                self.manifest.epochs.remove(&epoch);
+            }
+            let header: EpochHeader = read_json(&path)?;
+            self.add_epoch(&file_name, &header)?;
+        }
+
+        // remaining files are listed in the manifest but missing on disk
+        for (_, epoch) in known_files {
+            self.manifest.epochs.remove(&epoch);
+        }
+        Ok(())
+    }
+
+    // Rebuild the manifest from scratch by reading headers of all
+    // epoch data files in the workspace directory
+    pub fn rescan(&mut self) -> anyhow::Result<()> {
+        self.manifest.epochs.clear();
+        self.manifest.network.clear();
+        self.sync()
+    }
+
+    // Record an epoch data file (already stored in the workspace directory)
+    // in the manifest, refusing data from another network or another epoch
+    // data file for the same epoch
+    pub fn add_epoch(&mut self, file: &str, header: &EpochHeader) -> anyhow::Result<()> {
+        if self.manifest.network.is_empty() {
+            self.manifest.network = header.network.clone();
+        } else if self.manifest.network != header.network {
+            bail!("Epoch data file \"{}\" contains {} data, but workspace \"{}\" contains {} data",
+                file, header.network, self.dir.display(), self.manifest.network);
+        }
+        if let Some(entry) = self.manifest.epochs.get(&header.epoch) {
+            if entry.file != file {
+                bail!("Epoch {} is stored in both \"{}\" and \"{}\" in workspace \"{}\"",
+                    header.epoch, entry.file, file, self.dir.display());
+            }
+        }
+
+        let (size, modified_ms) = file_stamp(&self.dir.join(file))?;
+        self.manifest.epochs.insert(header.epoch, WorkspaceEpoch {
+            file: file.to_string(),
+            version: header.version.clone(),
+            start_checkpoint: header.start_checkpoint,
+            end_checkpoint: header.end_checkpoint,
+            num_txs_in_epoch: header.num_txs_in_epoch,
+            num_txs_scanned: header.num_txs_scanned,
+            complete: header.is_complete(),
+            pruned: header.pruned,
+            queried_at_ms: header.queried_at_ms,
+            size,
+            modified_ms,
+        });
+        Ok(())
+    }
+
+    // Save the manifest to disk
+    pub fn save(&self) -> anyhow::Result<()> {
+        fs::write(
+            self.dir.join(WORKSPACE_MANIFEST_FILENAME),
+            serde_json::to_string_pretty(&self.manifest)?,
+        )?;
+        Ok(())
+    }
+
+    // Epochs in the workspace in ascending order
+    pub fn epochs(&self) -> Vec<usize> {
+        self.manifest.epochs.keys().copied().collect()
+    }
+
+    // Path to the epoch data file of a given epoch
+    pub fn epoch_path(&self, epoch: usize) -> Option<PathBuf> {
+        self.manifest
+            .epochs
+            .get(&epoch)
+            .map(|entry| self.dir.join(&entry.file))
+    }
+
+    // Read the header of the epoch data file of a given epoch
+    pub fn read_header(&self, epoch: usize) -> anyhow::Result<EpochHeader> {
+        let path = self.epoch_path(epoch)
+            .with_context(|| format!("Epoch {} not found in workspace \"{}\"", epoch, self.dir.display()))?;
+        let header: EpochHeader = read_json(&path)?;
+        self.validate(epoch, &header)?;
+        Ok(header)
+    }
+
+    // Read the epoch data file of a given epoch, which must not be pruned
+    pub fn open_epoch(&self, epoch: usize) -> anyhow::Result<ResultData> {
+        match self.open_epoch_file(epoch)? {
+            EpochFile::Full(result) => Ok(result),
+            EpochFile::Pruned(_) => bail!("Epoch {} is stored pruned in workspace \"{}\"",
+                epoch, self.dir.display()),
+        }
+    }
+
+    // Read the epoch data file of a given epoch in either format
+    pub fn open_epoch_file(&self, epoch: usize) -> anyhow::Result<EpochFile> {
+        let path = self.epoch_path(epoch)
+            .with_context(|| format!("Epoch {} not found in workspace \"{}\"", epoch, self.dir.display()))?;
+        let epoch_file = if self.manifest.epochs[&epoch].pruned {
+            let result: PrunedResultData = read_json(&path)?;
+            self.validate(epoch, &EpochHeader::from(&result))?;
+            EpochFile::Pruned(result)
+        } else {
+            let result: ResultData = read_json(&path)?;
+            self.validate(epoch, &EpochHeader::from(&result))?;
+            EpochFile::Full(result)
+        };
+        Ok(epoch_file)
+    }
+
+    // Replace the epoch data file of a given epoch by its pruned version.
+    // Returns false if it was pruned already
+    pub fn prune_epoch(&mut self, epoch: usize) -> anyhow::Result<bool> {
+        let result = match self.open_epoch_file(epoch)? {
+            EpochFile::Full(result) => result.prune(),
+            EpochFile::Pruned(_) => return Ok(false),
+        };
+        let file = self.manifest.epochs[&epoch].file.clone();
+        fs::write(self.dir.join(&file), serde_json::to_string_pretty(&result)?)?;
+        self.add_epoch(&file, &EpochHeader::from(&result))?;
+        self.save()?;
+        Ok(true)
+    }
+
+    // Merge epoch data files of the given workspaces into this workspace.
+    // For epochs present in more than one workspace (including this one),
+    // the complete epoch data file is preferred, then the one with more
+    // scanned TXs, then the full one, then the newer one (see
+    // `EpochPreference`); exact ties keep the epoch data file of this
+    // workspace, or else take the one of the first source.
+    pub fn merge(&mut self, sources: &[Workspace]) -> anyhow::Result<Vec<MergedEpoch>> {
+        self.merge_range(sources, ..)
+    }
+
+    // Extract a range of epochs from this workspace into another workspace
+    pub fn split<R: RangeBounds<usize>>(&self, workspace: &mut Workspace, epochs: R)
+            -> anyhow::Result<Vec<MergedEpoch>> {
+        workspace.merge_range(std::slice::from_ref(self), epochs)
+    }
+
+    // Merge epochs within a range, see `merge`
+    pub fn merge_range<R: RangeBounds<usize>>(&mut self, sources: &[Workspace], epochs: R)
+            -> anyhow::Result<Vec<MergedEpoch>> {
+        for source in sources {
+            if source.dir == self.dir {
+                bail!("Cannot merge workspace \"{}\" into itself", self.dir.display());
+            }
+        }
+        // all workspaces with data, this one included, must be of the same network
+        let mut networks = std::iter::once(&*self)
+            .chain(sources)
+            .filter(|workspace| !workspace.manifest.network.is_empty());
+        if let Some(first) = networks.next() {
+            for workspace in networks {
+                if workspace.manifest.network != first.manifest.network {
+                    bail!("Workspace \"{}\" contains {} data, but workspace \"{}\" contains {} data",
+                        workspace.dir.display(), workspace.manifest.network,
+                        first.dir.display(), first.manifest.network);
+                }
+            }
+        }
+
+        // for every epoch, find the workspace with the preferred epoch data file;
+        // `None` stands for this workspace
+        let mut chosen: BTreeMap<usize, (Option<usize>, EpochPreference, Vec<String>)> = BTreeMap::new();
+        for (epoch, entry) in self.manifest.epochs.range((epochs.start_bound(), epochs.end_bound())) {
+            let preference = EpochPreference::new(entry);
+            chosen.insert(*epoch, (None, preference, vec![self.name.clone()]));
+        }
+        for (k, source) in sources.iter().enumerate() {
+            for (epoch, entry) in source.manifest.epochs.range((epochs.start_bound(), epochs.end_bound())) {
+                let preference = EpochPreference::new(entry);
+                match chosen.get_mut(epoch) {
+                    Some((best, best_preference, candidates)) => {
+                        candidates.push(source.name.clone());
+                        if preference > *best_preference {
+                            *best = Some(k);
+                            *best_preference = preference;
+                        }
+                    },
+                    None => {
+                        chosen.insert(*epoch, (Some(k), preference, vec![source.name.clone()]));
+                    },
+                }
+            }
+        }
+
+        let mut merged = Vec::new();
+        for (epoch, (best, _, candidates)) in chosen.into_iter() {
+            let source = match best {
+                Some(k) => {
+                    self.copy_epoch_from(&sources[k], epoch)?;
+                    sources[k].name.clone()
+                },
+                None => self.name.clone(),
+            };
+            merged.push(MergedEpoch {
+                epoch,
+                source,
+                candidates,
+            });
+        }
+        self.save()?;
+        Ok(merged)
+    }
+
+    // Copy the epoch data file of a given epoch from another workspace,
+    // replacing the epoch data file for that epoch in this workspace, if any.
+    // The copy keeps the modification time of the original
+    fn copy_epoch_from(&mut self, source: &Workspace, epoch: usize) -> anyhow::Result<()> {
+        let entry = source.manifest.epochs
+            .get(&epoch)
+            .with_context(|| format!("Epoch {} not found in workspace \"{}\"", epoch, source.dir.display()))?;
+        // the file itself may not agree with the manifest of its workspace
+        let header = source.read_header(epoch)?;
+        if self.manifest.network.is_empty() {
+            self.manifest.network = source.manifest.network.clone();
+        }
+        self.validate(epoch, &header)?;
+
+        if let Some(previous) = self.manifest.epochs.get(&epoch) {
+            if previous.file != entry.file {
+                fs::remove_file(self.dir.join(&previous.file))?;
+            }
+        }
+        let original = source.dir.join(&entry.file);
+        let copy = self.dir.join(&entry.file);
+        fs::copy(&original, &copy)?;
+        fs::File::options()
+            .write(true)
+            .open(&copy)?
+            .set_modified(fs::metadata(&original)?.modified()?)?;
+
+        let (size, modified_ms) = file_stamp(&copy)?;
+        self.manifest.epochs.insert(epoch, WorkspaceEpoch {
+            size,
+            modified_ms,
+            ..entry.clone()
+        });
+        Ok(())
+    }
+
+    // Check that the header of an epoch data file agrees with the manifest
+    fn validate(&self, epoch: usize, header: &EpochHeader) -> anyhow::Result<()> {
+        if header.network != self.manifest.network {
+            bail!("Epoch {} contains {} data, but workspace \"{}\" contains {} data",
+                epoch, header.network, self.dir.display(), self.manifest.network);
+        }
+        if header.epoch != epoch {
+            bail!("Epoch data file for epoch {} contains data for epoch {}", epoch, header.epoch);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,70 @@
+use std::ops::RangeBounds;
+
+use crate::db::{
+    db_path,
+    Database,
+};
+use crate::types::ResultData;
+use crate::workspace::Workspace;
+
+// Where the epoch data of a workspace is read from: its epoch data files, or
+// its SQLite database (see `import`)
+pub enum EpochSource {
+    Files(Workspace),
+    Database(Database),
+}
+
+// Epochs to analyze, and why others were skipped
+#[derive(Debug, Default)]
+pub struct EpochSelection {
+    pub epochs: Vec<usize>,
+    pub warnings: Vec<String>,
+}
+
+impl EpochSource {
+    pub fn open(workspace: &str, sqlite: bool) -> anyhow::Result<Self> {
+        if sqlite {
+            Ok(EpochSource::Database(Database::open(db_path(workspace))?))
+        } else {
+            Ok(EpochSource::Files(Workspace::open(workspace)?))
+        }
+    }
+
+    // Epochs within a range with complete epoch data including TX digests,
+    // as needed to know which TXs touch which objects together; incomplete
+    // and pruned epochs are skipped
+    pub fn complete_epochs<R: RangeBounds<usize>>(&self, range: R) -> anyhow::Result<EpochSelection> {
+        let mut selection = EpochSelection::default();
+        match self {
+            EpochSource::Database(db) => {
+                for epoch in db.epochs()?.into_iter().filter(|epoch| range.contains(epoch)) {
+                    if db.read_header(epoch)?.is_complete() {
+                        selection.epochs.push(epoch);
+                    } else {
+                        selection.warnings.push(format!("Skipping incomplete epoch data for epoch {}", epoch));
+                    }
+                }
+            },
+            EpochSource::Files(workspace) => {
+                for (epoch, entry) in workspace.manifest.epochs.range((range.start_bound(), range.end_bound())) {
+                    if !entry.complete {
+                        selection.warnings.push(format!("Skipping incomplete epoch data file for epoch {}", epoch));
+                    } else if entry.pruned {
+                        selection.warnings.push(format!("Skipping pruned epoch data file for epoch {}", epoch));
+                    } else {
+                        selection.epochs.push(*epoch);
+                    }
+                }
+            },
+        }
+        Ok(selection)
+    }
+
+    // Read the (full) epoch data of a given epoch
+    pub fn read_epoch(&self, epoch: usize) -> anyhow::Result<ResultData> {
+        match self {
+            EpochSource::Database(db) => db.read_epoch(epoch),
+            EpochSource::Files(workspace) => workspace.open_epoch(epoch),
+        }
+    }
+}
@@ -0,0 +1,262 @@
+use std::fs;
+use std::path::{
+    Path,
+    PathBuf,
+};
+
+use serde::Serialize;
+
+use super::{
+    epoch_file_name,
+    file_stamp,
+    read_json,
+    MergedEpoch,
+    Workspace,
+};
+use crate::types::{
+    EpochFile,
+    PrunedResultData,
+    ResultData,
+};
+use crate::utils::fixtures::{
+    checkpoint,
+    result_data,
+};
+
+// Empty directory for a workspace, unique to this test run
+fn workspace_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("workspace-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn open(dir: &Path) -> anyhow::Result<Workspace> {
+    let name = dir.file_name().unwrap().to_str().unwrap();
+    Workspace::open_dir(name, dir.to_path_buf(), None)
+}
+
+// Store epoch data in a workspace directory as `query-txs` does
+fn write_epoch<C: Serialize>(dir: &Path, result: &ResultData<C>) -> String {
+    let file = epoch_file_name(result.epoch, result.start_checkpoint, result.end_checkpoint);
+    fs::write(dir.join(&file), serde_json::to_string_pretty(result).unwrap()).unwrap();
+    file
+}
+
+// Epoch spanning checkpoints `10 * epoch..10 * epoch + num_checkpoints`
+// with one TX touching a shared object per checkpoint
+fn epoch(epoch: usize, num_checkpoints: usize) -> ResultData {
+    let checkpoints = (0..num_checkpoints).map(|k| checkpoint(1, 1, &[("0xa", &[(k as u8, true)])])).collect();
+    result_data(epoch, 10 * epoch, checkpoints)
+}
+
+// Epoch scanned up to its first checkpoint only
+fn incomplete_epoch(epoch_number: usize, num_checkpoints: usize) -> ResultData {
+    let mut result = epoch(epoch_number, num_checkpoints);
+    result.checkpoints.retain(|checkpoint, _| *checkpoint == result.start_checkpoint as u64);
+    result.num_txs_scanned = 1;
+    result
+}
+
+#[test]
+fn sync_rereads_changed_files() {
+    let dir = workspace_dir("changed");
+    let file = write_epoch(&dir, &incomplete_epoch(0, 3));
+    let mut workspace = open(&dir).unwrap();
+    assert!(!workspace.manifest.epochs[&0].complete);
+
+    // queried further until complete, under the same name
+    assert_eq!(write_epoch(&dir, &epoch(0, 3)), file);
+    workspace.sync().unwrap();
+    let entry = &workspace.manifest.epochs[&0];
+    assert!(entry.complete);
+    assert_eq!(entry.num_txs_scanned, 3);
+    assert_eq!(entry.size, fs::metadata(dir.join(&file)).unwrap().len());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sync_drops_deleted_files() {
+    let dir = workspace_dir("deleted");
+    write_epoch(&dir, &epoch(0, 2));
+    let file = write_epoch(&dir, &epoch(1, 2));
+    let mut workspace = open(&dir).unwrap();
+    workspace.save().unwrap();
+    assert_eq!(workspace.epochs(), vec![0, 1]);
+
+    fs::remove_file(dir.join(file)).unwrap();
+    workspace.sync().unwrap();
+    assert_eq!(workspace.epochs(), vec![0]);
+    // also when reopening from the saved manifest
+    assert_eq!(open(&dir).unwrap().epochs(), vec![0]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sync_skips_files_not_named_like_epoch_data_files() {
+    let dir = workspace_dir("stray");
+    write_epoch(&dir, &epoch(0, 2));
+    fs::write(dir.join("notes.json"), "{\"todo\": []}").unwrap();
+    fs::write(dir.join("epoch=001_10-11.json.bak"), "").unwrap();
+    fs::write(dir.join("epoch=x_1-2.json"), "").unwrap();
+    fs::create_dir(dir.join("epoch=002_20-21.json")).unwrap();
+
+    let workspace = open(&dir).unwrap();
+    assert_eq!(workspace.epochs(), vec![0]);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn sync_refuses_other_network() {
+    let dir = workspace_dir("network");
+    write_epoch(&dir, &epoch(0, 2));
+    let mut workspace = open(&dir).unwrap();
+
+    let mut testnet = epoch(1, 2);
+    testnet.network = String::from("Testnet");
+    write_epoch(&dir, &testnet);
+    let error = workspace.sync().unwrap_err().to_string();
+    assert!(error.contains("Testnet"), "{}", error);
+    assert!(open(&dir).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+// Epochs merged, with the workspaces they were taken from
+fn sources(merged: &[MergedEpoch]) -> Vec<(usize, &str)> {
+    merged.iter().map(|merged| (merged.epoch, merged.source.as_str())).collect()
+}
+
+#[test]
+fn merge_prefers_complete_then_more_scanned_then_full() {
+    let (dir, dir_a, dir_b) = (workspace_dir("merged"), workspace_dir("a"), workspace_dir("b"));
+    // epoch 0: incomplete here, complete but pruned in A, complete in B
+    write_epoch(&dir, &incomplete_epoch(0, 3));
+    write_epoch(&dir_a, &epoch(0, 3).prune());
+    write_epoch(&dir_b, &epoch(0, 3));
+    // epoch 1: 2 of 3 TXs scanned in A, 1 of 3 in B
+    let mut scanned_more = incomplete_epoch(1, 3);
+    scanned_more.num_txs_scanned = 2;
+    write_epoch(&dir_a, &scanned_more);
+    write_epoch(&dir_b, &incomplete_epoch(1, 3));
+    // epoch 2: only here, epoch 3: only in B
+    write_epoch(&dir, &epoch(2, 1));
+    write_epoch(&dir_b, &epoch(3, 1));
+
+    let mut workspace = open(&dir).unwrap();
+    let (a, b) = (open(&dir_a).unwrap(), open(&dir_b).unwrap());
+    let names = [workspace.name.clone(), a.name.clone(), b.name.clone()];
+    let merged = workspace.merge(&[a, b]).unwrap();
+    let [name, name_a, name_b] = names.each_ref().map(String::as_str);
+    assert_eq!(sources(&merged), vec![(0, name_b), (1, name_a), (2, name), (3, name_b)]);
+    assert_eq!(merged[0].candidates.len(), 3);
+    assert_eq!(merged[3].candidates.len(), 1);
+
+    // the manifest describes the copies and is saved
+    for workspace in [&workspace, &open(&dir).unwrap()] {
+        assert_eq!(workspace.epochs(), vec![0, 1, 2, 3]);
+        let entry = &workspace.manifest.epochs[&0];
+        assert!(entry.complete && !entry.pruned);
+        assert_eq!(entry.size, fs::metadata(dir.join(&entry.file)).unwrap().len());
+        assert_eq!(workspace.manifest.epochs[&1].num_txs_scanned, 2);
+    }
+    assert_eq!(workspace.open_epoch(0).unwrap(), epoch(0, 3));
+    for dir in [dir, dir_a, dir_b] {
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[test]
+fn merge_prefers_newer_query() {
+    let (dir, dir_a, dir_b) = (workspace_dir("newer"), workspace_dir("older"), workspace_dir("newest"));
+    let queried_at = |queried_at_ms| {
+        let mut result = epoch(0, 2);
+        result.queried_at_ms = Some(queried_at_ms);
+        result
+    };
+    write_epoch(&dir, &queried_at(2));
+    write_epoch(&dir_a, &queried_at(1));
+    write_epoch(&dir_b, &queried_at(3));
+
+    let mut workspace = open(&dir).unwrap();
+    let (a, b) = (open(&dir_a).unwrap(), open(&dir_b).unwrap());
+    let name_b = b.name.clone();
+    let merged = workspace.merge(&[a, b]).unwrap();
+    assert_eq!(sources(&merged), vec![(0, name_b.as_str())]);
+    assert_eq!(workspace.manifest.epochs[&0].queried_at_ms, Some(3));
+    assert_eq!(workspace.open_epoch(0).unwrap().queried_at_ms, Some(3));
+    for dir in [dir, dir_a, dir_b] {
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[test]
+fn split_copies_range_of_epochs() {
+    let (dir, dir_split) = (workspace_dir("whole"), workspace_dir("split"));
+    for k in 0..4 {
+        write_epoch(&dir, &epoch(k, 2));
+    }
+    let source = open(&dir).unwrap();
+    let mut workspace = open(&dir_split).unwrap();
+    let merged = source.split(&mut workspace, 1..=2).unwrap();
+    assert_eq!(sources(&merged), vec![(1, source.name.as_str()), (2, source.name.as_str())]);
+
+    let workspace = open(&dir_split).unwrap();
+    assert_eq!(workspace.manifest.network, "Mainnet");
+    assert_eq!(workspace.epochs(), vec![1, 2]);
+    assert_eq!(workspace.open_epoch(2).unwrap(), epoch(2, 2));
+    // copies are as new as the originals
+    assert_eq!(workspace.manifest.epochs[&1].modified_ms, source.manifest.epochs[&1].modified_ms);
+    assert_eq!(open(&dir).unwrap().epochs(), vec![0, 1, 2, 3]);
+    for dir in [dir, dir_split] {
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[test]
+fn merge_refuses_to_mix_networks() {
+    let (dir, dir_a, dir_b) = (workspace_dir("mixed"), workspace_dir("mainnet"), workspace_dir("testnet"));
+    write_epoch(&dir_a, &epoch(0, 2));
+    let mut testnet = epoch(1, 2);
+    testnet.network = String::from("Testnet");
+    write_epoch(&dir_b, &testnet);
+
+    // sources disagree even though this workspace is empty
+    let mut workspace = open(&dir).unwrap();
+    let error = workspace.merge(&[open(&dir_a).unwrap(), open(&dir_b).unwrap()]).unwrap_err();
+    assert!(error.to_string().contains("Testnet"), "{}", error);
+    assert!(workspace.epochs().is_empty());
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+
+    // and a source disagrees with this workspace
+    let mut workspace = open(&dir_a).unwrap();
+    assert!(workspace.merge(&[open(&dir_b).unwrap()]).is_err());
+    assert_eq!(workspace.epochs(), vec![0]);
+    for dir in [dir, dir_a, dir_b] {
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
+
+#[test]
+fn prune_epoch_rewrites_file_and_manifest() {
+    let dir = workspace_dir("pruned");
+    let file = write_epoch(&dir, &epoch(0, 3));
+    let mut workspace = open(&dir).unwrap();
+    let full_size = workspace.manifest.epochs[&0].size;
+
+    assert!(workspace.prune_epoch(0).unwrap());
+    let pruned: PrunedResultData = read_json(dir.join(&file)).unwrap();
+    assert_eq!(pruned, epoch(0, 3).prune());
+
+    // the manifest is saved with the new flag and stamp of the file
+    for workspace in [&workspace, &open(&dir).unwrap()] {
+        let entry = &workspace.manifest.epochs[&0];
+        assert!(entry.pruned && entry.complete);
+        assert_eq!(entry.file, file);
+        assert_eq!((entry.size, entry.modified_ms), file_stamp(&dir.join(&file)).unwrap());
+        assert_ne!(entry.size, full_size);
+    }
+    assert!(matches!(workspace.open_epoch_file(0).unwrap(), EpochFile::Pruned(_)));
+    assert!(workspace.open_epoch(0).is_err());
+    assert!(!workspace.prune_epoch(0).unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+}